@@ -0,0 +1,1078 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+/// Which KDF `encrypt` should protect the secret with.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "lower")]
+pub enum KdfChoice {
+    Argon2id,
+    Scrypt,
+}
+
+/// Which finite field `split --format custom` should share the secret over.
+/// Both produce the same amount of share data for a given secret; `gf65536`
+/// only changes how many polynomial elements the math works over, batching
+/// two secret bytes per element instead of one. See `shamir::ShareEncoding`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[value(rename_all = "lower")]
+pub enum ShareEncodingArg {
+    #[default]
+    Gf256,
+    Gf65536,
+}
+
+/// Error correction level for `encrypt`/`split`'s QR codes, mirroring
+/// `qrcode::EcLevel`. Higher levels recover more damage (a scratched or
+/// engraved card) at the cost of needing a larger/denser code for the same
+/// payload; see `QRGenerator::estimate_capacity`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "upper")]
+pub enum EcLevelArg {
+    /// Recovers up to ~7% damage; the most capacity per QR version.
+    L,
+    /// Recovers up to ~15% damage. The default when this flag is omitted.
+    M,
+    /// Recovers up to ~25% damage.
+    Q,
+    /// Recovers up to ~30% damage; the least capacity per QR version.
+    H,
+}
+
+impl From<EcLevelArg> for qrcode::EcLevel {
+    fn from(level: EcLevelArg) -> Self {
+        match level {
+            EcLevelArg::L => qrcode::EcLevel::L,
+            EcLevelArg::M => qrcode::EcLevel::M,
+            EcLevelArg::Q => qrcode::EcLevel::Q,
+            EcLevelArg::H => qrcode::EcLevel::H,
+        }
+    }
+}
+
+/// How to draw each dark module of a generated QR code, mirroring
+/// `qrcrypt::qr::ModuleStyle`. `Dot`/`Rounded` are aimed at fiber laser
+/// engraving, where square modules with sharp corners blur together
+/// etched into metal; pair with `--fill-ratio` to control how much of
+/// each module's pitch actually gets marked.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[value(rename_all = "lower")]
+pub enum ModuleStyleArg {
+    #[default]
+    Square,
+    Dot,
+    Rounded,
+}
+
+impl From<ModuleStyleArg> for qrcrypt::qr::ModuleStyle {
+    fn from(style: ModuleStyleArg) -> Self {
+        match style {
+            ModuleStyleArg::Square => qrcrypt::qr::ModuleStyle::Square,
+            ModuleStyleArg::Dot => qrcrypt::qr::ModuleStyle::Dot,
+            ModuleStyleArg::Rounded => qrcrypt::qr::ModuleStyle::Rounded,
+        }
+    }
+}
+
+/// Page size `split --sheet` lays share cards out onto.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[value(rename_all = "lower")]
+pub enum PaperSizeArg {
+    #[default]
+    A4,
+    Letter,
+}
+
+impl From<PaperSizeArg> for qrcrypt::qr::PaperSize {
+    fn from(paper: PaperSizeArg) -> Self {
+        match paper {
+            PaperSizeArg::A4 => qrcrypt::qr::PaperSize::A4,
+            PaperSizeArg::Letter => qrcrypt::qr::PaperSize::Letter,
+        }
+    }
+}
+
+/// Which share format `split` should produce.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "lower")]
+pub enum ShareFormat {
+    /// QRCrypt's own base64/bincode `ShamirShare` format.
+    Custom,
+    /// SLIP-39 mnemonics, importable directly into compatible hardware
+    /// wallets. Only the single-group case is supported.
+    Slip39,
+    /// Plain-text "index-hexshare" lines, the format the classic Debian
+    /// `ssss` utility reads and writes. Only the single-group case is
+    /// supported; see `shamir::parse_ssss_share` for how far the
+    /// cross-tool compatibility actually goes.
+    Ssss,
+    /// Blockchain Commons SSKR, wrapped in a `ur:crypto-sskr/...` URI, for
+    /// import into Keystone/SeedSigner and other air-gapped wallets. Not yet
+    /// implemented: `handle_split` rejects it with an explanation rather
+    /// than emitting shares that look like SSKR/UR but aren't byte-exact
+    /// with the spec, which would silently fail to scan on real hardware.
+    Sskr,
+}
+
+/// How `decrypt`/`reconstruct` should print a recovered secret's bytes to
+/// stdout. `Utf8` (the default) behaves exactly as before this existed:
+/// print the text, or error out if the bytes aren't valid UTF-8. `Hex`/
+/// `Base64` skip that check entirely and print the encoded bytes instead,
+/// for secrets that were never text in the first place.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[value(rename_all = "lower")]
+pub enum SecretEncoding {
+    #[default]
+    Utf8,
+    Hex,
+    Base64,
+}
+
+/// Which 2D symbology to render a payload as. `Qr` (the default) is the
+/// usual choice; `Datamatrix` packs more data per area, which matters on
+/// tiny engraved metal tags, but needs qrcrypt built with `--features
+/// datamatrix` and currently only applies to `encrypt`'s plain auto-saved
+/// output and `split --plain-qr`'s captioned share images -- not cards,
+/// logos, animated QRs, sheets, PDFs, or stealth shares.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[value(rename_all = "lower")]
+pub enum SymbologyArg {
+    #[default]
+    Qr,
+    Datamatrix,
+}
+
+impl From<SymbologyArg> for qrcrypt::qr::Symbology {
+    fn from(symbology: SymbologyArg) -> Self {
+        match symbology {
+            SymbologyArg::Qr => qrcrypt::qr::Symbology::Qr,
+            SymbologyArg::Datamatrix => qrcrypt::qr::Symbology::DataMatrix,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "qrcrypt",
+    version,
+    about = "Encrypt crypto seed words and store them as encrypted QR codes"
+)]
+pub struct Cli {
+    /// Emit a single JSON object on stdout instead of human-readable output.
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Suppress decorative info messages, leaving only warnings, errors, and
+    /// the actual result on stdout.
+    #[arg(long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Show debug-level diagnostics (e.g. font fallbacks, skipped scanner
+    /// frames) that are otherwise silent.
+    #[arg(long, global = true)]
+    pub verbose: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Encrypt a secret and save it as a QR code.
+    Encrypt {
+        /// The secret text to encrypt (omit to be prompted).
+        #[arg(long)]
+        secret: Option<String>,
+        /// Where to write the resulting QR PNG.
+        #[arg(long, default_value = "encrypted.png")]
+        output: PathBuf,
+        /// A decoy secret to layer underneath a separate decoy password,
+        /// for plausible deniability (omit to be prompted when --decoy-password is set).
+        #[arg(long, requires = "decoy_password")]
+        decoy_secret: Option<String>,
+        /// The password that reveals the decoy secret instead of the real one.
+        #[arg(long)]
+        decoy_password: Option<String>,
+        /// Derive the decoy secret deterministically from the real secret via
+        /// BIP85 instead of supplying one: the real secret must itself be a
+        /// valid BIP39 mnemonic, and this index selects which child phrase to
+        /// use. Lets a user later prove the decoy was derived, not funded.
+        #[arg(long, requires = "decoy_password", conflicts_with = "decoy_secret")]
+        decoy_bip85_index: Option<u32>,
+        /// Prompt for a BIP39 passphrase (the "25th word") and bundle it
+        /// together with the secret, so `decrypt` can split them back apart.
+        /// Requires the secret to be a mnemonic.
+        #[arg(long, conflicts_with = "decoy_bip85_index")]
+        with_passphrase: bool,
+        /// Require a touch on a FIDO2 security key and mix its hmac-secret
+        /// response into the key derivation (requires the `fido2` feature).
+        #[arg(long, conflicts_with = "decoy_password")]
+        fido2: bool,
+        /// Use a fixed salt/nonce from QRCRYPT_TEST_SALT_HEX/QRCRYPT_TEST_NONCE_HEX
+        /// instead of OsRng, for reproducible test vectors. Refuses to run
+        /// unless QRCRYPT_INSECURE_TEST=1 is also set.
+        #[arg(long, hide = true, conflicts_with_all = ["decoy_password", "fido2"])]
+        test_vector: bool,
+        /// Key derivation function to protect the secret with.
+        #[arg(long, value_enum, default_value = "argon2id")]
+        kdf: KdfChoice,
+        /// scrypt cost parameter N (must be a power of two); only used with --kdf scrypt.
+        #[arg(long, default_value_t = 1 << 20)]
+        scrypt_n: u32,
+        /// scrypt block size r; only used with --kdf scrypt.
+        #[arg(long, default_value_t = 8)]
+        scrypt_r: u32,
+        /// scrypt parallelization p; only used with --kdf scrypt.
+        #[arg(long, default_value_t = 1)]
+        scrypt_p: u32,
+        /// Skip the confirmation prompt when the password looks weak.
+        #[arg(long)]
+        allow_weak_password: bool,
+        /// Foreground (module) color for the generated QR code, as a hex
+        /// string like "1a2b3c" (default: black).
+        #[arg(long)]
+        qr_color: Option<String>,
+        /// Background color for the generated QR code, as a hex string
+        /// like "f5f0e6" (default: white).
+        #[arg(long)]
+        qr_background: Option<String>,
+        /// Render white (or --fg-color) modules on a black (or
+        /// --bg-color) background instead -- e.g. for engraving on
+        /// anodized steel, where a plain black-on-white code would
+        /// engrave as a solid dark square. Swaps whichever foreground
+        /// and background colors are in effect, defaults included.
+        #[arg(long)]
+        invert: bool,
+        /// How to draw each dark module: square, dot, or rounded. Defaults
+        /// to square; dot/rounded are for fiber laser engraving.
+        #[arg(long, value_enum, default_value = "square")]
+        module_style: ModuleStyleArg,
+        /// Fraction of a module's pitch that --module-style dot/rounded
+        /// actually draws, e.g. 0.8 for 80% fill. Ignored with
+        /// --module-style square, which always fills the whole module.
+        #[arg(long, default_value_t = 1.0)]
+        fill_ratio: f32,
+        /// Error correction level for the generated QR code(s): L, M, Q or
+        /// H. Defaults to M; pick H for cards that might get scratched or
+        /// engraved. A payload that doesn't fit at the chosen level errors
+        /// out with the levels that would fit instead. Ignored with --logo,
+        /// which always needs EcLevel::H for the logo's error budget.
+        #[arg(long, value_enum)]
+        error_correction: Option<EcLevelArg>,
+        /// Composite this logo image into the center of the QR code,
+        /// forcing EcLevel::H error correction so the covered modules stay
+        /// recoverable. Rejected if the logo would need more of the code's
+        /// modules than its error-correction budget allows, or if the
+        /// composited result fails a self-scan check.
+        #[arg(long)]
+        logo: Option<PathBuf>,
+        /// Cap a `--logo` to this fraction of the QR code's total modules.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_LOGO_MAX_FRACTION, requires = "logo")]
+        logo_max_fraction: f64,
+        /// Report the planned output path and QR dimensions without
+        /// writing anything or prompting for a password. Still performs
+        /// enough sizing work to warn if the secret won't fit a QR code.
+        #[arg(long)]
+        dry_run: bool,
+        /// Instead of a single QR PNG, write a looping animated GIF of
+        /// fountain-coded QR frames to this path, so a receiver's scanner
+        /// can reconstruct the payload from any sufficiently large subset
+        /// of frames (not required to catch the start of the loop). Not
+        /// compatible with `--logo`. See `crate::ur` for why this isn't
+        /// the Blockchain Commons UR format despite the similar idea.
+        #[arg(long, conflicts_with = "logo")]
+        animated: Option<PathBuf>,
+        /// Number of fountain-coded frames to render for `--animated`.
+        /// Must be at least enough to cover the payload at `--max-fragment`
+        /// bytes per frame; extra frames beyond that add redundancy against
+        /// a missed scan.
+        #[arg(long, default_value_t = 60, requires = "animated")]
+        frames: u32,
+        /// Playback speed, in frames per second, for `--animated`.
+        #[arg(long, default_value_t = 4, requires = "animated")]
+        fps: u32,
+        /// Maximum payload bytes carried by each `--animated` frame, before
+        /// the QR/JSON/base64 envelope overhead. Smaller frames scan more
+        /// reliably but need more of them to reconstruct the payload.
+        #[arg(long, default_value_t = 60, requires = "animated")]
+        max_fragment: usize,
+        /// Render the QR at this physical size in millimetres per side
+        /// instead of the renderer's default pixel scale, e.g. for a print
+        /// shop asking for "25x25 mm at 600 DPI". Embeds `--dpi` in the
+        /// PNG's pHYs chunk so it prints at true size. Not compatible with
+        /// `--logo` or `--animated`, which need their own module scaling.
+        #[arg(long, conflicts_with_all = ["logo", "animated"])]
+        size_mm: Option<f32>,
+        /// Print resolution for `--size-mm`, embedded in the output PNG so
+        /// it prints at true size.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_CARD_DPI, requires = "size_mm")]
+        dpi: u32,
+        /// Refuse to render `--size-mm` below this module size in
+        /// millimetres, since a smaller module is unlikely to survive a
+        /// phone camera's autofocus or a printer's dot gain.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_MIN_MODULE_MM, requires = "size_mm")]
+        min_module_mm: f32,
+        /// Cap generated QR codes at this version (1-40) so they stay
+        /// readable by cheap handheld scanners that choke above version 20
+        /// or so. A payload that doesn't fit within the cap at the chosen
+        /// --error-correction is split across multiple QR codes instead of
+        /// rendering one denser code.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_MAX_QR_VERSION)]
+        max_qr_version: i16,
+        /// Render as a DataMatrix symbol instead of a QR code. Only applies
+        /// to the plain auto-saved output path -- rejected with `--logo`,
+        /// `--animated`, or `--size-mm`, and needs qrcrypt built with
+        /// `--features datamatrix`.
+        #[arg(long, value_enum, default_value = "qr", conflicts_with_all = ["logo", "animated", "size_mm"])]
+        symbology: SymbologyArg,
+        /// Sign the payload with this 32-byte Ed25519 key (see `qrcrypt
+        /// keygen`) and write a second, small QR carrying the detached
+        /// signature alongside the main output, so `qrcrypt verify` can
+        /// later prove a backup card wasn't swapped for someone else's
+        /// data. Only applies to the plain auto-saved output path.
+        #[arg(long, conflicts_with_all = ["logo", "animated", "size_mm"])]
+        sign_key: Option<PathBuf>,
+        /// Write the QR's payload as a bare `EncryptedData` JSON object,
+        /// with no `QRData` "type" tag and no compact CBOR framing --
+        /// for other tools or hand-rolled scripts that read qrcrypt's
+        /// encrypted layer(s) directly. `decrypt` and `verify` still read
+        /// it back, auto-detecting it by its fields.
+        #[arg(long)]
+        raw_payload: bool,
+        /// Record a short note in the encrypted envelope, e.g. "backup
+        /// phrase 2024", so `inspect` can show what a QR code was for
+        /// without needing the password. Authenticated along with
+        /// --no-timestamp's creation time, so neither can be changed
+        /// without invalidating every layer's password check. Not
+        /// compatible with --test-vector, which needs fully reproducible
+        /// output.
+        #[arg(long, conflicts_with = "test_vector")]
+        label: Option<String>,
+        /// Skip stamping the encrypted envelope with today's creation time.
+        /// Useful if even a coarse timestamp is more than you want embedded
+        /// in a payload headed somewhere untrusted.
+        #[arg(long)]
+        no_timestamp: bool,
+    },
+    /// Decrypt a secret from a QR code image.
+    Decrypt {
+        /// Path to the QR code PNG to decrypt.
+        #[arg(long)]
+        input: PathBuf,
+        /// Warn if the recovered secret doesn't parse as a valid BIP39
+        /// mnemonic, which can catch a wrong password that happened to
+        /// decrypt to valid-looking UTF-8.
+        #[arg(long)]
+        expect_bip39: bool,
+        /// If the decrypted secret is a keyring (see `encrypt-keyring`),
+        /// print this one named entry instead of just listing every name.
+        #[arg(long)]
+        entry: Option<String>,
+        /// After a successful decrypt, overwrite --input with random bytes
+        /// and delete it, instead of leaving the encrypted QR image on
+        /// disk. Best-effort: see `utils::secure_delete`.
+        #[arg(long)]
+        shred: bool,
+        /// How to print the recovered secret: as UTF-8 text (the default,
+        /// erroring if the bytes aren't valid UTF-8), or as hex/base64 for
+        /// a binary secret or for piping into another tool.
+        #[arg(long, value_enum, default_value = "utf8")]
+        encoding: SecretEncoding,
+    },
+    /// Encrypt several named secrets together as one QR code, so a handful
+    /// of seed phrases or passwords can share a single code instead of
+    /// needing one QR each. Pull one back out with `decrypt --entry <name>`,
+    /// or plain `decrypt` to list what's inside without revealing any of them.
+    EncryptKeyring {
+        /// One entry, as "name=secret"; repeat once per entry.
+        #[arg(long = "add")]
+        add: Vec<String>,
+        /// Where to write the resulting QR PNG.
+        #[arg(long, default_value = "keyring.png")]
+        output: PathBuf,
+        /// Skip the confirmation prompt when the password looks weak.
+        #[arg(long)]
+        allow_weak_password: bool,
+        /// Foreground (module) color for the generated QR code, as a hex
+        /// string like "1a2b3c" (default: black).
+        #[arg(long)]
+        qr_color: Option<String>,
+        /// Background color for the generated QR code, as a hex string
+        /// like "f5f0e6" (default: white).
+        #[arg(long)]
+        qr_background: Option<String>,
+        /// Render white (or --fg-color) modules on a black (or
+        /// --bg-color) background instead -- e.g. for engraving on
+        /// anodized steel, where a plain black-on-white code would
+        /// engrave as a solid dark square. Swaps whichever foreground
+        /// and background colors are in effect, defaults included.
+        #[arg(long)]
+        invert: bool,
+        /// How to draw each dark module: square, dot, or rounded. Defaults
+        /// to square; dot/rounded are for fiber laser engraving.
+        #[arg(long, value_enum, default_value = "square")]
+        module_style: ModuleStyleArg,
+        /// Fraction of a module's pitch that --module-style dot/rounded
+        /// actually draws, e.g. 0.8 for 80% fill. Ignored with
+        /// --module-style square, which always fills the whole module.
+        #[arg(long, default_value_t = 1.0)]
+        fill_ratio: f32,
+        /// Error correction level for the generated QR code(s): L, M, Q or H.
+        #[arg(long, value_enum)]
+        error_correction: Option<EcLevelArg>,
+        /// Report the planned output path and QR dimensions without writing
+        /// anything or prompting for a password.
+        #[arg(long)]
+        dry_run: bool,
+        /// Cap generated QR codes at this version (1-40); see `encrypt
+        /// --max-qr-version`.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_MAX_QR_VERSION)]
+        max_qr_version: i16,
+    },
+    /// Add a decoy layer to an already-encrypted QR code, for plausible
+    /// deniability without ever touching the real password or plaintext.
+    MergeLayers {
+        /// Path to the existing encrypted QR code PNG to layer a decoy onto.
+        #[arg(long)]
+        input: PathBuf,
+        /// Where to write the resulting layered QR PNG.
+        #[arg(long, default_value = "layered.png")]
+        output: PathBuf,
+        /// The decoy secret to layer on top (omit to be prompted).
+        #[arg(long)]
+        decoy_secret: Option<String>,
+        /// The password that reveals the decoy secret instead of the real
+        /// one (omit to be prompted).
+        #[arg(long)]
+        decoy_password: Option<String>,
+        /// Foreground (module) color for the generated QR code, as a hex
+        /// string like "1a2b3c" (default: black).
+        #[arg(long)]
+        qr_color: Option<String>,
+        /// Background color for the generated QR code, as a hex string
+        /// like "f5f0e6" (default: white).
+        #[arg(long)]
+        qr_background: Option<String>,
+        /// Render white (or --fg-color) modules on a black (or
+        /// --bg-color) background instead -- e.g. for engraving on
+        /// anodized steel, where a plain black-on-white code would
+        /// engrave as a solid dark square. Swaps whichever foreground
+        /// and background colors are in effect, defaults included.
+        #[arg(long)]
+        invert: bool,
+        /// How to draw each dark module: square, dot, or rounded. Defaults
+        /// to square; dot/rounded are for fiber laser engraving.
+        #[arg(long, value_enum, default_value = "square")]
+        module_style: ModuleStyleArg,
+        /// Fraction of a module's pitch that --module-style dot/rounded
+        /// actually draws, e.g. 0.8 for 80% fill. Ignored with
+        /// --module-style square, which always fills the whole module.
+        #[arg(long, default_value_t = 1.0)]
+        fill_ratio: f32,
+        /// Error correction level for the generated QR code: L, M, Q or H.
+        /// Defaults to M.
+        #[arg(long, value_enum)]
+        error_correction: Option<EcLevelArg>,
+        /// Cap generated QR codes at this version (1-40); see `encrypt
+        /// --max-qr-version`.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_MAX_QR_VERSION)]
+        max_qr_version: i16,
+    },
+    /// Report encrypted/share sizes and QR sizing at every error correction
+    /// level, without writing or scanning anything. Useful for sizing a
+    /// card layout or checking a secret will fit before committing to
+    /// `encrypt`/`split` (which also have their own narrower `--dry-run`).
+    Estimate {
+        /// The secret text to estimate for (omit to be prompted, or use --secret-file).
+        #[arg(long, conflicts_with = "secret_file")]
+        secret: Option<String>,
+        /// Read the secret from a file instead of a flag or interactive prompt.
+        #[arg(long)]
+        secret_file: Option<PathBuf>,
+        /// Also estimate Shamir share sizes for this reconstruction threshold.
+        #[arg(long, requires = "total")]
+        threshold: Option<u8>,
+        /// Also estimate Shamir share sizes for this many total shares.
+        #[arg(long, requires = "threshold")]
+        total: Option<u8>,
+        /// Also report each fitting error correction level's physical QR
+        /// module size in millimetres on an 8.5cm x 5.5cm card at 300 DPI.
+        #[arg(long)]
+        card: bool,
+    },
+    /// Split a secret into Shamir shares and save each as a QR code.
+    Split {
+        /// The secret text to split (omit to be prompted).
+        #[arg(long, conflicts_with = "binary")]
+        secret: Option<String>,
+        /// Treat the secret as raw bytes read from --input instead of text
+        /// typed at a prompt, for splitting binary data (e.g. raw entropy or
+        /// an encrypted file) rather than a seed phrase.
+        #[arg(long, requires = "input")]
+        binary: bool,
+        /// File to read the raw secret bytes from; only used with --binary.
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Number of shares required to reconstruct the secret. Not used
+        /// with --group, which defines its own per-group threshold.
+        #[arg(long, required_unless_present = "group", conflicts_with = "group")]
+        threshold: Option<u8>,
+        /// Total number of shares to generate. Not used with --group or
+        /// --ids, which each determine the total their own way.
+        #[arg(
+            long,
+            required_unless_present_any = ["group", "ids"],
+            conflicts_with_all = ["group", "ids"]
+        )]
+        total: Option<u8>,
+        /// Comma-separated explicit share ids to use as x-coordinates instead
+        /// of the default sequential 1..=total, e.g. "3,7,12,40,255" so a
+        /// card's id alone doesn't hint at how many shares exist, and a
+        /// specific lost id can be regenerated later with `reshare --ids`.
+        /// The number of ids given becomes the total share count. Only
+        /// supported with --format custom, and not combined with --total
+        /// (implied by the id count) or --parity (which assumes the default
+        /// sequential numbering to find what's missing).
+        #[arg(long, conflicts_with_all = ["group", "total", "parity"])]
+        ids: Option<String>,
+        /// Which finite field to share the secret over; see
+        /// `ShareEncodingArg`. Only supported with --format custom and
+        /// without --group or --ids; a mix of encodings can't be
+        /// reconstructed together, so every share from one split always
+        /// shares the same one.
+        #[arg(long, value_enum, default_value = "gf256", conflicts_with_all = ["group", "ids"])]
+        share_encoding: ShareEncodingArg,
+        /// Split into named groups instead of one flat threshold/total, e.g.
+        /// "--group 2of3 --group 1of2 --groups-required 2" for "any 2 of my
+        /// 3 family shares AND any 1 of my 2 lawyer shares." Each entry is
+        /// "<threshold>of<total>"; repeat --group once per group. Only
+        /// supported with --format custom.
+        #[arg(long, conflicts_with_all = ["threshold", "total"])]
+        group: Vec<String>,
+        /// How many of the --group entries must each have enough shares
+        /// present to reconstruct the secret. Required with --group.
+        #[arg(long, requires = "group")]
+        groups_required: Option<u8>,
+        /// Directory to write share QR codes and the info file into.
+        #[arg(long, default_value = ".")]
+        output_dir: PathBuf,
+        /// Share format to produce: QRCrypt's own format, or SLIP-39
+        /// mnemonics for import into compatible hardware wallets.
+        #[arg(long, value_enum, default_value = "custom")]
+        format: ShareFormat,
+        /// Encrypt each share's data with its own password (only for
+        /// --format custom), so a single share's QR code is useless to
+        /// whoever finds it without also knowing that holder's password.
+        #[arg(long)]
+        share_passwords: bool,
+        /// File with one password per line, in share-index order, to use
+        /// instead of prompting interactively for --share-passwords.
+        #[arg(long, requires = "share_passwords")]
+        share_password_file: Option<PathBuf>,
+        /// Comma-separated holder names, in share-index order (e.g.
+        /// "mom,bank,lawyer,safe,friend"), used as each share's filename
+        /// suffix, card text, and label in the info file. Only for --format
+        /// custom; purely informational and doesn't affect reconstruction.
+        #[arg(long)]
+        labels: Option<String>,
+        /// Generate this many extra parity shares (only for --format custom)
+        /// so up to that many destroyed original shares can be rebuilt from
+        /// the rest plus parity, on top of (not instead of) the normal
+        /// threshold-of-total tolerance.
+        #[arg(long, default_value_t = 0)]
+        parity: u8,
+        /// Foreground (module) color for the generated QR codes, as a hex
+        /// string like "1a2b3c" (default: black).
+        #[arg(long)]
+        qr_color: Option<String>,
+        /// Background color for the generated QR codes, as a hex string
+        /// like "f5f0e6" (default: white).
+        #[arg(long)]
+        qr_background: Option<String>,
+        /// Render white (or --fg-color) modules on a black (or
+        /// --bg-color) background instead -- e.g. for engraving on
+        /// anodized steel, where a plain black-on-white code would
+        /// engrave as a solid dark square. Swaps whichever foreground
+        /// and background colors are in effect, defaults included.
+        #[arg(long)]
+        invert: bool,
+        /// How to draw each dark module: square, dot, or rounded. Defaults
+        /// to square; dot/rounded are for fiber laser engraving.
+        #[arg(long, value_enum, default_value = "square")]
+        module_style: ModuleStyleArg,
+        /// Fraction of a module's pitch that --module-style dot/rounded
+        /// actually draws, e.g. 0.8 for 80% fill. Ignored with
+        /// --module-style square, which always fills the whole module.
+        #[arg(long, default_value_t = 1.0)]
+        fill_ratio: f32,
+        /// Quiet zone around each share card's QR code, in QR modules (not
+        /// pixels), so the white border scales with however large the
+        /// module size ends up. Wider than the default can help scanning
+        /// on reflective surfaces like engraved steel.
+        #[arg(long, default_value_t = 4)]
+        border: u32,
+        /// Error correction level for the generated share QR codes: L, M, Q
+        /// or H. Defaults to M; pick H for cards that might get scratched or
+        /// engraved. A share whose payload doesn't fit at the chosen level
+        /// errors out before writing anything, naming the levels that would
+        /// fit.
+        #[arg(long, value_enum)]
+        error_correction: Option<EcLevelArg>,
+        /// Pixel density to render share cards at. Higher values produce
+        /// larger, denser card images at the same physical 8.5cm x 5.5cm
+        /// size, e.g. for an engraver that wants 600 DPI art instead of
+        /// print-resolution 300 DPI.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_CARD_DPI)]
+        dpi: u32,
+        /// Path to a TrueType/OpenType font file to render card titles and
+        /// captions with, overriding the embedded default font.
+        #[arg(long)]
+        font: Option<PathBuf>,
+        /// Replace the "QRCrypt" title drawn at the top of every share card.
+        #[arg(long)]
+        card_title: Option<String>,
+        /// Replace the "Share N of M - threshold T" caption drawn on every share
+        /// card with this fixed text instead. Both the title and this
+        /// caption are automatically shrunk to fit the card's width and to
+        /// stay clear of the QR code's quiet zone, however long they are.
+        #[arg(long)]
+        card_subtitle: Option<String>,
+        /// Render each share as a plain captioned QR code instead of a full
+        /// card: no title, no fixed 8.5cm x 5.5cm physical size, just the QR
+        /// code with "Share N of M - threshold T" (or --card-subtitle, if
+        /// given) drawn beneath it so shares can be told apart without
+        /// scanning each one. --dpi and --card-title don't apply, since
+        /// there's no card to render them onto. Only supported with the
+        /// default --format custom.
+        #[arg(long, conflicts_with_all = ["pdf"])]
+        plain_qr: bool,
+        /// Skip writing info.txt. Every share card QR already carries its own
+        /// threshold/total/index, so info.txt is a convenience, not a
+        /// requirement -- some consider it an unwanted plaintext-metadata
+        /// file they have to remember to destroy alongside the shares.
+        #[arg(long, conflicts_with = "info")]
+        no_info: bool,
+        /// Write info.txt to this path instead of `<output-dir>/info.txt`.
+        #[arg(long, value_name = "PATH", conflicts_with = "no_info")]
+        info: Option<PathBuf>,
+        /// Report the planned share filenames and card dimensions without
+        /// writing anything to disk.
+        #[arg(long)]
+        dry_run: bool,
+        /// Disguise every share's QR as an ordinary encrypted secret instead
+        /// of a recognizable Shamir share: the threshold/total/index/checksum
+        /// metadata is JSON-encoded and encrypted with a separate password
+        /// rather than left in the clear, so a thief who scans one card can't
+        /// tell it's 1 of N shares at all. Only supported with the default
+        /// flat --format custom split (no --group, --parity, or
+        /// --share-passwords). info.txt becomes the only place the split
+        /// parameters are recorded unencrypted.
+        #[arg(long, conflicts_with_all = ["group", "parity", "share_passwords"])]
+        stealth: bool,
+        /// Write each share's bytes as a sequence of words from QRCrypt's
+        /// word list (see `shamir::encode_share_words`) into info.txt instead
+        /// of rendering QR card PNGs, for transcribing shares by hand onto
+        /// paper or metal instead of trusting QR durability. Decode them
+        /// back with `reconstruct --words`. Only supported with the default
+        /// flat --format custom split at --share-encoding gf256 (no --group,
+        /// --parity, --share-passwords, --dry-run, or --stealth, none of
+        /// which have anything to render without a QR code). info.txt is
+        /// words_only's only output, so it also conflicts with --no-info.
+        #[arg(long, conflicts_with_all = ["group", "parity", "share_passwords", "dry_run", "stealth", "no_info"])]
+        words_only: bool,
+        /// Also render every share card into a single PDF at this path,
+        /// followed by the reconstruction instructions as text, so there's
+        /// one file to print instead of arranging individual card PNGs by
+        /// hand. Requires qrcrypt to be built with --features pdf. Only
+        /// supported with the default flat --format custom split (no
+        /// --group, --words-only, or --stealth, none of which render the
+        /// usual labeled share card image).
+        #[arg(long, conflicts_with_all = ["group", "words_only", "stealth"])]
+        pdf: Option<PathBuf>,
+        /// How many share cards to put on each page of --pdf.
+        #[arg(long, default_value_t = 1, requires = "pdf")]
+        per_page: u8,
+        /// Also compose every share card into a printable grid sheet PNG at
+        /// this path, with dashed cut guides between cells and each share's
+        /// caption reprinted in the margin below it, instead of handing out
+        /// loose per-share files. Paginated across `<path>`,
+        /// `<stem>-page-2.png`, `<stem>-page-3.png`, ... when the shares
+        /// don't all fit on one `--paper-size` page at `--dpi`. Only
+        /// supported with the default flat --format custom split (no
+        /// --group, --words-only, or --stealth, none of which render the
+        /// usual labeled share card image).
+        #[arg(long, conflicts_with_all = ["group", "words_only", "stealth"])]
+        sheet: Option<PathBuf>,
+        /// Page size for --sheet.
+        #[arg(long, value_enum, default_value = "a4", requires = "sheet")]
+        paper_size: PaperSizeArg,
+        /// Also render a back-side card for every share, at this path: the
+        /// project URL, the exact `reconstruct` invocation, and the
+        /// share's threshold/total. Meant to be printed and kept with the
+        /// share's QR card so whoever finds it knows what reads it. With
+        /// more than one share, `<path>` is reused as a stem:
+        /// `<stem>-share-2-back.png`, `<stem>-share-3-back.png`, ...
+        /// alongside it. Only supported with the default flat --format
+        /// custom split (no --group, --plain-qr, --words-only, or
+        /// --stealth -- stealth shares are rendered as bare QR codes with
+        /// no card to back, and printing threshold/total on a back card
+        /// would give away exactly what --stealth is meant to hide).
+        #[arg(long, conflicts_with_all = ["group", "plain_qr", "words_only", "stealth"])]
+        card_back: Option<PathBuf>,
+        /// Extra free-form text to print on the back of every --card-back
+        /// card, below the recovery instructions.
+        #[arg(long, requires = "card_back")]
+        card_back_text: Option<String>,
+        /// Print a second QR code on every share card carrying the split's
+        /// threshold/total/creation time and a SHA-256 commitment to the
+        /// secret, so a holder can confirm a reconstructed secret matches
+        /// the original split without anyone having to expose the secret
+        /// itself beforehand. This only checks the secret *after*
+        /// reconstruction -- it can't validate an individual share on its
+        /// own, since qrcrypt's Shamir shares don't carry the per-share
+        /// algebraic structure a true verifiable-secret-sharing scheme would
+        /// need. Requires --password: the commitment is a bare unsalted
+        /// SHA-256, which is fine as a collision check against
+        /// high-entropy ciphertext but would otherwise let anyone holding
+        /// just one share card's verification QR run an offline
+        /// dictionary/brute-force attack against a low-entropy secret
+        /// without ever needing a single Shamir share. Only supported with
+        /// the default flat --format custom split (no --group, --plain-qr,
+        /// --words-only, or --stealth).
+        #[arg(
+            long,
+            requires = "password",
+            conflicts_with_all = ["group", "plain_qr", "words_only", "stealth"]
+        )]
+        with_verify: bool,
+        /// Encrypt the secret with a password before Shamir-splitting it, so
+        /// a --threshold of physically stolen or colluding shares isn't
+        /// enough to reconstruct the secret on its own -- the password
+        /// `reconstruct --password` prompts for is also required. Only
+        /// supported with the default flat --format custom split (no
+        /// --group, which would need its own separate password handling).
+        #[arg(long, conflicts_with = "group")]
+        password: bool,
+        /// Skip the confirmation prompt when --password looks weak.
+        #[arg(long, requires = "password")]
+        allow_weak_password: bool,
+        /// Sign each share with this 32-byte raw Ed25519 signing key (only
+        /// for --format custom), so a custodian can later use
+        /// `validate --verify-key` to confirm their share genuinely came
+        /// from whoever holds the matching key and wasn't substituted. Not
+        /// supported with --words-only, which doesn't carry the extra
+        /// signature field.
+        #[arg(long, conflicts_with = "words_only")]
+        sign_key: Option<PathBuf>,
+        /// Render each share as a DataMatrix symbol instead of a QR code.
+        /// Only supported with --plain-qr, and needs qrcrypt built with
+        /// --features datamatrix.
+        #[arg(long, value_enum, default_value = "qr", requires = "plain_qr")]
+        symbology: SymbologyArg,
+    },
+    /// Reconstruct a secret from share QR code images or files.
+    Reconstruct {
+        /// Paths to share QR code images.
+        #[arg(
+            long,
+            required_unless_present_any = ["scan_dir", "words"],
+            conflicts_with_all = ["scan_dir", "words"]
+        )]
+        shares: Vec<PathBuf>,
+        /// Scan every image in this directory for shares instead of listing
+        /// them individually; files that aren't a readable share are skipped
+        /// with a warning.
+        #[arg(long, conflicts_with = "words")]
+        scan_dir: Option<PathBuf>,
+        /// Prompt interactively for shares typed as words (from
+        /// `split`'s word-encoded output) instead of reading files. Enter
+        /// one share's words per line; finish with a blank line.
+        #[arg(long)]
+        words: bool,
+        /// Treat the reconstructed secret as raw bytes and write it to
+        /// --output instead of printing it as text, for secrets that aren't
+        /// valid UTF-8.
+        #[arg(long, requires = "output")]
+        binary: bool,
+        /// File to write the raw reconstructed bytes to; only used with --binary.
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// When more than `threshold` shares are supplied, cross-check every
+        /// threshold-sized subset against the others and report which
+        /// share(s) look damaged, before the normal reconstruction attempt.
+        #[arg(long)]
+        diagnose: bool,
+        /// The shares were disguised by `split --stealth`: prompt for the
+        /// metadata password and decrypt each one back into a normal share
+        /// before reconstructing as usual. Only supported together with
+        /// --shares (not --scan-dir or --words).
+        #[arg(long)]
+        stealth: bool,
+        /// The shares were produced by `split --password`: after
+        /// reconstructing them, prompt for the password used at split time
+        /// and decrypt the result back into the real secret. Without this,
+        /// a --password split's reconstructed bytes are still the
+        /// encrypted ciphertext, not the secret itself.
+        #[arg(long)]
+        password: bool,
+        /// Treat --shares as ssss-split's plain-text "index-hexshare" lines
+        /// instead of QRCrypt's own share files, for importing shares from
+        /// the classic `ssss` command-line tool (or from
+        /// `split --format ssss`). Requires --threshold, since that
+        /// plain-text format doesn't record one itself.
+        #[arg(
+            long,
+            requires = "threshold",
+            conflicts_with_all = ["scan_dir", "words", "stealth"]
+        )]
+        ssss: bool,
+        /// Reconstruction threshold to use with --ssss.
+        #[arg(long)]
+        threshold: Option<u8>,
+        /// Reconstruct and check the result against the secret fingerprint
+        /// `split` recorded in --info, then discard it, without ever
+        /// printing or saving the secret. For a periodic integrity check of
+        /// a backup that doesn't need (or want) to reveal it. Exits non-zero
+        /// if reconstruction fails or the fingerprint doesn't match.
+        #[arg(long, requires = "info", conflicts_with_all = ["binary", "output"])]
+        verify_only: bool,
+        /// Path to the info.txt that `split` wrote alongside the shares;
+        /// required by --verify-only to know what fingerprint to check against.
+        #[arg(long)]
+        info: Option<PathBuf>,
+        /// Print the full secret immediately instead of a masked preview.
+        /// The default masked preview (first and last word, word count, and
+        /// fingerprint) avoids flashing the whole secret over a screen-shared
+        /// terminal or SSH session before you've confirmed it; pass this for
+        /// scripts that need the secret without an interactive prompt.
+        #[arg(long)]
+        no_mask: bool,
+        /// After --output is written, overwrite and delete the --shares
+        /// input files -- the secret is fully recoverable from --output
+        /// now, so the share images no longer need protecting. Requires
+        /// --binary --output; not supported with --scan-dir, --words, or
+        /// --ssss, which don't name a single input file list to shred.
+        #[arg(
+            long,
+            requires = "binary",
+            conflicts_with_all = ["scan_dir", "words", "ssss"]
+        )]
+        shred: bool,
+        /// How to print the reconstructed secret: as UTF-8 text (the
+        /// default, erroring if the bytes aren't valid UTF-8), or as
+        /// hex/base64 for a binary secret or for piping into another tool.
+        /// Not used with --binary, which already writes raw bytes to a file.
+        #[arg(long, value_enum, default_value = "utf8", conflicts_with = "binary")]
+        encoding: SecretEncoding,
+    },
+    /// Reconstruct a secret from enough existing shares and immediately
+    /// re-split it into a fresh share set, e.g. after losing a card or
+    /// wanting to change the threshold/total. The old shares are now part
+    /// of a superseded set and should be destroyed.
+    Reshare {
+        /// Paths to at least `threshold` existing share QR code images.
+        #[arg(long, required = true)]
+        shares: Vec<PathBuf>,
+        /// Number of shares required to reconstruct the new set.
+        #[arg(long)]
+        threshold: u8,
+        /// Total number of shares to generate in the new set. Not used with
+        /// --ids, which determines the total from the id count.
+        #[arg(long, required_unless_present = "ids", conflicts_with = "ids")]
+        total: Option<u8>,
+        /// Comma-separated explicit share ids for the new set, e.g. to give
+        /// the replacement for a lost share the same id it had before
+        /// instead of resplitting into a fresh sequential 1..=total set.
+        /// This is still a brand-new split with a new set_id; every share
+        /// from the old set is superseded either way. See `split --ids` for
+        /// the id rules.
+        #[arg(long)]
+        ids: Option<String>,
+        /// Directory to write the new share QR codes and info file into.
+        #[arg(long, default_value = ".")]
+        output_dir: PathBuf,
+        /// Pixel density to render the new share cards at. Higher values
+        /// produce larger, denser card images at the same physical
+        /// 8.5cm x 5.5cm size, e.g. for an engraver that wants 600 DPI art
+        /// instead of print-resolution 300 DPI.
+        #[arg(long, default_value_t = qrcrypt::qr::DEFAULT_CARD_DPI)]
+        dpi: u32,
+        /// Path to a TrueType/OpenType font file to render card titles and
+        /// captions with, overriding the embedded default font.
+        #[arg(long)]
+        font: Option<PathBuf>,
+        /// Replace the "QRCrypt" title drawn at the top of every new share
+        /// card.
+        #[arg(long)]
+        card_title: Option<String>,
+        /// Replace the "Share N of M - threshold T" caption drawn on every new
+        /// share card with this fixed text instead.
+        #[arg(long)]
+        card_subtitle: Option<String>,
+    },
+    /// Validate that a set of shares can reconstruct their secret.
+    Validate {
+        /// Paths to share QR code images.
+        #[arg(long, required_unless_present = "scan_dir", conflicts_with = "scan_dir")]
+        shares: Vec<PathBuf>,
+        /// Scan every image in this directory for shares instead of listing
+        /// them individually; files that aren't a readable share are skipped
+        /// with a warning. Unlike `reconstruct --scan-dir`, this never stops
+        /// once it has enough shares to reconstruct -- validation checks the
+        /// shares it's given, not the smallest set that would work.
+        #[arg(long)]
+        scan_dir: Option<PathBuf>,
+        /// With --scan-dir, stop once exactly this many shares have been
+        /// collected instead of scanning the whole directory, and fail if
+        /// fewer than this many are found. Scanning still proceeds in the
+        /// same sorted directory order either way.
+        #[arg(long, requires = "scan_dir")]
+        count: Option<usize>,
+        /// Beyond checking share metadata, actually run the reconstruction
+        /// in memory and check the result's BIP39/UTF-8 validity before
+        /// zeroizing it, without ever printing it. For an annual backup
+        /// fire-drill that proves the shares still combine, not just that
+        /// they're individually well-formed.
+        #[arg(long)]
+        deep: bool,
+        /// Check each share's `split --sign-key` signature against this
+        /// 32-byte raw Ed25519 public key, failing loudly if any share is
+        /// missing a signature or doesn't match.
+        #[arg(long)]
+        verify_key: Option<PathBuf>,
+    },
+    /// Encrypt a file and save it as a sequence of QR codes.
+    EncryptFile {
+        /// The file to encrypt.
+        #[arg(long)]
+        input: PathBuf,
+        /// Directory to write part QR codes and the info file into.
+        #[arg(long, default_value = ".")]
+        output_dir: PathBuf,
+        /// Skip the confirmation prompt when the password looks weak.
+        #[arg(long)]
+        allow_weak_password: bool,
+        /// Foreground (module) color for the generated QR codes, as a hex
+        /// string like "1a2b3c" (default: black).
+        #[arg(long)]
+        qr_color: Option<String>,
+        /// Background color for the generated QR codes, as a hex string
+        /// like "f5f0e6" (default: white).
+        #[arg(long)]
+        qr_background: Option<String>,
+        /// Render white (or --fg-color) modules on a black (or
+        /// --bg-color) background instead -- e.g. for engraving on
+        /// anodized steel, where a plain black-on-white code would
+        /// engrave as a solid dark square. Swaps whichever foreground
+        /// and background colors are in effect, defaults included.
+        #[arg(long)]
+        invert: bool,
+        /// How to draw each dark module: square, dot, or rounded. Defaults
+        /// to square; dot/rounded are for fiber laser engraving.
+        #[arg(long, value_enum, default_value = "square")]
+        module_style: ModuleStyleArg,
+        /// Fraction of a module's pitch that --module-style dot/rounded
+        /// actually draws, e.g. 0.8 for 80% fill. Ignored with
+        /// --module-style square, which always fills the whole module.
+        #[arg(long, default_value_t = 1.0)]
+        fill_ratio: f32,
+        /// Use real QR "Structured Append" (the symbol sequence indicator
+        /// and parity byte from ISO/IEC 18004 section 8.4.3.3) instead of
+        /// the custom `FilePart` JSON header, so a stock scanner app
+        /// reassembles the parts itself. Not yet implemented: the
+        /// `qrcode` crate we render codes with only exposes the mode
+        /// indicator for it, not a way to push the sequence/total/parity
+        /// fields that follow, so `handle_encrypt_file` rejects this with
+        /// an explanation rather than emitting codes a real scanner can't
+        /// actually stitch back together.
+        #[arg(long)]
+        structured_append: bool,
+    },
+    /// Reconstruct a file from its part QR codes and decrypt it.
+    DecryptFile {
+        /// Directory to scan for part QR code images.
+        #[arg(long)]
+        input_dir: PathBuf,
+        /// File to write the decrypted bytes to.
+        #[arg(long)]
+        output: PathBuf,
+        /// After --output is written, overwrite and delete the part QR
+        /// images found in --input-dir -- the file is fully recovered now,
+        /// so the encrypted parts no longer need protecting.
+        #[arg(long)]
+        shred: bool,
+    },
+    /// Check a share card against the hash `split` recorded for it in
+    /// info.txt, to catch a card that was reprinted, edited, or corrupted
+    /// after the split.
+    VerifyShare {
+        /// Path to the share QR code image or JSON file to check.
+        #[arg(long)]
+        share: PathBuf,
+        /// Path to the info.txt that `split` wrote alongside the shares.
+        #[arg(long)]
+        info: PathBuf,
+    },
+    /// Report what a QR PNG contains without decoding the QR code itself,
+    /// by reading the `qrcrypt:meta` metadata chunk `encrypt`/`split`/etc.
+    /// embed in every PNG they write. Useful for a file found without
+    /// context, e.g. `backup.png` from years ago. PNGs written before this
+    /// existed, or that were never qrcrypt's to begin with, simply have no
+    /// chunk to report.
+    Inspect {
+        /// Path to the PNG to inspect.
+        #[arg(long)]
+        input: PathBuf,
+    },
+    /// Validate a BIP39 mnemonic and show its seed fingerprint.
+    ValidatePhrase {
+        /// The mnemonic to validate (omit to be prompted).
+        #[arg(long)]
+        mnemonic: Option<String>,
+        /// Prompt for a BIP39 passphrase (the "25th word") to include when
+        /// deriving the seed fingerprint, so it can be checked against a
+        /// hardware wallet that uses the same passphrase.
+        #[arg(long)]
+        with_passphrase: bool,
+    },
+    /// Benchmark Argon2id to suggest memory/time cost parameters for this
+    /// machine. Doesn't store or change anything; it just reports a
+    /// recommendation.
+    Calibrate {
+        /// How long a single key derivation should take, in milliseconds.
+        #[arg(long, default_value_t = 1000)]
+        target_ms: u64,
+    },
+    /// Interactive guided setup for first-time users: choose encrypt or
+    /// split, enter the secret with live BIP39 checking, pick a password,
+    /// and pick where to save -- then hand off to the same `encrypt`/
+    /// `split` logic those subcommands use. Purely a guided front door;
+    /// skip it entirely and use `encrypt`/`split` directly once you know
+    /// which flags you want.
+    Setup,
+    /// Generate an Ed25519 keypair for `encrypt --sign-key`/`qrcrypt
+    /// verify` (the same raw 32-byte key format `split --sign-key`/
+    /// `validate --verify-key` already use). Writes the signing (secret)
+    /// key to `--output` and the matching public key alongside it at
+    /// `<output>.pub`.
+    Keygen {
+        /// Where to write the signing key.
+        #[arg(long, default_value = "qrcrypt.key")]
+        output: PathBuf,
+    },
+    /// Check an `encrypt --sign-key` payload signature, to prove a backup
+    /// card wasn't swapped for someone else's data (an "evil maid" against
+    /// whoever inherits it). Scans both QR codes the same way `decrypt`
+    /// does; the password to the payload itself isn't needed.
+    Verify {
+        /// Path to the main payload QR/DataMatrix image `encrypt` wrote.
+        #[arg(long)]
+        payload: PathBuf,
+        /// Path to the signature QR `encrypt --sign-key` wrote alongside it.
+        #[arg(long)]
+        signature: PathBuf,
+        /// Path to the signer's 32-byte Ed25519 public key.
+        #[arg(long)]
+        pubkey: PathBuf,
+    },
+}
@@ -0,0 +1,917 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::error::{QRCryptError, Result};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// Fixed label HMACed under a layer's derived key and stored alongside it, so
+/// `decrypt_layer` can tell "wrong password" apart from "corrupted
+/// ciphertext" before ever touching AES-GCM, instead of both surfacing as the
+/// same AEAD tag-mismatch error. Absent on layers written before this
+/// existed, which fall back to AES-GCM's tag check alone.
+const KEY_COMMITMENT_LABEL: &[u8] = b"qrcrypt:key-commitment:v1";
+
+/// Truncated length of a key-commitment tag. 128 bits is already far beyond
+/// what a password-guessing attacker could exploit, and keeping the tag
+/// short matters here since it rides along in every QR code.
+const KEY_COMMITMENT_LEN: usize = 16;
+
+/// Which key derivation function protects an `EncryptedData`'s layers, and
+/// its parameters. Defaults to `Argon2id` so payloads written before this
+/// field existed keep deserializing. Unrecognized identifiers decode to
+/// `Unknown` instead of failing parsing outright, so `derive_key` can report
+/// a clear "needs a newer qrcrypt" error instead of a serde one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KdfParams {
+    #[default]
+    Argon2id,
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+/// A single AES-256-GCM encrypted layer: the salt and nonce needed to
+/// re-derive the key and decrypt, plus the ciphertext (with AEAD tag).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Layer {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    /// HMAC-SHA256 of `KEY_COMMITMENT_LABEL` under this layer's derived key,
+    /// checked by `decrypt_layer` before AES-GCM to give a clear "incorrect
+    /// password" error instead of a generic AEAD failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_commitment: Option<Vec<u8>>,
+}
+
+/// The on-disk / in-QR representation of an encrypted secret. `decoy` is
+/// present when the secret was encrypted in "layered" mode for plausible
+/// deniability: a shallow password reveals the decoy plaintext, the real
+/// password reveals the hidden one. `fido2_challenge` is present when a
+/// security key's hmac-secret was mixed into the `hidden` layer's key.
+/// `kdf` applies to both `hidden` and `decoy`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedData {
+    pub hidden: Layer,
+    pub decoy: Option<Layer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fido2_challenge: Option<Vec<u8>>,
+    #[serde(default)]
+    pub kdf: KdfParams,
+    /// The exact Argon2 primitive (`argon2id`, `argon2i`, `argon2d`) and
+    /// version (e.g. `0x13`) used to derive this payload's key, recorded so
+    /// decryption doesn't depend on `Argon2::default()` never changing across
+    /// argon2 crate upgrades. Only meaningful when `kdf` is `Argon2id`.
+    /// Absent on payloads written before this field existed; `derive_key`
+    /// falls back to `Argon2::default()` for those, which is only correct
+    /// because the default hasn't changed since.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf_algorithm: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf_version: Option<u32>,
+    /// Unix timestamp of when this payload was encrypted, set via `encrypt
+    /// --no-timestamp` to omit. Plain integer rather than RFC 3339, matching
+    /// `unix_timestamp_now`'s rationale -- qrcrypt otherwise has no
+    /// calendar/timezone dependency. Plaintext, but bound into `hidden` (and
+    /// `decoy`, if present) as AES-GCM associated data, so it can't be
+    /// changed without invalidating the password check; see `metadata_aad`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// A short, user-supplied note set via `encrypt --label`, e.g. "backup
+    /// phrase 2024". Plaintext and optional for the same reasons as
+    /// `created_at`, and authenticated the same way.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Password-based encryption/decryption built on Argon2id + AES-256-GCM,
+/// optionally strengthened with a FIDO2 security key's hmac-secret.
+pub struct Crypto;
+
+// A thread-local (rather than global) counter, since `cargo test` runs each
+// test on its own thread and these tests run concurrently with the rest of
+// the suite.
+#[cfg(test)]
+thread_local! {
+    static KDF_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(test)]
+fn reset_kdf_calls() {
+    KDF_CALLS.with(|c| c.set(0));
+}
+
+#[cfg(test)]
+fn kdf_call_count() -> usize {
+    KDF_CALLS.with(|c| c.get())
+}
+
+impl Crypto {
+    /// Derive a 256-bit key from a password and salt using `kdf`, folding in
+    /// a FIDO2 hmac-secret response if one was supplied. Shared by both the
+    /// encryption and decryption paths (`encrypt_layer_with` and
+    /// `decrypt_layer`) so they can never disagree about how a given
+    /// payload's key was derived, and so the 32-byte truncation/layout lives
+    /// in exactly one place. The returned buffer is `Zeroizing`, so it's
+    /// wiped as soon as the caller is done building a cipher from it.
+    fn derive_key(
+        kdf: &KdfParams,
+        password: &str,
+        salt: &[u8],
+        fido2_secret: Option<&[u8; 32]>,
+        argon2_algorithm: Option<&str>,
+        argon2_version: Option<u32>,
+    ) -> Result<Zeroizing<[u8; KEY_LEN]>> {
+        #[cfg(test)]
+        KDF_CALLS.with(|c| c.set(c.get() + 1));
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        match kdf {
+            KdfParams::Argon2id => {
+                let argon2 = match (argon2_algorithm, argon2_version) {
+                    (Some(algorithm), Some(version)) => {
+                        let algorithm = Algorithm::new(algorithm).map_err(|_| {
+                            QRCryptError::KeyDerivation(format!(
+                                "unknown argon2 algorithm '{algorithm}'"
+                            ))
+                        })?;
+                        let version = Version::try_from(version).map_err(|_| {
+                            QRCryptError::KeyDerivation(format!("unknown argon2 version {version}"))
+                        })?;
+                        Argon2::new(algorithm, version, Params::default())
+                    }
+                    // Legacy payload written before `kdf_algorithm`/`kdf_version`
+                    // existed: assume whatever `Argon2::default()` produces now,
+                    // which is only safe because the default hasn't changed
+                    // since those payloads were written.
+                    _ => Argon2::default(),
+                };
+                argon2
+                    .hash_password_into(password.as_bytes(), salt, &mut *key)
+                    .map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+            }
+            KdfParams::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(*log_n, *r, *p, KEY_LEN).map_err(|e| {
+                    QRCryptError::KeyDerivation(format!("invalid scrypt params: {e}"))
+                })?;
+                scrypt::scrypt(password.as_bytes(), salt, &params, &mut *key)
+                    .map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+            }
+            KdfParams::Unknown => {
+                return Err(QRCryptError::KeyDerivation(
+                    "this payload uses a key derivation function this qrcrypt doesn't know; you need a newer qrcrypt".to_string(),
+                ));
+            }
+        }
+        if let Some(secret) = fido2_secret {
+            for (k, s) in key.iter_mut().zip(secret.iter()) {
+                *k ^= s;
+            }
+        }
+        Ok(key)
+    }
+
+    /// The Argon2 algorithm/version pair to record for a freshly written
+    /// `kdf`, so decryption can reconstruct the exact `Argon2` instance used
+    /// rather than depending on `Argon2::default()`. `None` for KDFs other
+    /// than `Argon2id`, which don't go through this crate's `Argon2` type.
+    fn current_argon2_stamp(kdf: &KdfParams) -> (Option<String>, Option<u32>) {
+        match kdf {
+            KdfParams::Argon2id => (
+                Some(Algorithm::Argon2id.as_str().to_string()),
+                Some(Version::V0x13.into()),
+            ),
+            KdfParams::Scrypt { .. } | KdfParams::Unknown => (None, None),
+        }
+    }
+
+    /// HMAC-SHA256 of `KEY_COMMITMENT_LABEL` under `key`, used as a
+    /// key-commitment tag so a wrong password can be detected directly
+    /// instead of only surfacing as an AES-GCM tag mismatch.
+    fn key_commitment_tag(key: &[u8]) -> Vec<u8> {
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(key).expect("HMAC accepts a key of any length");
+        mac.update(KEY_COMMITMENT_LABEL);
+        mac.finalize().into_bytes()[..KEY_COMMITMENT_LEN].to_vec()
+    }
+
+    fn encrypt_layer(
+        plaintext: &[u8],
+        password: &str,
+        kdf: &KdfParams,
+        fido2_secret: Option<&[u8; 32]>,
+        aad: &[u8],
+    ) -> Result<Layer> {
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = vec![0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        Self::encrypt_layer_with(plaintext, password, kdf, fido2_secret, salt, nonce, aad)
+    }
+
+    /// Bytes `encrypt_layer`/`decrypt_layer` bind into AES-GCM as associated
+    /// data so `created_at`/`label` can't be changed without invalidating
+    /// every layer's password check, while staying plaintext (unlike the
+    /// ciphertext itself, neither needs a password to read). Empty when both
+    /// are `None`, which reproduces the exact no-AAD call `encrypt_layer`
+    /// always made before these fields existed -- so payloads without
+    /// metadata are byte-for-byte unaffected, which `known_answer_vectors...`
+    /// below depends on.
+    fn metadata_aad(created_at: Option<u64>, label: Option<&str>) -> Vec<u8> {
+        let mut aad = Vec::new();
+        if let Some(created_at) = created_at {
+            aad.extend_from_slice(b"created_at:");
+            aad.extend_from_slice(&created_at.to_le_bytes());
+        }
+        if let Some(label) = label {
+            aad.extend_from_slice(b"label:");
+            aad.extend_from_slice(&(label.len() as u64).to_le_bytes());
+            aad.extend_from_slice(label.as_bytes());
+        }
+        aad
+    }
+
+    /// Encrypt a layer with an explicit salt and nonce instead of pulling
+    /// them from `OsRng`. Shared by `encrypt_layer` (random) and
+    /// `encrypt_deterministic` (fixed, for known-answer test vectors).
+    fn encrypt_layer_with(
+        plaintext: &[u8],
+        password: &str,
+        kdf: &KdfParams,
+        fido2_secret: Option<&[u8; 32]>,
+        salt: Vec<u8>,
+        nonce: Vec<u8>,
+        aad: &[u8],
+    ) -> Result<Layer> {
+        let (algorithm, version) = Self::current_argon2_stamp(kdf);
+        let key = Self::derive_key(
+            kdf,
+            password,
+            &salt,
+            fido2_secret,
+            algorithm.as_deref(),
+            version,
+        )?;
+        let key_commitment = Some(Self::key_commitment_tag(key.as_slice()));
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()));
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|e| QRCryptError::Encryption(e.to_string()))?;
+
+        Ok(Layer {
+            salt,
+            nonce,
+            ciphertext,
+            key_commitment,
+        })
+    }
+
+    fn decrypt_layer(
+        layer: &Layer,
+        password: &str,
+        kdf: &KdfParams,
+        fido2_secret: Option<&[u8; 32]>,
+        argon2_algorithm: Option<&str>,
+        argon2_version: Option<u32>,
+        aad: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        let key = Self::derive_key(
+            kdf,
+            password,
+            &layer.salt,
+            fido2_secret,
+            argon2_algorithm,
+            argon2_version,
+        )?;
+        if let Some(expected) = &layer.key_commitment {
+            let mut mac = <HmacSha256 as Mac>::new_from_slice(key.as_slice())
+                .expect("HMAC accepts a key of any length");
+            mac.update(KEY_COMMITMENT_LABEL);
+            mac.verify_truncated_left(expected)
+                .map_err(|_| QRCryptError::Decryption("incorrect password".to_string()))?;
+        }
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()));
+        let nonce = Nonce::from_slice(&layer.nonce);
+        let plaintext = cipher
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: layer.ciphertext.as_ref(),
+                    aad,
+                },
+            )
+            .map_err(|_| {
+                QRCryptError::Decryption("wrong password or corrupted data".to_string())
+            })?;
+        Ok(Zeroizing::new(plaintext))
+    }
+
+    /// Encrypt a single secret with a single password, using Argon2id.
+    pub fn encrypt(plaintext: &[u8], password: &str) -> Result<EncryptedData> {
+        Self::encrypt_with_kdf(plaintext, password, KdfParams::Argon2id, None, None)
+    }
+
+    /// Encrypt a single secret with a single password under an explicit KDF,
+    /// e.g. `KdfParams::Scrypt` to produce a payload compatible with
+    /// scrypt-based tools. `created_at`/`label` are stamped onto the
+    /// envelope and authenticated as AES-GCM associated data; see
+    /// `metadata_aad`.
+    pub fn encrypt_with_kdf(
+        plaintext: &[u8],
+        password: &str,
+        kdf: KdfParams,
+        created_at: Option<u64>,
+        label: Option<String>,
+    ) -> Result<EncryptedData> {
+        let (kdf_algorithm, kdf_version) = Self::current_argon2_stamp(&kdf);
+        let aad = Self::metadata_aad(created_at, label.as_deref());
+        Ok(EncryptedData {
+            hidden: Self::encrypt_layer(plaintext, password, &kdf, None, &aad)?,
+            decoy: None,
+            fido2_challenge: None,
+            kdf,
+            kdf_algorithm,
+            kdf_version,
+            created_at,
+            label,
+        })
+    }
+
+    /// Encrypt with an injected salt and nonce instead of `OsRng`, so the
+    /// output is reproducible. Only for known-answer test vectors and
+    /// integration tests: reusing a salt/nonce pair for a real secret
+    /// destroys AES-GCM's security guarantees. Never carries `created_at`/
+    /// `label` -- a timestamp would make a "known-answer" vector different
+    /// on every run.
+    pub fn encrypt_deterministic(
+        plaintext: &[u8],
+        password: &str,
+        salt: &[u8],
+        nonce: &[u8],
+    ) -> Result<EncryptedData> {
+        let kdf = KdfParams::Argon2id;
+        let (kdf_algorithm, kdf_version) = Self::current_argon2_stamp(&kdf);
+        Ok(EncryptedData {
+            hidden: Self::encrypt_layer_with(
+                plaintext,
+                password,
+                &kdf,
+                None,
+                salt.to_vec(),
+                nonce.to_vec(),
+                &[],
+            )?,
+            decoy: None,
+            fido2_challenge: None,
+            kdf,
+            kdf_algorithm,
+            kdf_version,
+            created_at: None,
+            label: None,
+        })
+    }
+
+    /// Encrypt a secret with a password and a FIDO2 security key: a random
+    /// challenge is generated and stored alongside the ciphertext, and its
+    /// hmac-secret response is mixed into the Argon2-derived key.
+    #[cfg(feature = "fido2")]
+    pub fn encrypt_with_fido2(
+        plaintext: &[u8],
+        password: &str,
+        created_at: Option<u64>,
+        label: Option<String>,
+    ) -> Result<EncryptedData> {
+        let mut challenge = [0u8; 32];
+        OsRng.fill_bytes(&mut challenge);
+        let hmac_secret = crate::fido2::hmac_secret_response(&challenge)?;
+        let kdf = KdfParams::Argon2id;
+        let (kdf_algorithm, kdf_version) = Self::current_argon2_stamp(&kdf);
+        let aad = Self::metadata_aad(created_at, label.as_deref());
+
+        Ok(EncryptedData {
+            hidden: Self::encrypt_layer(plaintext, password, &kdf, Some(&hmac_secret), &aad)?,
+            decoy: None,
+            fido2_challenge: Some(challenge.to_vec()),
+            kdf,
+            kdf_algorithm,
+            kdf_version,
+            created_at,
+            label,
+        })
+    }
+
+    /// Encrypt two independent secrets under two passwords into one payload:
+    /// the `decoy` layer is what a coerced user can hand over, the `hidden`
+    /// layer holds the real secret. `created_at`/`label` describe the
+    /// envelope as a whole and are bound into both layers' AEAD tags.
+    pub fn encrypt_layered(
+        hidden_plaintext: &[u8],
+        hidden_password: &str,
+        decoy_plaintext: &[u8],
+        decoy_password: &str,
+        created_at: Option<u64>,
+        label: Option<String>,
+    ) -> Result<EncryptedData> {
+        let kdf = KdfParams::Argon2id;
+        let (kdf_algorithm, kdf_version) = Self::current_argon2_stamp(&kdf);
+        let aad = Self::metadata_aad(created_at, label.as_deref());
+        Ok(EncryptedData {
+            hidden: Self::encrypt_layer(hidden_plaintext, hidden_password, &kdf, None, &aad)?,
+            decoy: Some(Self::encrypt_layer(
+                decoy_plaintext,
+                decoy_password,
+                &kdf,
+                None,
+                &aad,
+            )?),
+            fido2_challenge: None,
+            kdf,
+            kdf_algorithm,
+            kdf_version,
+            created_at,
+            label,
+        })
+    }
+
+    /// Add a decoy layer to an already-encrypted, non-layered payload,
+    /// without touching its `hidden` layer at all: the real password and
+    /// plaintext are never decrypted or re-encrypted. The decoy is encrypted
+    /// under `existing.kdf`, since that field describes both layers and
+    /// can't be changed without re-encrypting `hidden` too. Reuses
+    /// `existing.created_at`/`label` for the decoy's associated data too,
+    /// since both fields already describe the whole envelope, not just the
+    /// hidden layer.
+    pub fn add_decoy_layer(
+        mut existing: EncryptedData,
+        decoy_plaintext: &[u8],
+        decoy_password: &str,
+    ) -> Result<EncryptedData> {
+        if existing.decoy.is_some() {
+            return Err(QRCryptError::Encryption(
+                "this payload already has a decoy layer".to_string(),
+            ));
+        }
+        if existing.fido2_challenge.is_some() {
+            return Err(QRCryptError::Encryption(
+                "a FIDO2-protected payload can't also carry a decoy layer".to_string(),
+            ));
+        }
+        let aad = Self::metadata_aad(existing.created_at, existing.label.as_deref());
+        existing.decoy = Some(Self::encrypt_layer(
+            decoy_plaintext,
+            decoy_password,
+            &existing.kdf,
+            None,
+            &aad,
+        )?);
+        Ok(existing)
+    }
+
+    #[cfg(feature = "fido2")]
+    fn resolve_fido2_secret(data: &EncryptedData) -> Result<Option<[u8; 32]>> {
+        match &data.fido2_challenge {
+            Some(challenge) if challenge.len() == 32 => {
+                let mut buf = [0u8; 32];
+                buf.copy_from_slice(challenge);
+                Ok(Some(crate::fido2::hmac_secret_response(&buf)?))
+            }
+            Some(_) => Err(QRCryptError::KeyDerivation(
+                "stored FIDO2 challenge has an unexpected length".to_string(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(not(feature = "fido2"))]
+    fn resolve_fido2_secret(data: &EncryptedData) -> Result<Option<[u8; 32]>> {
+        if data.fido2_challenge.is_some() {
+            Err(QRCryptError::KeyDerivation(
+                "this payload requires a FIDO2 security key; rebuild qrcrypt with --features fido2"
+                    .to_string(),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Decrypt a non-layered payload, touching a FIDO2 key first if the
+    /// payload requires one. The returned buffer zeroizes itself on drop.
+    pub fn decrypt(data: &EncryptedData, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let fido2_secret = Self::resolve_fido2_secret(data)?;
+        let aad = Self::metadata_aad(data.created_at, data.label.as_deref());
+        Self::decrypt_layer(
+            &data.hidden,
+            password,
+            &data.kdf,
+            fido2_secret.as_ref(),
+            data.kdf_algorithm.as_deref(),
+            data.kdf_version,
+            &aad,
+        )
+    }
+
+    /// Decrypt a payload that may be layered. Both the decoy layer and the
+    /// hidden layer are always attempted, in the same order, so that timing
+    /// a call to `decrypt_layered` cannot reveal whether the password
+    /// unlocked the decoy or the hidden secret — that distinction is exactly
+    /// what plausible deniability needs to hide. Whichever plaintext isn't
+    /// returned is wiped when it falls out of scope.
+    ///
+    /// `decrypt_layered_burns_the_same_number_of_kdf_calls_regardless_of_outcome`
+    /// below documents this guarantee directly, since wall-clock timing can't
+    /// be asserted reliably in a unit test.
+    pub fn decrypt_layered(data: &EncryptedData, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let hidden_result = Self::decrypt(data, password);
+        let aad = Self::metadata_aad(data.created_at, data.label.as_deref());
+        let decoy_result = data.decoy.as_ref().map(|decoy| {
+            Self::decrypt_layer(
+                decoy,
+                password,
+                &data.kdf,
+                None,
+                data.kdf_algorithm.as_deref(),
+                data.kdf_version,
+                &aad,
+            )
+        });
+
+        match decoy_result {
+            Some(Ok(plaintext)) => Ok(plaintext),
+            _ => hidden_result,
+        }
+    }
+
+    /// Sanity-check that `OsRng` is actually producing varying output before
+    /// any key or nonce is drawn from it. Draws two 32-byte buffers and
+    /// rejects them if either is all-zero or if they're identical -- the
+    /// kind of failure a misconfigured embedded environment (no hardware
+    /// RNG, a broken `/dev/urandom`) could produce. Cheap enough to run on
+    /// every invocation; it's not a cryptographic RNG test, just a
+    /// fast-fail for "this is obviously not random."
+    pub fn self_test() -> Result<()> {
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        OsRng.fill_bytes(&mut a);
+        OsRng.fill_bytes(&mut b);
+
+        if a == [0u8; 32] || b == [0u8; 32] {
+            return Err(QRCryptError::Rng(
+                "OsRng returned an all-zero buffer".to_string(),
+            ));
+        }
+        if a == b {
+            return Err(QRCryptError::Rng(
+                "OsRng returned the same bytes twice in a row".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let plaintext = b"zoo zebra zone ...";
+        let data = Crypto::encrypt(plaintext, "correct horse").unwrap();
+        let recovered = Crypto::decrypt(&data, "correct horse").unwrap();
+        assert_eq!(recovered.as_slice(), plaintext);
+    }
+
+    #[test]
+    fn self_test_passes_against_the_real_osrng() {
+        Crypto::self_test().unwrap();
+    }
+
+    #[test]
+    fn wrong_password_fails() {
+        let data = Crypto::encrypt(b"secret", "right password").unwrap();
+        assert!(Crypto::decrypt(&data, "wrong password").is_err());
+    }
+
+    #[test]
+    fn wrong_password_is_reported_distinctly_from_corrupted_ciphertext() {
+        let mut data = Crypto::encrypt(b"secret", "right password").unwrap();
+        let err = Crypto::decrypt(&data, "wrong password").unwrap_err();
+        assert!(err.to_string().contains("incorrect password"));
+
+        data.hidden.ciphertext[0] ^= 0xff;
+        let err = Crypto::decrypt(&data, "right password").unwrap_err();
+        assert!(err.to_string().contains("corrupted data"));
+    }
+
+    #[test]
+    fn legacy_layer_without_a_key_commitment_falls_back_to_the_aes_gcm_tag_check() {
+        let mut data = Crypto::encrypt(b"secret", "right password").unwrap();
+        data.hidden.key_commitment = None;
+        let err = Crypto::decrypt(&data, "wrong password").unwrap_err();
+        assert!(err.to_string().contains("corrupted data"));
+        assert_eq!(
+            Crypto::decrypt(&data, "right password").unwrap().as_slice(),
+            b"secret"
+        );
+    }
+
+    #[test]
+    fn layered_decrypt_reveals_correct_layer() {
+        let data =
+            Crypto::encrypt_layered(b"hidden", "hidden-pw", b"decoy", "decoy-pw", None, None)
+                .unwrap();
+
+        assert_eq!(
+            Crypto::decrypt_layered(&data, "hidden-pw")
+                .unwrap()
+                .as_slice(),
+            b"hidden"
+        );
+        assert_eq!(
+            Crypto::decrypt_layered(&data, "decoy-pw")
+                .unwrap()
+                .as_slice(),
+            b"decoy"
+        );
+        assert!(Crypto::decrypt_layered(&data, "neither-pw").is_err());
+    }
+
+    #[test]
+    fn add_decoy_layer_leaves_the_hidden_layer_untouched_and_both_passwords_work() {
+        let hidden = Crypto::encrypt(b"hidden", "hidden-pw").unwrap();
+        let layered = Crypto::add_decoy_layer(hidden.clone(), b"decoy", "decoy-pw").unwrap();
+
+        assert_eq!(layered.hidden.salt, hidden.hidden.salt);
+        assert_eq!(layered.hidden.nonce, hidden.hidden.nonce);
+        assert_eq!(layered.hidden.ciphertext, hidden.hidden.ciphertext);
+        assert_eq!(
+            Crypto::decrypt_layered(&layered, "hidden-pw")
+                .unwrap()
+                .as_slice(),
+            b"hidden"
+        );
+        assert_eq!(
+            Crypto::decrypt_layered(&layered, "decoy-pw")
+                .unwrap()
+                .as_slice(),
+            b"decoy"
+        );
+    }
+
+    #[test]
+    fn add_decoy_layer_rejects_a_payload_that_already_has_one() {
+        let data =
+            Crypto::encrypt_layered(b"hidden", "hidden-pw", b"decoy", "decoy-pw", None, None)
+                .unwrap();
+        let err = Crypto::add_decoy_layer(data, b"second-decoy", "second-pw").unwrap_err();
+        assert!(err.to_string().contains("already has a decoy layer"));
+    }
+
+    #[test]
+    fn add_decoy_layer_rejects_a_fido2_protected_payload() {
+        let mut data = Crypto::encrypt(b"hidden", "hidden-pw").unwrap();
+        data.fido2_challenge = Some(vec![0u8; 32]);
+        let err = Crypto::add_decoy_layer(data, b"decoy", "decoy-pw").unwrap_err();
+        assert!(err.to_string().contains("FIDO2"));
+    }
+
+    #[test]
+    fn scrypt_kdf_round_trips() {
+        let kdf = KdfParams::Scrypt {
+            log_n: 10,
+            r: 8,
+            p: 1,
+        };
+        let data =
+            Crypto::encrypt_with_kdf(b"seed words", "correct horse", kdf, None, None).unwrap();
+        assert_eq!(
+            Crypto::decrypt(&data, "correct horse").unwrap().as_slice(),
+            b"seed words"
+        );
+    }
+
+    #[test]
+    fn created_at_and_label_round_trip_and_decrypt_normally() {
+        let data = Crypto::encrypt_with_kdf(
+            b"seed words",
+            "correct horse",
+            KdfParams::Argon2id,
+            Some(1_700_000_000),
+            Some("backup phrase 2024".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(data.created_at, Some(1_700_000_000));
+        assert_eq!(data.label.as_deref(), Some("backup phrase 2024"));
+        assert_eq!(
+            Crypto::decrypt(&data, "correct horse").unwrap().as_slice(),
+            b"seed words"
+        );
+    }
+
+    #[test]
+    fn tampering_with_created_at_or_label_breaks_decryption() {
+        let data = Crypto::encrypt_with_kdf(
+            b"seed words",
+            "correct horse",
+            KdfParams::Argon2id,
+            Some(1_700_000_000),
+            Some("original label".to_string()),
+        )
+        .unwrap();
+
+        let mut tampered_time = data.clone();
+        tampered_time.created_at = Some(1_700_000_001);
+        assert!(Crypto::decrypt(&tampered_time, "correct horse").is_err());
+
+        let mut tampered_label = data.clone();
+        tampered_label.label = Some("forged label".to_string());
+        assert!(Crypto::decrypt(&tampered_label, "correct horse").is_err());
+
+        let mut stripped = data;
+        stripped.created_at = None;
+        stripped.label = None;
+        assert!(Crypto::decrypt(&stripped, "correct horse").is_err());
+    }
+
+    #[test]
+    fn omitting_created_at_and_label_keeps_behavior_unchanged() {
+        let data = Crypto::encrypt(b"seed words", "correct horse").unwrap();
+        assert_eq!(data.created_at, None);
+        assert_eq!(data.label, None);
+        assert_eq!(
+            Crypto::decrypt(&data, "correct horse").unwrap().as_slice(),
+            b"seed words"
+        );
+    }
+
+    #[test]
+    fn encrypt_records_the_argon2_variant_and_version_used() {
+        let data = Crypto::encrypt(b"seed words", "correct horse").unwrap();
+        assert_eq!(data.kdf_algorithm.as_deref(), Some("argon2id"));
+        assert_eq!(data.kdf_version, Some(u32::from(Version::V0x13)));
+    }
+
+    /// Simulates an argon2 crate upgrade that changes what `Argon2::default()`
+    /// produces: builds a payload whose key was derived with a *different*
+    /// Argon2 version than today's default, with that version recorded in
+    /// `kdf_version` the way a real encrypt would. Decryption must reconstruct
+    /// that exact version from the recorded field rather than quietly using
+    /// whatever `Argon2::default()` happens to be right now.
+    #[test]
+    fn decrypt_reconstructs_the_recorded_argon2_version_even_when_it_differs_from_the_current_default(
+    ) {
+        assert_ne!(Version::V0x10, Version::default());
+
+        let password = "correct horse";
+        let salt = vec![7u8; 16];
+        let nonce = vec![9u8; 12];
+        let plaintext = b"seed words";
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x10, Params::default())
+            .hash_password_into(password.as_bytes(), &salt, &mut *key)
+            .unwrap();
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()));
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .unwrap();
+
+        let data = EncryptedData {
+            hidden: Layer {
+                salt,
+                nonce,
+                ciphertext,
+                key_commitment: None,
+            },
+            decoy: None,
+            fido2_challenge: None,
+            kdf: KdfParams::Argon2id,
+            kdf_algorithm: Some("argon2id".to_string()),
+            kdf_version: Some(Version::V0x10.into()),
+            created_at: None,
+            label: None,
+        };
+
+        assert_eq!(
+            Crypto::decrypt(&data, password).unwrap().as_slice(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn legacy_payload_without_recorded_argon2_fields_falls_back_to_the_current_default() {
+        let mut data = Crypto::encrypt(b"seed words", "correct horse").unwrap();
+        data.kdf_algorithm = None;
+        data.kdf_version = None;
+        assert_eq!(
+            Crypto::decrypt(&data, "correct horse").unwrap().as_slice(),
+            b"seed words"
+        );
+    }
+
+    #[test]
+    fn unknown_kdf_reports_a_clear_upgrade_error() {
+        let mut data = Crypto::encrypt(b"seed words", "correct horse").unwrap();
+        data.kdf = KdfParams::Unknown;
+        let err = Crypto::decrypt(&data, "correct horse").unwrap_err();
+        assert!(err.to_string().contains("newer qrcrypt"));
+    }
+
+    #[test]
+    fn unrecognized_kdf_identifier_deserializes_as_unknown_instead_of_failing() {
+        let json = r#""some-future-kdf""#;
+        let parsed: KdfParams = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed, KdfParams::Unknown);
+    }
+
+    #[test]
+    fn known_answer_vectors_catch_format_breakage() {
+        // Fixed (password, salt, nonce, plaintext) -> expected ciphertext
+        // triples. If the Argon2id parameters, the AES-GCM wiring, or the
+        // `Layer` byte layout ever change, one of these will fail instead of
+        // the drift going unnoticed.
+        struct Vector {
+            password: &'static str,
+            salt: &'static str,
+            nonce: &'static str,
+            plaintext: &'static [u8],
+            ciphertext: &'static str,
+        }
+
+        let vectors = [
+            Vector {
+                password: "correct horse battery staple",
+                salt: "000102030405060708090a0b0c0d0e0f",
+                nonce: "000102030405060708090a0b",
+                plaintext: b"zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+                ciphertext: "a5daa03df07b7e55be3b3c712813c6e9743f385ade50e07841190c82a0640\
+00014a1b0fbabf6e888187f479ae949d1c4260f5f1004707cff18e0ba79898ecdd60c",
+            },
+            Vector {
+                password: "test-vector-password",
+                salt: "ffeeddccbbaa99887766554433221100",
+                nonce: "0f0e0d0c0b0a090807060504",
+                plaintext: b"hello",
+                ciphertext: "058992599bf4def2161220e1ad5d75da637bf2e39a",
+            },
+        ];
+
+        for v in vectors {
+            let salt = hex::decode(v.salt).unwrap();
+            let nonce = hex::decode(v.nonce).unwrap();
+            let data =
+                Crypto::encrypt_deterministic(v.plaintext, v.password, &salt, &nonce).unwrap();
+            assert_eq!(hex::encode(&data.hidden.ciphertext), v.ciphertext);
+            assert_eq!(
+                Crypto::decrypt(&data, v.password).unwrap().as_slice(),
+                v.plaintext
+            );
+        }
+    }
+
+    #[test]
+    fn decrypt_layered_burns_the_same_number_of_kdf_calls_regardless_of_outcome() {
+        let data =
+            Crypto::encrypt_layered(b"hidden", "hidden-pw", b"decoy", "decoy-pw", None, None)
+                .unwrap();
+
+        reset_kdf_calls();
+        Crypto::decrypt_layered(&data, "decoy-pw").unwrap();
+        let decoy_hit = kdf_call_count();
+
+        reset_kdf_calls();
+        Crypto::decrypt_layered(&data, "hidden-pw").unwrap();
+        let hidden_hit = kdf_call_count();
+
+        reset_kdf_calls();
+        assert!(Crypto::decrypt_layered(&data, "neither-pw").is_err());
+        let miss = kdf_call_count();
+
+        assert_eq!(decoy_hit, hidden_hit);
+        assert_eq!(decoy_hit, miss);
+    }
+}
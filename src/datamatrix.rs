@@ -0,0 +1,238 @@
+//! Optional DataMatrix (ECC 200) symbology, offered as an alternative to QR
+//! for tiny physical tags (engraved metal plates down to ~12x12mm) where
+//! DataMatrix packs more data per area and is what industrial engravers
+//! expect. Gated behind the `datamatrix` cargo feature so a default build
+//! doesn't pull in the `datamatrix` crate; with the feature off,
+//! `render_image` fails with a clear "rebuild with --features datamatrix"
+//! error and `detect_and_decode` quietly reports nothing found, the same
+//! way a QR-only build behaves today.
+//!
+//! The `datamatrix` crate has no general-purpose visual detector (its own
+//! docs say so: "No visual detection is currently implemented"), so
+//! `detect_and_decode` only handles an axis-aligned symbol on a plain
+//! background -- our own renderer's output, or a flatbed scan -- by finding
+//! the bounding box of dark pixels and trying each standard square module
+//! count until one decodes. A perspective-distorted photo of an engraved
+//! tag needs real detection this crate doesn't provide.
+
+use image::{DynamicImage, ImageBuffer, Rgba};
+
+use crate::error::{QRCryptError, Result};
+
+pub type RgbaImage = ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+/// Quiet zone around a rendered DataMatrix, in modules. `datamatrix`'s own
+/// `examples/image.rs` leaves one module of background on each side, the
+/// minimum ISO/IEC 16022 recommends; QR's 4-module default would waste most
+/// of the point of using DataMatrix on a small tag in the first place.
+#[cfg(feature = "datamatrix")]
+const QUIET_ZONE_MODULES: u32 = 1;
+
+/// Standard square symbol sizes (module counts per side) `datamatrix`
+/// chooses from when `render_image` calls `SymbolList::default()
+/// .enforce_square()`. `detect_and_decode` tries each of these in turn when
+/// guessing a scanned symbol's size, since there's no detector to read it
+/// off the image directly.
+#[cfg(feature = "datamatrix")]
+const SQUARE_MODULE_COUNTS: [u32; 24] = [
+    10, 12, 14, 16, 18, 20, 22, 24, 26, 32, 36, 40, 44, 48, 52, 64, 72, 80, 88, 96, 104, 120, 132,
+    144,
+];
+
+/// Render `payload` as a DataMatrix (ECC 200) symbol, `px_per_module`
+/// pixels per module, in `fg` on `bg`. The smallest square symbol that fits
+/// `payload` is chosen automatically, the same way `qr::render_qr_image`
+/// picks the smallest QR version.
+#[cfg(feature = "datamatrix")]
+pub fn render_image(
+    payload: &[u8],
+    px_per_module: u32,
+    fg: Rgba<u8>,
+    bg: Rgba<u8>,
+) -> Result<RgbaImage> {
+    use datamatrix::{DataMatrix, SymbolList};
+
+    let code = DataMatrix::encode(payload, SymbolList::default().enforce_square())
+        .map_err(|e| QRCryptError::QRGeneration(format!("DataMatrix encoding failed: {e:?}")))?;
+    let bitmap = code.bitmap();
+    let side_modules = bitmap.width() as u32 + 2 * QUIET_ZONE_MODULES;
+    let side_px = side_modules * px_per_module;
+
+    let mut image: RgbaImage = ImageBuffer::from_pixel(side_px, side_px, bg);
+    for (mx, my) in bitmap.pixels() {
+        let x0 = (mx as u32 + QUIET_ZONE_MODULES) * px_per_module;
+        let y0 = (my as u32 + QUIET_ZONE_MODULES) * px_per_module;
+        for dy in 0..px_per_module {
+            for dx in 0..px_per_module {
+                image.put_pixel(x0 + dx, y0 + dy, fg);
+            }
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(not(feature = "datamatrix"))]
+pub fn render_image(
+    _payload: &[u8],
+    _px_per_module: u32,
+    _fg: Rgba<u8>,
+    _bg: Rgba<u8>,
+) -> Result<RgbaImage> {
+    Err(QRCryptError::QRGeneration(
+        "--symbology datamatrix requires qrcrypt to be rebuilt with --features datamatrix"
+            .to_string(),
+    ))
+}
+
+/// Try to find and decode a single DataMatrix symbol in `image`, returning
+/// its raw payload bytes. `None` covers both "there's no DataMatrix here"
+/// and "the feature is off" -- this is only ever a fallback after a QR scan
+/// already failed, so there's nothing useful to report beyond that.
+#[cfg(feature = "datamatrix")]
+pub fn detect_and_decode(image: &DynamicImage) -> Option<Vec<u8>> {
+    use datamatrix::DataMatrix;
+
+    // `render_image`'s output is always square, and both it and
+    // `qr::QRGenerator::generate_captioned_qr` place the symbol flush at the
+    // canvas's top-left, spanning its full width -- a caption (if any) is
+    // drawn below, not beside. Crop to that top `width x width` square
+    // before finding the dark bounding box, so caption text doesn't widen
+    // it into a non-square blob the module-count search below can't match.
+    let full = image.to_luma8();
+    let side = full.width().min(full.height());
+    let gray = image::imageops::crop_imm(&full, 0, 0, full.width(), side).to_image();
+    let (min, max) = luma_range(&gray)?;
+    if max <= min {
+        return None;
+    }
+    let threshold = min + (max - min) / 2;
+    let (x0, y0, x1, y1) = dark_bounding_box(&gray, threshold)?;
+    let width_px = (x1 - x0 + 1) as f32;
+    let height_px = (y1 - y0 + 1) as f32;
+
+    for &modules in &SQUARE_MODULE_COUNTS {
+        let px_per_module = width_px / modules as f32;
+        if px_per_module < 1.0 {
+            continue;
+        }
+        if (height_px / px_per_module - modules as f32).abs() > 1.0 {
+            continue;
+        }
+        let pixels = sample_grid(&gray, x0, y0, px_per_module, modules, threshold);
+        if let Ok(data) = DataMatrix::decode(&pixels, modules as usize) {
+            return Some(data);
+        }
+    }
+    None
+}
+
+#[cfg(not(feature = "datamatrix"))]
+pub fn detect_and_decode(_image: &DynamicImage) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(feature = "datamatrix")]
+fn luma_range(image: &image::GrayImage) -> Option<(u8, u8)> {
+    let mut min = 255u8;
+    let mut max = 0u8;
+    for pixel in image.pixels() {
+        let v = pixel.0[0];
+        min = min.min(v);
+        max = max.max(v);
+    }
+    if image.width() == 0 || image.height() == 0 {
+        None
+    } else {
+        Some((min, max))
+    }
+}
+
+#[cfg(feature = "datamatrix")]
+fn dark_bounding_box(image: &image::GrayImage, threshold: u8) -> Option<(u32, u32, u32, u32)> {
+    let (mut x0, mut y0) = (u32::MAX, u32::MAX);
+    let (mut x1, mut y1) = (0u32, 0u32);
+    let mut found = false;
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel.0[0] <= threshold {
+            found = true;
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+        }
+    }
+    found.then_some((x0, y0, x1, y1))
+}
+
+#[cfg(feature = "datamatrix")]
+fn sample_grid(
+    image: &image::GrayImage,
+    x0: u32,
+    y0: u32,
+    px_per_module: f32,
+    modules: u32,
+    threshold: u8,
+) -> Vec<bool> {
+    let mut pixels = Vec::with_capacity((modules * modules) as usize);
+    for row in 0..modules {
+        for col in 0..modules {
+            let x = x0 as f32 + (col as f32 + 0.5) * px_per_module;
+            let y = y0 as f32 + (row as f32 + 0.5) * px_per_module;
+            let x = (x.round() as u32).min(image.width() - 1);
+            let y = (y.round() as u32).min(image.height() - 1);
+            pixels.push(image.get_pixel(x, y).0[0] <= threshold);
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "datamatrix")]
+    fn render_image_round_trips_through_detect_and_decode() {
+        let payload = b"datamatrix round trip test";
+        let black = Rgba([0, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        let image = render_image(payload, 8, black, white).unwrap();
+
+        let decoded = detect_and_decode(&DynamicImage::ImageRgba8(image)).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    #[cfg(feature = "datamatrix")]
+    fn detect_and_decode_ignores_a_caption_below_the_symbol() {
+        let payload = b"captioned datamatrix test";
+        let black = Rgba([0, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        let symbol = render_image(payload, 8, black, white).unwrap();
+
+        // Stand in for `qr::QRGenerator::generate_captioned_qr`'s layout: the
+        // symbol at (0, 0), a caption-shaped band of dark pixels below it,
+        // spanning the full width -- this used to widen the dark bounding
+        // box into a non-square blob no module count could match.
+        let mut captioned: RgbaImage =
+            ImageBuffer::from_pixel(symbol.width(), symbol.height() + 20, white);
+        image::imageops::overlay(&mut captioned, &symbol, 0, 0);
+        for x in 0..captioned.width() {
+            for y in symbol.height()..symbol.height() + 10 {
+                captioned.put_pixel(x, y, black);
+            }
+        }
+
+        let decoded = detect_and_decode(&DynamicImage::ImageRgba8(captioned)).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    #[cfg(not(feature = "datamatrix"))]
+    fn render_image_without_the_feature_errors_with_a_rebuild_hint() {
+        let err = render_image(b"payload", 8, Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--features datamatrix"));
+    }
+}
@@ -0,0 +1,361 @@
+//! BIP32/BIP39/BIP85 derivation, used to turn a real seed phrase into a
+//! deterministic decoy mnemonic instead of generating the decoy randomly.
+//! See <https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki>.
+
+use std::str::FromStr;
+
+use bip32::{DerivationPath, PrivateKey, PublicKey, XPrv};
+use bip39::{Language, Mnemonic};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{QRCryptError, Result};
+
+/// Collapse runs of whitespace to single spaces and apply Unicode NFKD, the
+/// normalization BIP39 requires before a mnemonic is hashed or split.
+/// Without this, the same phrase typed on two devices that pre-normalize
+/// differently (e.g. a composed "é" from one keyboard vs. a decomposed
+/// "e" and a combining acute from another) hashes to different bytes, so
+/// `validate_full_bip39_mnemonic`, `Crypto::encrypt`, and
+/// `shamir::split_secret` all apply this first and split/encrypt the
+/// normalized form, making reconstruction deterministic regardless of how
+/// the phrase was originally typed.
+pub fn normalize_seed_phrase(mnemonic: &str) -> String {
+    mnemonic
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .nfkd()
+        .collect()
+}
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// The HMAC key BIP85 mixes in to turn a derived private key into entropy,
+/// fixed by the spec so implementations agree on the output.
+const BIP85_ENTROPY_KEY: &[u8] = b"bip-entropy-from-k";
+
+/// BIP39 application number under BIP85's `m/83696968'/application'/...` tree.
+const BIP85_BIP39_APPLICATION: u32 = 39;
+
+/// Derive the standard BIP32 master key fingerprint (`RIPEMD160(SHA256(pubkey))`,
+/// truncated to 4 bytes) for a mnemonic plus optional passphrase, so it can be
+/// checked against the fingerprint a hardware wallet shows for the same words.
+pub fn seed_fingerprint(mnemonic: &str, passphrase: &str) -> Result<String> {
+    let mnemonic = Mnemonic::parse_normalized(mnemonic)
+        .map_err(|e| QRCryptError::InvalidFormat(format!("not a valid BIP39 mnemonic: {e}")))?;
+    let seed = mnemonic.to_seed_normalized(passphrase);
+    let master = XPrv::new(seed).map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+    let fingerprint = master.private_key().public_key().fingerprint();
+    Ok(hex::encode(fingerprint))
+}
+
+/// Prefix marking a `SecretData` payload as a bundled mnemonic + BIP39
+/// passphrase rather than a plain secret, so `split_mnemonic_and_passphrase`
+/// doesn't misinterpret an unrelated secret that happens to contain the
+/// separator byte.
+const BUNDLE_MAGIC: &[u8] = b"qrcrypt:mnemonic+passphrase:v1:";
+
+/// Byte separating the mnemonic from the passphrase inside a bundle. Chosen
+/// as the ASCII unit separator, which can't appear in BIP39 wordlist text or
+/// a typed passphrase.
+const BUNDLE_SEPARATOR: u8 = 0x1f;
+
+/// Pack a mnemonic and its BIP39 passphrase into one byte string for
+/// encryption, so they travel together as a single `SecretData` and
+/// `split_mnemonic_and_passphrase` can recover both after decryption.
+pub fn bundle_mnemonic_and_passphrase(mnemonic: &str, passphrase: &str) -> Vec<u8> {
+    let mut bundle = BUNDLE_MAGIC.to_vec();
+    bundle.extend_from_slice(mnemonic.as_bytes());
+    bundle.push(BUNDLE_SEPARATOR);
+    bundle.extend_from_slice(passphrase.as_bytes());
+    bundle
+}
+
+/// Split a decrypted payload back into `(mnemonic, passphrase)` if it was
+/// produced by `bundle_mnemonic_and_passphrase`. Returns `None` for a plain
+/// secret that was never bundled.
+pub fn split_mnemonic_and_passphrase(payload: &[u8]) -> Option<(&str, &str)> {
+    let rest = payload.strip_prefix(BUNDLE_MAGIC)?;
+    let separator = rest.iter().position(|&b| b == BUNDLE_SEPARATOR)?;
+    let mnemonic = std::str::from_utf8(&rest[..separator]).ok()?;
+    let passphrase = std::str::from_utf8(&rest[separator + 1..]).ok()?;
+    Some((mnemonic, passphrase))
+}
+
+/// Validate that `mnemonic` really is a usable BIP39 phrase: every word is
+/// in the wordlist and the checksum bits match. Used to confirm a derived
+/// decoy is a seed phrase a wallet would accept, not just entropy that
+/// looks like one.
+pub fn validate_full_bip39_mnemonic(mnemonic: &str) -> Result<()> {
+    Mnemonic::parse_normalized(mnemonic)
+        .map(|_| ())
+        .map_err(|e| QRCryptError::InvalidFormat(format!("not a valid BIP39 mnemonic: {e}")))
+}
+
+/// Check every word of `mnemonic` against the English BIP39 wordlist and
+/// report the 1-based position of the first one that isn't in it, with a
+/// typo suggestion if one looks plausible. `validate_full_bip39_mnemonic`
+/// also rejects an unknown word, but via the bip39 crate's error, which
+/// doesn't say which of a 24-word phrase is wrong; this is meant to run
+/// first and give that positional context before falling through to the
+/// checksum check.
+pub fn validate_bip39_words(mnemonic: &str) -> Result<()> {
+    let wordlist = Language::English.word_list();
+    for (i, word) in mnemonic.split_whitespace().enumerate() {
+        if !wordlist.contains(&word) {
+            return Err(QRCryptError::InvalidFormat(
+                match find_word_suggestions(word).first() {
+                    Some(suggestion) => format!(
+                        "word {} '{word}' is not in the BIP39 list; did you mean {suggestion}?",
+                        i + 1
+                    ),
+                    None => format!("word {} '{word}' is not in the BIP39 list", i + 1),
+                },
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Up to the 3 wordlist entries closest to `word` by Levenshtein distance,
+/// most plausible first. Only candidates within edit distance 2 are
+/// considered at all; ties are broken by longest shared prefix, since a
+/// transposed or dropped letter near the end of a word is a much more
+/// common typo than one near the start.
+fn find_word_suggestions(word: &str) -> Vec<&'static str> {
+    let mut candidates: Vec<(&'static str, usize, usize)> = Language::English
+        .word_list()
+        .iter()
+        .filter_map(|&candidate| {
+            let distance = calculate_edit_distance(word, candidate);
+            (distance <= 2).then_some((candidate, distance, shared_prefix_len(word, candidate)))
+        })
+        .collect();
+    candidates.sort_by_key(|&(_, distance, prefix_len)| (distance, usize::MAX - prefix_len));
+    candidates
+        .into_iter()
+        .take(3)
+        .map(|(candidate, _, _)| candidate)
+        .collect()
+}
+
+/// Number of leading characters `a` and `b` have in common.
+fn shared_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count()
+}
+
+/// Levenshtein edit distance between `a` and `b`: the minimum number of
+/// single-character insertions, deletions, or substitutions to turn one
+/// into the other.
+fn calculate_edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut prev_diag = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let above = row[j];
+            row[j] = (row[j - 1] + 1).min(above + 1).min(prev_diag + cost);
+            prev_diag = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Derive a deterministic decoy mnemonic from a real BIP39 seed phrase via
+/// BIP85: the real mnemonic's BIP39 seed becomes a BIP32 master key, which
+/// is walked down the hardened path `m/83696968'/39'/0'/{word_count}'/{index}'`
+/// to produce entropy for a new mnemonic with the same word count. The same
+/// `(real_mnemonic, index)` pair always derives the same decoy, so a user
+/// can prove after the fact that it was derived rather than funded.
+pub fn bip85_decoy_mnemonic(real_mnemonic: &str, index: u32) -> Result<String> {
+    let mnemonic = Mnemonic::parse_normalized(real_mnemonic)
+        .map_err(|e| QRCryptError::InvalidFormat(format!("not a valid BIP39 mnemonic: {e}")))?;
+    let word_count = mnemonic.word_count() as u32;
+    let entropy_len = bip39_entropy_len(word_count)?;
+
+    let seed = mnemonic.to_seed_normalized("");
+    let master = XPrv::new(seed).map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+    let path = format!("m/83696968'/{BIP85_BIP39_APPLICATION}'/0'/{word_count}'/{index}'");
+    let entropy = derive_bip85_entropy(&master, &path)?;
+
+    let decoy = Mnemonic::from_entropy(&entropy[..entropy_len])
+        .map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+    Ok(decoy.to_string())
+}
+
+/// Walk `master` down `path` and HMAC the resulting private key per BIP85,
+/// returning the full 64 bytes of entropy (callers truncate to what they need).
+fn derive_bip85_entropy(master: &XPrv, path: &str) -> Result<[u8; 64]> {
+    let path =
+        DerivationPath::from_str(path).map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+    let mut xprv = master.clone();
+    for child in path {
+        xprv = xprv
+            .derive_child(child)
+            .map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+    }
+
+    let mut mac =
+        HmacSha512::new_from_slice(BIP85_ENTROPY_KEY).expect("HMAC accepts a key of any length");
+    mac.update(&xprv.private_key().to_bytes());
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// BIP39 entropy length in bytes for a given mnemonic word count.
+fn bip39_entropy_len(word_count: u32) -> Result<usize> {
+    match word_count {
+        12 => Ok(16),
+        15 => Ok(20),
+        18 => Ok(24),
+        21 => Ok(28),
+        24 => Ok(32),
+        other => Err(QRCryptError::InvalidFormat(format!(
+            "unsupported BIP39 word count: {other}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Published BIP85 test vectors (BIP-0085, "Test Vectors" section),
+    // derived straight from the spec's fixed master xprv so this test
+    // doesn't depend on our own BIP39-seed derivation being correct too.
+    const TEST_VECTOR_XPRV: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    #[test]
+    fn bip85_entropy_matches_published_test_vectors() {
+        let master = XPrv::from_str(TEST_VECTOR_XPRV).unwrap();
+
+        let entropy = derive_bip85_entropy(&master, "m/83696968'/39'/0'/12'/0'").unwrap();
+        assert_eq!(
+            hex::encode(&entropy[..16]),
+            "6250b68daf746d12a24d58b4787a714b"
+        );
+
+        let entropy = derive_bip85_entropy(&master, "m/83696968'/39'/0'/18'/0'").unwrap();
+        assert_eq!(
+            hex::encode(&entropy[..24]),
+            "938033ed8b12698449d4bbca3c853c66b293ea1b1ce9d9dc"
+        );
+
+        let entropy = derive_bip85_entropy(&master, "m/83696968'/39'/0'/24'/0'").unwrap();
+        assert_eq!(
+            hex::encode(&entropy[..32]),
+            "ae131e2312cdc61331542efe0d1077bac5ea803adf24b313a4f0e48e9c51f37f"
+        );
+    }
+
+    #[test]
+    fn bip85_decoy_mnemonic_is_deterministic_and_differs_by_index() {
+        let real = Mnemonic::from_entropy(&[0x42; 32]).unwrap().to_string();
+
+        let decoy_a = bip85_decoy_mnemonic(&real, 0).unwrap();
+        let decoy_b = bip85_decoy_mnemonic(&real, 0).unwrap();
+        let decoy_c = bip85_decoy_mnemonic(&real, 1).unwrap();
+
+        assert_eq!(decoy_a, decoy_b);
+        assert_ne!(decoy_a, decoy_c);
+        assert_ne!(decoy_a, real);
+        validate_full_bip39_mnemonic(&decoy_a).unwrap();
+    }
+
+    #[test]
+    fn rejects_a_real_secret_that_is_not_a_bip39_mnemonic() {
+        let err = bip85_decoy_mnemonic("this is not a seed phrase", 0).unwrap_err();
+        assert!(err.to_string().contains("not a valid BIP39 mnemonic"));
+    }
+
+    #[test]
+    fn validate_full_bip39_mnemonic_rejects_bad_checksums() {
+        // Swapping the last word breaks the checksum even if every word is
+        // still in the wordlist.
+        let real = Mnemonic::from_entropy(&[0x42; 16]).unwrap().to_string();
+        let mut words: Vec<&str> = real.split(' ').collect();
+        let last = words.pop().unwrap();
+        let replacement = if last == "zoo" { "zebra" } else { "zoo" };
+        words.push(replacement);
+        let tampered = words.join(" ");
+
+        assert!(validate_full_bip39_mnemonic(&tampered).is_err());
+    }
+
+    #[test]
+    fn validate_bip39_words_reports_the_1_based_position_and_a_typo_suggestion() {
+        let err = validate_bip39_words("abandon abandon abandom abandon").unwrap_err();
+        assert!(err.to_string().contains("word 3 'abandom'"));
+        assert!(err.to_string().contains("did you mean abandon?"));
+    }
+
+    #[test]
+    fn validate_bip39_words_accepts_every_real_wordlist_entry() {
+        let real = Mnemonic::from_entropy(&[0x42; 16]).unwrap().to_string();
+        validate_bip39_words(&real).unwrap();
+    }
+
+    #[test]
+    fn calculate_edit_distance_matches_known_answers() {
+        assert_eq!(calculate_edit_distance("abandon", "abandon"), 0);
+        assert_eq!(calculate_edit_distance("abandon", "abandom"), 1);
+        assert_eq!(calculate_edit_distance("abandon", "abandan"), 1);
+        assert_eq!(calculate_edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn find_word_suggestions_ranks_single_letter_typos_and_transpositions_first() {
+        assert_eq!(find_word_suggestions("abandom").first(), Some(&"abandon"));
+        assert_eq!(find_word_suggestions("abadnon").first(), Some(&"abandon"));
+        assert_eq!(find_word_suggestions("zeebra").first(), Some(&"zebra"));
+    }
+
+    #[test]
+    fn find_word_suggestions_is_empty_for_a_word_unrelated_to_the_wordlist() {
+        assert!(find_word_suggestions("xyzxyzxyz").is_empty());
+    }
+
+    #[test]
+    fn seed_fingerprint_matches_known_answer_and_changes_with_passphrase() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                         abandon abandon about";
+        assert_eq!(seed_fingerprint(mnemonic, "").unwrap(), "73c5da0a");
+        assert_eq!(seed_fingerprint(mnemonic, "TREZOR").unwrap(), "b4e3f5ed");
+    }
+
+    #[test]
+    fn bundle_round_trips_mnemonic_and_passphrase() {
+        let bundle = bundle_mnemonic_and_passphrase("brief mnemonic words", "a passphrase");
+        let (mnemonic, passphrase) = split_mnemonic_and_passphrase(&bundle).unwrap();
+        assert_eq!(mnemonic, "brief mnemonic words");
+        assert_eq!(passphrase, "a passphrase");
+    }
+
+    #[test]
+    fn unbundled_secret_is_not_mistaken_for_a_bundle() {
+        assert!(split_mnemonic_and_passphrase(b"just a plain secret").is_none());
+    }
+
+    #[test]
+    fn normalize_seed_phrase_collapses_internal_whitespace() {
+        assert_eq!(
+            normalize_seed_phrase("abandon  abandon\tabandon"),
+            "abandon abandon abandon"
+        );
+    }
+
+    #[test]
+    fn normalize_seed_phrase_matches_for_composed_and_decomposed_accents() {
+        let composed = "caf\u{e9} abandon"; // "café", precomposed é (U+00E9)
+        let decomposed = "cafe\u{301} abandon"; // "e" + combining acute (U+0301)
+        assert_ne!(composed, decomposed);
+        assert_eq!(
+            normalize_seed_phrase(composed),
+            normalize_seed_phrase(decomposed)
+        );
+    }
+}
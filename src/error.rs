@@ -0,0 +1,40 @@
+use thiserror::Error;
+
+/// Top-level error type for all QRCrypt operations.
+#[derive(Debug, Error)]
+pub enum QRCryptError {
+    #[error("encryption failed: {0}")]
+    Encryption(String),
+
+    #[error("decryption failed: {0}")]
+    Decryption(String),
+
+    #[error("key derivation failed: {0}")]
+    KeyDerivation(String),
+
+    #[error("Shamir secret sharing error: {0}")]
+    Shamir(String),
+
+    #[error("SLIP-39 error: {0}")]
+    Slip39(String),
+
+    #[error("QR generation failed: {0}")]
+    QRGeneration(String),
+
+    #[error("QR scanning failed: {0}")]
+    QRScan(String),
+
+    #[error("system random number generator looks broken: {0}")]
+    Rng(String),
+
+    #[error("invalid data format: {0}")]
+    InvalidFormat(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("serialization error: {0}")]
+    Serialization(String),
+}
+
+pub type Result<T> = std::result::Result<T, QRCryptError>;
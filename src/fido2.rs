@@ -0,0 +1,30 @@
+//! FIDO2 hmac-secret support, used as a second key-derivation factor
+//! alongside the Argon2 password hash. Requires the `fido2` feature so
+//! default builds don't need USB/HID libraries.
+
+use ctap_hid_fido2::{Cfg, FidoKeyHidFactory};
+
+use crate::error::{QRCryptError, Result};
+
+/// Touch a connected FIDO2 security key and return the 32-byte hmac-secret
+/// response for `challenge`.
+pub fn hmac_secret_response(challenge: &[u8; 32]) -> Result<[u8; 32]> {
+    let device = FidoKeyHidFactory::create(&Cfg::init())
+        .map_err(|e| QRCryptError::KeyDerivation(format!("no FIDO2 security key found: {e}")))?;
+
+    crate::utils::print_info("Touch your security key to continue...");
+
+    let response = device
+        .get_hmac_secret(challenge)
+        .map_err(|e| QRCryptError::KeyDerivation(format!("FIDO2 touch failed: {e}")))?;
+
+    if response.len() != 32 {
+        return Err(QRCryptError::KeyDerivation(
+            "security key returned an unexpected hmac-secret length".to_string(),
+        ));
+    }
+
+    let mut secret = [0u8; 32];
+    secret.copy_from_slice(&response[..32]);
+    Ok(secret)
+}
@@ -0,0 +1,19 @@
+//! Library surface for the pieces of qrcrypt that make sense without a
+//! terminal: QR generation/scanning, encryption, Shamir/SLIP-39 splitting,
+//! and BIP39/BIP32 derivation. `cli` (the `clap` argument definitions and
+//! the subcommand handlers in `main.rs`) stays binary-only.
+
+pub mod crypto;
+pub mod datamatrix;
+pub mod derive;
+pub mod error;
+#[cfg(feature = "fido2")]
+pub mod fido2;
+pub mod pdf;
+pub mod qr;
+pub mod secret;
+pub mod shamir;
+pub mod signing;
+pub mod slip39;
+pub mod ur;
+pub mod utils;
@@ -0,0 +1,4973 @@
+mod cli;
+
+use qrcrypt::{crypto, derive, error, pdf, qr, secret, shamir, signing, slip39, utils};
+#[cfg(feature = "fido2")]
+use qrcrypt::fido2;
+
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::Parser;
+use qrcode::EcLevel;
+use serde_json::{json, Value};
+
+use cli::{
+    Cli, Command, EcLevelArg, KdfChoice, ModuleStyleArg, PaperSizeArg, SecretEncoding,
+    ShareEncodingArg, ShareFormat,
+};
+use crypto::{Crypto, EncryptedData, KdfParams, Layer};
+use error::{QRCryptError, Result};
+use qr::{QRData, QRGenerator, QRScanner, QrColors, ScannedShares};
+use secret::{Keyring, SecretData};
+use shamir::{reconstruct_secret, split_secret};
+use utils::{is_json_mode, print_error, print_info, print_success, print_warning};
+
+fn main() {
+    let cli = Cli::parse();
+    utils::set_json_mode(cli.json);
+    utils::set_quiet_mode(cli.quiet);
+    utils::set_verbose_mode(cli.verbose);
+
+    match run(cli) {
+        Ok(value) => {
+            if is_json_mode() {
+                println!("{value}");
+            }
+        }
+        Err(e) => {
+            if is_json_mode() {
+                println!("{}", json!({ "error": e.to_string() }));
+            } else {
+                print_error(&e.to_string());
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<Value> {
+    Crypto::self_test()?;
+
+    match cli.command {
+        Command::Encrypt {
+            secret,
+            output,
+            decoy_secret,
+            decoy_password,
+            decoy_bip85_index,
+            with_passphrase,
+            fido2,
+            test_vector,
+            kdf,
+            scrypt_n,
+            scrypt_r,
+            scrypt_p,
+            allow_weak_password,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            error_correction,
+            logo,
+            logo_max_fraction,
+            dry_run,
+            animated,
+            frames,
+            fps,
+            max_fragment,
+            size_mm,
+            dpi,
+            min_module_mm,
+            max_qr_version,
+            symbology,
+            sign_key,
+            raw_payload,
+            label,
+            no_timestamp,
+        } => handle_encrypt(
+            secret,
+            &output,
+            EncryptOptions {
+                decoy_secret,
+                decoy_password,
+                decoy_bip85_index,
+                with_passphrase,
+                fido2,
+                test_vector,
+                kdf,
+                scrypt_n,
+                scrypt_r,
+                scrypt_p,
+                allow_weak_password,
+                qr_color,
+                qr_background,
+                invert,
+                module_style,
+                fill_ratio,
+                error_correction,
+                logo,
+                logo_max_fraction,
+                dry_run,
+                animated,
+                frames,
+                fps,
+                max_fragment,
+                size_mm,
+                dpi,
+                min_module_mm,
+                max_qr_version,
+                symbology: symbology.into(),
+                sign_key,
+                raw_payload,
+                label,
+                no_timestamp,
+            },
+        ),
+        Command::Decrypt {
+            input,
+            expect_bip39,
+            entry,
+            shred,
+            encoding,
+        } => handle_decrypt(&input, expect_bip39, entry.as_deref(), shred, encoding),
+        Command::EncryptKeyring {
+            add,
+            output,
+            allow_weak_password,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            error_correction,
+            dry_run,
+            max_qr_version,
+        } => handle_encrypt_keyring(
+            add,
+            &output,
+            allow_weak_password,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            error_correction,
+            dry_run,
+            max_qr_version,
+        ),
+        Command::MergeLayers {
+            input,
+            output,
+            decoy_secret,
+            decoy_password,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            error_correction,
+            max_qr_version,
+        } => handle_merge_layers(
+            &input,
+            &output,
+            decoy_secret,
+            decoy_password,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            error_correction,
+            max_qr_version,
+        ),
+        Command::Estimate {
+            secret,
+            secret_file,
+            threshold,
+            total,
+            card,
+        } => handle_estimate(secret, secret_file.as_deref(), threshold, total, card),
+        Command::Split {
+            secret,
+            binary,
+            input,
+            threshold,
+            total,
+            ids,
+            share_encoding,
+            group,
+            groups_required,
+            output_dir,
+            format,
+            share_passwords,
+            share_password_file,
+            labels,
+            parity,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            border,
+            error_correction,
+            dpi,
+            font,
+            card_title,
+            card_subtitle,
+            plain_qr,
+            no_info,
+            info,
+            dry_run,
+            stealth,
+            words_only,
+            pdf,
+            per_page,
+            sign_key,
+            sheet,
+            paper_size,
+            card_back,
+            card_back_text,
+            with_verify,
+            password,
+            allow_weak_password,
+            symbology,
+        } => handle_split(
+            secret,
+            binary,
+            input.as_deref(),
+            &output_dir,
+            SplitOptions {
+                threshold,
+                total,
+                ids,
+                share_encoding,
+                group,
+                groups_required,
+                format,
+                share_passwords,
+                share_password_file,
+                labels,
+                parity,
+                qr_color,
+                qr_background,
+                invert,
+                module_style,
+                fill_ratio,
+                border,
+                error_correction,
+                dpi,
+                font,
+                card_title,
+                card_subtitle,
+                plain_qr,
+                no_info,
+                info,
+                dry_run,
+                stealth,
+                words_only,
+                pdf,
+                per_page,
+                sign_key,
+                sheet,
+                paper_size,
+                card_back,
+                card_back_text,
+                with_verify,
+                password,
+                allow_weak_password,
+                symbology: symbology.into(),
+            },
+        ),
+        Command::Reconstruct {
+            shares,
+            scan_dir,
+            words,
+            binary,
+            output,
+            diagnose,
+            stealth,
+            password,
+            ssss,
+            threshold,
+            verify_only,
+            info,
+            no_mask,
+            shred,
+            encoding,
+        } => handle_reconstruct(
+            &shares,
+            scan_dir.as_deref(),
+            words,
+            binary,
+            output.as_deref(),
+            ReconstructOptions {
+                diagnose,
+                stealth,
+                password,
+                ssss,
+                ssss_threshold: threshold,
+                verify_only,
+                info,
+                no_mask,
+                shred,
+                encoding,
+            },
+        ),
+        Command::Reshare {
+            shares,
+            threshold,
+            total,
+            ids,
+            output_dir,
+            dpi,
+            font,
+            card_title,
+            card_subtitle,
+        } => handle_reshare(
+            &shares,
+            threshold,
+            total,
+            ids,
+            &output_dir,
+            dpi,
+            font.as_deref(),
+            card_title.as_deref(),
+            card_subtitle.as_deref(),
+        ),
+        Command::Validate {
+            shares,
+            scan_dir,
+            count,
+            deep,
+            verify_key,
+        } => handle_validate(&shares, scan_dir.as_deref(), count, deep, verify_key.as_deref()),
+        Command::EncryptFile {
+            input,
+            output_dir,
+            allow_weak_password,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            structured_append,
+        } => handle_encrypt_file(
+            &input,
+            &output_dir,
+            allow_weak_password,
+            qr_color,
+            qr_background,
+            invert,
+            module_style,
+            fill_ratio,
+            structured_append,
+        ),
+        Command::DecryptFile {
+            input_dir,
+            output,
+            shred,
+        } => handle_decrypt_file(&input_dir, &output, shred),
+        Command::VerifyShare { share, info } => handle_verify_share(&share, &info),
+        Command::Inspect { input } => handle_inspect(&input),
+        Command::ValidatePhrase {
+            mnemonic,
+            with_passphrase,
+        } => handle_validate_phrase(mnemonic, with_passphrase),
+        Command::Calibrate { target_ms } => handle_calibrate(target_ms),
+        Command::Setup => handle_setup(),
+        Command::Keygen { output } => handle_keygen(&output),
+        Command::Verify {
+            payload,
+            signature,
+            pubkey,
+        } => handle_verify(&payload, &signature, &pubkey),
+    }
+}
+
+fn read_secret(provided: Option<String>) -> std::io::Result<SecretData> {
+    let text = match provided {
+        Some(s) => {
+            if !utils::is_wizard_mode() {
+                print_warning("passing --secret on the command line may leak it via shell history or process listings");
+            }
+            s
+        }
+        None => utils::prompt_password("Enter secret: ")?,
+    };
+    Ok(SecretData::from_string(normalize_if_seed_phrase(text)))
+}
+
+/// Apply `derive::normalize_seed_phrase` if `text` looks like a BIP39
+/// mnemonic, so a seed phrase is always validated, encrypted, and split in
+/// its normalized form; left untouched otherwise, since collapsing
+/// whitespace or reordering Unicode combining marks in an arbitrary
+/// password or note would silently change it.
+fn normalize_if_seed_phrase(text: String) -> String {
+    if looks_like_bip39_mnemonic(&text) {
+        derive::normalize_seed_phrase(&text)
+    } else {
+        text
+    }
+}
+
+/// Re-derive an `EncryptedData` with a fixed salt/nonce instead of `OsRng`,
+/// for reproducible test vectors. Gated on `QRCRYPT_INSECURE_TEST=1` so it
+/// can't be reached by accident outside of test harnesses.
+fn encrypt_test_vector(plaintext: &[u8], password: &str) -> Result<crypto::EncryptedData> {
+    if std::env::var("QRCRYPT_INSECURE_TEST").as_deref() != Ok("1") {
+        return Err(QRCryptError::InvalidFormat(
+            "--test-vector requires QRCRYPT_INSECURE_TEST=1 to be set".to_string(),
+        ));
+    }
+    let salt_hex = std::env::var("QRCRYPT_TEST_SALT_HEX").map_err(|_| {
+        QRCryptError::InvalidFormat("--test-vector requires QRCRYPT_TEST_SALT_HEX".to_string())
+    })?;
+    let nonce_hex = std::env::var("QRCRYPT_TEST_NONCE_HEX").map_err(|_| {
+        QRCryptError::InvalidFormat("--test-vector requires QRCRYPT_TEST_NONCE_HEX".to_string())
+    })?;
+    let salt = hex::decode(salt_hex)
+        .map_err(|e| QRCryptError::InvalidFormat(format!("QRCRYPT_TEST_SALT_HEX: {e}")))?;
+    let nonce = hex::decode(nonce_hex)
+        .map_err(|e| QRCryptError::InvalidFormat(format!("QRCRYPT_TEST_NONCE_HEX: {e}")))?;
+
+    Crypto::encrypt_deterministic(plaintext, password, &salt, &nonce)
+}
+
+/// Translate the `--kdf`/`--scrypt-*` flags into a `KdfParams`.
+fn resolve_kdf(kdf: KdfChoice, scrypt_n: u32, scrypt_r: u32, scrypt_p: u32) -> Result<KdfParams> {
+    match kdf {
+        KdfChoice::Argon2id => Ok(KdfParams::Argon2id),
+        KdfChoice::Scrypt => {
+            if !scrypt_n.is_power_of_two() {
+                return Err(QRCryptError::InvalidFormat(
+                    "--scrypt-n must be a power of two".to_string(),
+                ));
+            }
+            Ok(KdfParams::Scrypt {
+                log_n: scrypt_n.trailing_zeros() as u8,
+                r: scrypt_r,
+                p: scrypt_p,
+            })
+        }
+    }
+}
+
+/// Describe `kdf`'s cost parameters for the "Deriving key..." progress
+/// message `with_kdf_progress` prints, so a memory-heavy Argon2 run or a
+/// high `--scrypt-n` explains why it's slow instead of looking hung.
+fn describe_kdf_cost(kdf: &KdfParams) -> String {
+    match kdf {
+        KdfParams::Argon2id => format!(
+            "Argon2id, {} MiB",
+            argon2::Params::default().m_cost() / 1024
+        ),
+        KdfParams::Scrypt { log_n, .. } => format!("scrypt, N = 2^{log_n}"),
+        KdfParams::Unknown => "an unrecognized KDF".to_string(),
+    }
+}
+
+/// Options for `handle_encrypt`, grouped into a struct so the function
+/// doesn't accumulate an ever-growing flat argument list as flags are added.
+struct EncryptOptions {
+    decoy_secret: Option<String>,
+    decoy_password: Option<String>,
+    decoy_bip85_index: Option<u32>,
+    with_passphrase: bool,
+    fido2: bool,
+    test_vector: bool,
+    kdf: KdfChoice,
+    scrypt_n: u32,
+    scrypt_r: u32,
+    scrypt_p: u32,
+    allow_weak_password: bool,
+    qr_color: Option<String>,
+    qr_background: Option<String>,
+    invert: bool,
+    module_style: ModuleStyleArg,
+    fill_ratio: f32,
+    error_correction: Option<EcLevelArg>,
+    logo: Option<std::path::PathBuf>,
+    logo_max_fraction: f64,
+    dry_run: bool,
+    animated: Option<std::path::PathBuf>,
+    frames: u32,
+    fps: u32,
+    max_fragment: usize,
+    size_mm: Option<f32>,
+    dpi: u32,
+    min_module_mm: f32,
+    max_qr_version: i16,
+    symbology: qrcrypt::qr::Symbology,
+    sign_key: Option<std::path::PathBuf>,
+    raw_payload: bool,
+    label: Option<String>,
+    no_timestamp: bool,
+}
+
+/// Translate `--error-correction` into a `qrcode::EcLevel`, defaulting to
+/// the auto heuristic's `EcLevel::M` when the flag is omitted.
+fn resolve_ec_level(arg: Option<EcLevelArg>) -> EcLevel {
+    arg.map(EcLevel::from).unwrap_or(EcLevel::M)
+}
+
+/// The path `handle_encrypt` writes an `--sign-key` signature QR to,
+/// derived from the main `--output` the same way `QRGenerator::card_back_path`
+/// derives a back card's path: `<stem>-sig.<ext>` alongside it.
+fn signature_qr_path(output: &Path) -> std::path::PathBuf {
+    let stem = output
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("signature");
+    let extension = output.extension().and_then(|e| e.to_str()).unwrap_or("png");
+    let filename = format!("{stem}-sig.{extension}");
+    match output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.join(filename),
+        _ => std::path::PathBuf::from(filename),
+    }
+}
+
+/// Warn, before doing any KDF work, if a secret of `plaintext_len` bytes
+/// would project to a ciphertext whose encoded QR payload doesn't fit a
+/// single QR code at `ec`. Projects the ciphertext as `plaintext_len` plus
+/// the AES-GCM tag, plus the fixed-size salt/nonce/JSON overhead, by
+/// building a dummy payload of that shape rather than duplicating the real
+/// encoding. `has_logo` changes the advice: `QRGenerator::save_payload_auto`
+/// will split an oversized plain QR automatically, but `generate_with_logo`
+/// has no multi-part equivalent, so a logo'd encrypt still has to fail
+/// outright.
+fn warn_if_wont_fit_a_qr_code(plaintext_len: usize, has_logo: bool, ec: EcLevel) -> Result<()> {
+    let capacity = encrypted_qr_capacity(plaintext_len, ec);
+    if !capacity.fits {
+        if has_logo {
+            print_warning(&format!(
+                "a secret this size ({plaintext_len} bytes) won't fit in a single QR code at \
+                 EcLevel::{ec:?}; --logo doesn't support splitting across multiple QR codes, so \
+                 either drop --logo, pick a lower --error-correction, or write the secret to a \
+                 file and use `encrypt-file` instead"
+            ));
+        } else {
+            print_warning(&format!(
+                "a secret this size ({plaintext_len} bytes) won't fit in a single QR code at \
+                 EcLevel::{ec:?}; it will be split across multiple QR codes"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Project the `CapacityInfo` a real `encrypt` of a `plaintext_len`-byte
+/// secret at `ec` would produce, by building a dummy `EncryptedData` of the
+/// same shape (zero-filled salt/nonce, ciphertext sized for the AES-GCM tag)
+/// rather than duplicating the real encoding. Shared by
+/// `warn_if_wont_fit_a_qr_code` and `encrypt --dry-run`, neither of which
+/// needs to touch the real secret or password to answer a sizing question.
+fn encrypted_qr_capacity(plaintext_len: usize, ec: EcLevel) -> qr::CapacityInfo {
+    let dummy = EncryptedData {
+        hidden: Layer {
+            salt: vec![0; 32],
+            nonce: vec![0; 12],
+            ciphertext: vec![0; plaintext_len + 16],
+            key_commitment: Some(vec![0; 16]),
+        },
+        decoy: None,
+        fido2_challenge: None,
+        kdf: KdfParams::Argon2id,
+        kdf_algorithm: Some("argon2id".to_string()),
+        kdf_version: Some(0x13),
+        created_at: None,
+        label: None,
+    };
+    let payload = QRGenerator::encode_payload(&QRData::Encrypted(dummy))
+        .expect("a dummy EncryptedData always serializes");
+    QRGenerator::estimate_capacity(payload.len(), ec)
+}
+
+/// Describe a `CapacityInfo` for `--dry-run` output.
+fn describe_capacity(capacity: &qr::CapacityInfo) -> String {
+    match (capacity.fits, capacity.version, capacity.modules) {
+        (true, Some(version), Some(modules)) => {
+            format!("QR version {version}, {modules}x{modules} modules")
+        }
+        _ => "won't fit in a single QR code".to_string(),
+    }
+}
+
+fn handle_encrypt(secret: Option<String>, output: &Path, opts: EncryptOptions) -> Result<Value> {
+    let colors = resolve_qr_colors(
+        opts.qr_color.clone(),
+        opts.qr_background.clone(),
+        qr::DEFAULT_BORDER_MODULES,
+        opts.invert,
+        opts.module_style,
+        opts.fill_ratio,
+    )?;
+    let ec = resolve_ec_level(opts.error_correction);
+    let secret = read_secret(secret)?;
+    let secret = if opts.with_passphrase {
+        let mnemonic = secret.as_str().ok_or_else(|| {
+            QRCryptError::InvalidFormat(
+                "--with-passphrase requires the secret to be valid UTF-8 text".to_string(),
+            )
+        })?;
+        let passphrase = utils::prompt_password("Enter BIP39 passphrase (25th word): ")?;
+        SecretData::from_bytes(derive::bundle_mnemonic_and_passphrase(
+            mnemonic,
+            &passphrase,
+        ))
+    } else {
+        secret
+    };
+    warn_if_wont_fit_a_qr_code(secret.as_bytes().len(), opts.logo.is_some(), ec)?;
+    if opts.dry_run {
+        let capacity = encrypted_qr_capacity(secret.as_bytes().len(), ec);
+        print_info(&format!(
+            "Would write an encrypted QR code to {} ({})",
+            output.display(),
+            describe_capacity(&capacity),
+        ));
+        return Ok(json!({
+            "dry_run": true,
+            "output": output,
+            "fits": capacity.fits,
+            "qr_version": capacity.version,
+            "qr_modules": capacity.modules,
+        }));
+    }
+    let password = utils::prompt_password("Enter password: ")?;
+    if !utils::check_password_strength(&password, opts.allow_weak_password)? {
+        print_info("Aborted.");
+        return Ok(json!({ "aborted": true }));
+    }
+
+    let has_decoy = opts.decoy_password.is_some();
+    let created_at = if opts.no_timestamp {
+        None
+    } else {
+        Some(qr::unix_timestamp_now())
+    };
+    let label = opts.label.clone();
+    let encrypted = if opts.test_vector {
+        encrypt_test_vector(secret.as_bytes(), &password)?
+    } else if opts.fido2 {
+        #[cfg(feature = "fido2")]
+        {
+            let message = format!(
+                "Deriving key ({}, plus a FIDO2 touch)... this may take a few seconds",
+                describe_kdf_cost(&KdfParams::Argon2id)
+            );
+            utils::with_kdf_progress(&message, || {
+                Crypto::encrypt_with_fido2(secret.as_bytes(), &password, created_at, label)
+            })?
+        }
+        #[cfg(not(feature = "fido2"))]
+        {
+            return Err(QRCryptError::KeyDerivation(
+                "qrcrypt was built without FIDO2 support; rebuild with --features fido2"
+                    .to_string(),
+            ));
+        }
+    } else {
+        match opts.decoy_password {
+            Some(decoy_password) => {
+                let decoy_secret = match opts.decoy_bip85_index {
+                    Some(index) => {
+                        let real_mnemonic = secret.as_str().ok_or_else(|| {
+                            QRCryptError::InvalidFormat(
+                                "--decoy-bip85-index requires the real secret to be a valid BIP39 mnemonic".to_string(),
+                            )
+                        })?;
+                        let decoy = derive::bip85_decoy_mnemonic(real_mnemonic, index)?;
+                        derive::validate_full_bip39_mnemonic(&decoy)?;
+                        SecretData::from_string(decoy)
+                    }
+                    None => read_secret(opts.decoy_secret)?,
+                };
+                let message = format!(
+                    "Deriving keys for 2 layers ({})... this may take a few seconds",
+                    describe_kdf_cost(&KdfParams::Argon2id)
+                );
+                utils::with_kdf_progress(&message, || {
+                    Crypto::encrypt_layered(
+                        secret.as_bytes(),
+                        &password,
+                        decoy_secret.as_bytes(),
+                        &decoy_password,
+                        created_at,
+                        label,
+                    )
+                })?
+            }
+            None => {
+                let kdf = resolve_kdf(opts.kdf, opts.scrypt_n, opts.scrypt_r, opts.scrypt_p)?;
+                let message = format!(
+                    "Deriving key ({})... this may take a few seconds",
+                    describe_kdf_cost(&kdf)
+                );
+                utils::with_kdf_progress(&message, || {
+                    Crypto::encrypt_with_kdf(secret.as_bytes(), &password, kdf, created_at, label)
+                })?
+            }
+        }
+    };
+    let main_data = QRData::Encrypted(encrypted);
+    let payload = if opts.raw_payload {
+        match &main_data {
+            QRData::Encrypted(inner) => QRGenerator::encode_raw_payload(inner)?,
+            _ => unreachable!("main_data is always QRData::Encrypted here"),
+        }
+    } else {
+        QRGenerator::encode_payload(&main_data)?
+    };
+    if let Some(animated_path) = opts.animated {
+        QRGenerator::save_animated_qr(
+            &payload,
+            opts.frames,
+            opts.fps,
+            opts.max_fragment,
+            &animated_path,
+            colors,
+        )?;
+        print_success(&format!(
+            "Encrypted secret saved as an animated QR to {}",
+            animated_path.display()
+        ));
+        return Ok(json!({
+            "output": animated_path,
+            "animated": true,
+            "layered": has_decoy,
+            "fido2": opts.fido2,
+        }));
+    }
+    let outputs = match opts.logo {
+        Some(logo_path) => {
+            let logo = image::open(&logo_path)
+                .map_err(|e| QRCryptError::QRGeneration(format!("failed to read --logo: {e}")))?;
+            QRGenerator::generate_with_logo(&payload, &logo, output, colors, opts.logo_max_fraction)?;
+            vec![output.to_path_buf()]
+        }
+        None if opts.size_mm.is_some() => {
+            let size_mm = opts.size_mm.expect("just checked Some");
+            QRGenerator::generate_qr_physical_size(
+                &payload,
+                output,
+                colors,
+                ec,
+                size_mm,
+                opts.dpi,
+                opts.min_module_mm,
+            )?;
+            vec![output.to_path_buf()]
+        }
+        None => {
+            let output_dir = output.parent().filter(|p| !p.as_os_str().is_empty());
+            let prefix = output.file_stem().and_then(|s| s.to_str()).ok_or_else(|| {
+                QRCryptError::InvalidFormat(format!("{} has no usable file stem", output.display()))
+            })?;
+            QRGenerator::save_payload_auto(
+                &payload,
+                output_dir.unwrap_or_else(|| Path::new(".")),
+                prefix,
+                colors,
+                ec,
+                opts.max_qr_version,
+                opts.symbology,
+            )?
+        }
+    };
+
+    if outputs.len() > 1 {
+        print_success(&format!(
+            "Encrypted secret split across {} QR codes: {}",
+            outputs.len(),
+            outputs
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    } else {
+        print_success(&format!(
+            "Encrypted secret saved to {}",
+            outputs[0].display()
+        ));
+    }
+
+    let signature_output = match &opts.sign_key {
+        Some(sign_key_path) => {
+            let signing_key = signing::load_signing_key(sign_key_path)?;
+            let signature = signing::sign_payload(&main_data, &signing_key);
+            let sig_payload = QRGenerator::encode_payload(&QRData::PayloadSignature(signature))?;
+            let sig_path = signature_qr_path(output);
+            QRGenerator::generate_qr(
+                &sig_payload,
+                &sig_path,
+                QrColors::default(),
+                EcLevel::M,
+                qr::Symbology::Qr,
+            )?;
+            print_success(&format!("Signature QR saved to {}", sig_path.display()));
+            Some(sig_path)
+        }
+        None => None,
+    };
+
+    Ok(json!({
+        "output": outputs,
+        "signature_output": signature_output,
+        "layered": has_decoy,
+        "fido2": opts.fido2,
+    }))
+}
+
+/// Parse one `--add` entry of the form "<name>=<secret>".
+fn parse_keyring_entry(spec: &str) -> Result<(String, String)> {
+    let (name, secret) = spec.split_once('=').ok_or_else(|| {
+        QRCryptError::InvalidFormat(format!(
+            "\"{spec}\" is not a keyring entry; expected \"<name>=<secret>\""
+        ))
+    })?;
+    if name.is_empty() {
+        return Err(QRCryptError::InvalidFormat(
+            "keyring entry name can't be empty".to_string(),
+        ));
+    }
+    Ok((name.to_string(), secret.to_string()))
+}
+
+/// Whether `text` looks enough like a BIP39 mnemonic (a word count the spec
+/// actually uses, every "word" just letters) to be worth validating, as
+/// opposed to an arbitrary password or note that happens to be several
+/// words long. Letters are checked with `is_alphabetic`, not
+/// `is_ascii_alphabetic`, so a non-English mnemonic (French, Spanish,
+/// Portuguese, ... all have accented wordlist entries) is still recognized.
+fn looks_like_bip39_mnemonic(text: &str) -> bool {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    matches!(words.len(), 12 | 15 | 18 | 21 | 24)
+        && words.iter().all(|w| w.chars().all(|c| c.is_alphabetic()))
+}
+
+/// Warn (but don't fail) if a keyring entry's secret looks like a BIP39
+/// mnemonic but doesn't actually validate, the way `warn_if_not_bip39` does
+/// for `decrypt --expect-bip39` -- except this runs unconditionally at
+/// encrypt time, since a typo in a seed phrase is worth catching before it's
+/// sealed into a QR code rather than after.
+fn warn_if_looks_like_bip39_but_isnt(name: &str, secret: &str) {
+    if looks_like_bip39_mnemonic(secret) {
+        if let Err(e) = derive::validate_full_bip39_mnemonic(secret) {
+            print_warning(&format!(
+                "keyring entry \"{name}\" looks like a BIP39 mnemonic but isn't valid ({e})"
+            ));
+        }
+    }
+}
+
+/// Encrypt several named secrets together under one password, bundled via
+/// `secret::Keyring::bundle` into a single `SecretData` and otherwise
+/// following the same path as `handle_encrypt`'s plain (non-layered,
+/// non-FIDO2) case.
+#[allow(clippy::too_many_arguments)]
+fn handle_encrypt_keyring(
+    add: Vec<String>,
+    output: &Path,
+    allow_weak_password: bool,
+    qr_color: Option<String>,
+    qr_background: Option<String>,
+    invert: bool,
+    module_style: ModuleStyleArg,
+    fill_ratio: f32,
+    error_correction: Option<EcLevelArg>,
+    dry_run: bool,
+    max_qr_version: i16,
+) -> Result<Value> {
+    if add.is_empty() {
+        return Err(QRCryptError::InvalidFormat(
+            "encrypt-keyring needs at least one --add name=secret".to_string(),
+        ));
+    }
+    let colors = resolve_qr_colors(
+        qr_color,
+        qr_background,
+        qr::DEFAULT_BORDER_MODULES,
+        invert,
+        module_style,
+        fill_ratio,
+    )?;
+    let ec = resolve_ec_level(error_correction);
+
+    let mut keyring = Keyring::new();
+    for spec in &add {
+        let (name, secret) = parse_keyring_entry(spec)?;
+        warn_if_looks_like_bip39_but_isnt(&name, &secret);
+        keyring.insert(name, normalize_if_seed_phrase(secret));
+    }
+    let bundle = keyring.bundle();
+
+    warn_if_wont_fit_a_qr_code(bundle.len(), false, ec)?;
+    if dry_run {
+        let capacity = encrypted_qr_capacity(bundle.len(), ec);
+        print_info(&format!(
+            "Would write an encrypted keyring QR code to {} ({})",
+            output.display(),
+            describe_capacity(&capacity),
+        ));
+        return Ok(json!({
+            "dry_run": true,
+            "output": output,
+            "entries": keyring.names().collect::<Vec<_>>(),
+            "fits": capacity.fits,
+            "qr_version": capacity.version,
+            "qr_modules": capacity.modules,
+        }));
+    }
+
+    let password = utils::prompt_password("Enter password: ")?;
+    if !utils::check_password_strength(&password, allow_weak_password)? {
+        print_info("Aborted.");
+        return Ok(json!({ "aborted": true }));
+    }
+
+    let message = format!(
+        "Deriving key ({})... this may take a few seconds",
+        describe_kdf_cost(&KdfParams::Argon2id)
+    );
+    let encrypted = utils::with_kdf_progress(&message, || Crypto::encrypt(&bundle, &password))?;
+
+    let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted))?;
+    let output_dir = output.parent().filter(|p| !p.as_os_str().is_empty());
+    let prefix = output.file_stem().and_then(|s| s.to_str()).ok_or_else(|| {
+        QRCryptError::InvalidFormat(format!("{} has no usable file stem", output.display()))
+    })?;
+    let outputs = QRGenerator::save_payload_auto(
+        &payload,
+        output_dir.unwrap_or_else(|| Path::new(".")),
+        prefix,
+        colors,
+        ec,
+        max_qr_version,
+        qrcrypt::qr::Symbology::Qr,
+    )?;
+
+    if outputs.len() > 1 {
+        print_success(&format!(
+            "Encrypted keyring split across {} QR codes: {}",
+            outputs.len(),
+            outputs
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    } else {
+        print_success(&format!(
+            "Encrypted keyring saved to {}",
+            outputs[0].display()
+        ));
+    }
+    Ok(json!({ "output": outputs, "entries": keyring.names().collect::<Vec<_>>() }))
+}
+
+/// Scan `input` once and decrypt whatever it contains. The password is only
+/// ever prompted for once: `Crypto::decrypt_layered` tries the hidden and
+/// decoy layers itself rather than `handle_decrypt` needing to guess which
+/// one applies ahead of time, and a real scan failure (no QR found, unparsable
+/// payload) propagates as an error instead of being mistaken for "not layered".
+fn handle_decrypt(
+    input: &Path,
+    expect_bip39: bool,
+    entry: Option<&str>,
+    shred: bool,
+    encoding: SecretEncoding,
+) -> Result<Value> {
+    let password = utils::prompt_password("Enter password: ")?;
+
+    let data = match QRScanner::interactive_scan(input)? {
+        QRData::Encrypted(data) => data,
+        QRData::ShamirShare(_)
+        | QRData::ParityShare(_)
+        | QRData::Slip39Share(_)
+        | QRData::FilePart(_)
+        | QRData::PayloadPart(_)
+        | QRData::FountainFrame(_)
+        | QRData::PayloadSignature(_)
+        | QRData::ShareVerification(_) => {
+            return Err(QRCryptError::InvalidFormat(
+                "this QR code contains a share, not an encrypted secret".to_string(),
+            ))
+        }
+    };
+
+    let message = format!(
+        "Deriving key ({})... this may take a few seconds",
+        describe_kdf_cost(&data.kdf)
+    );
+    let plaintext =
+        utils::with_kdf_progress(&message, || Crypto::decrypt_layered(&data, &password))?;
+    let secret = SecretData::from_bytes(plaintext.to_vec());
+
+    let result = if let Some(keyring) = Keyring::parse(secret.as_bytes()) {
+        report_keyring(&keyring, expect_bip39, entry)
+    } else if let Some((mnemonic, passphrase)) =
+        derive::split_mnemonic_and_passphrase(secret.as_bytes())
+    {
+        print_success("Decrypted secret (mnemonic + passphrase):");
+        if !is_json_mode() {
+            println!("Mnemonic: {mnemonic}");
+            println!("Passphrase: {passphrase}");
+        }
+        Ok(json!({ "mnemonic": mnemonic, "passphrase": passphrase }))
+    } else {
+        let text = match encoding {
+            SecretEncoding::Utf8 => {
+                let text = secret.as_str().ok_or_else(|| {
+                    QRCryptError::InvalidFormat(
+                        "decrypted data is not valid UTF-8; use --encoding hex or --encoding \
+                         base64 to print it as raw bytes"
+                            .to_string(),
+                    )
+                })?;
+                if expect_bip39 {
+                    warn_if_not_bip39(text);
+                }
+                text.to_string()
+            }
+            SecretEncoding::Hex => hex::encode(secret.as_bytes()),
+            SecretEncoding::Base64 => STANDARD.encode(secret.as_bytes()),
+        };
+
+        print_success("Decrypted secret:");
+        if !is_json_mode() {
+            println!("{text}");
+        }
+        Ok(json!({ "secret": text }))
+    }?;
+
+    if shred {
+        utils::secure_delete(input)?;
+        print_info(&format!("Shredded {}", input.display()));
+    }
+
+    Ok(result)
+}
+
+/// Report a decrypted `Keyring`: either one named entry (`--entry`), or just
+/// the names of every entry if it's omitted. Never prints every secret at
+/// once -- that would defeat the point of splitting them into named entries
+/// in the first place.
+fn report_keyring(keyring: &Keyring, expect_bip39: bool, entry: Option<&str>) -> Result<Value> {
+    match entry {
+        Some(name) => {
+            let secret = keyring.get(name).ok_or_else(|| {
+                QRCryptError::InvalidFormat(format!(
+                    "keyring has no entry named \"{name}\"; entries: {}",
+                    keyring.names().collect::<Vec<_>>().join(", ")
+                ))
+            })?;
+            if expect_bip39 {
+                warn_if_not_bip39(secret);
+            }
+            print_success(&format!("Decrypted keyring entry \"{name}\":"));
+            if !is_json_mode() {
+                println!("{secret}");
+            }
+            Ok(json!({ "entry": name, "secret": secret }))
+        }
+        None => {
+            let names: Vec<&str> = keyring.names().collect();
+            print_success(&format!("Decrypted keyring ({} entries):", names.len()));
+            if !is_json_mode() {
+                for name in &names {
+                    println!("{name}");
+                }
+            }
+            Ok(json!({ "entries": names }))
+        }
+    }
+}
+
+/// Add a decoy layer to an already-encrypted QR code. The hidden layer is
+/// loaded as-is and never decrypted, so the real password and plaintext are
+/// never touched: only `Crypto::add_decoy_layer` sees the existing
+/// `EncryptedData`, and only to attach a new `decoy` `Layer` to it.
+#[allow(clippy::too_many_arguments)]
+fn handle_merge_layers(
+    input: &Path,
+    output: &Path,
+    decoy_secret: Option<String>,
+    decoy_password: Option<String>,
+    qr_color: Option<String>,
+    qr_background: Option<String>,
+    invert: bool,
+    module_style: ModuleStyleArg,
+    fill_ratio: f32,
+    error_correction: Option<EcLevelArg>,
+    max_qr_version: i16,
+) -> Result<Value> {
+    let colors = resolve_qr_colors(
+        qr_color,
+        qr_background,
+        qr::DEFAULT_BORDER_MODULES,
+        invert,
+        module_style,
+        fill_ratio,
+    )?;
+    let ec = resolve_ec_level(error_correction);
+
+    let data = match QRScanner::interactive_scan(input)? {
+        QRData::Encrypted(data) => data,
+        QRData::ShamirShare(_)
+        | QRData::ParityShare(_)
+        | QRData::Slip39Share(_)
+        | QRData::FilePart(_)
+        | QRData::PayloadPart(_)
+        | QRData::FountainFrame(_)
+        | QRData::PayloadSignature(_)
+        | QRData::ShareVerification(_) => {
+            return Err(QRCryptError::InvalidFormat(
+                "this QR code contains a share, not an encrypted secret".to_string(),
+            ))
+        }
+    };
+
+    let decoy_secret = read_secret(decoy_secret)?;
+    let decoy_password = match decoy_password {
+        Some(password) => password,
+        None => utils::prompt_password("Enter decoy password: ")?,
+    };
+
+    let message = format!(
+        "Deriving key ({})... this may take a few seconds",
+        describe_kdf_cost(&data.kdf)
+    );
+    let layered = utils::with_kdf_progress(&message, || {
+        Crypto::add_decoy_layer(data, decoy_secret.as_bytes(), &decoy_password)
+    })?;
+
+    let payload = QRGenerator::encode_payload(&QRData::Encrypted(layered))?;
+    let output_dir = output.parent().filter(|p| !p.as_os_str().is_empty());
+    let prefix = output.file_stem().and_then(|s| s.to_str()).ok_or_else(|| {
+        QRCryptError::InvalidFormat(format!("{} has no usable file stem", output.display()))
+    })?;
+    let outputs = QRGenerator::save_payload_auto(
+        &payload,
+        output_dir.unwrap_or_else(|| Path::new(".")),
+        prefix,
+        colors,
+        ec,
+        max_qr_version,
+        qrcrypt::qr::Symbology::Qr,
+    )?;
+
+    if outputs.len() > 1 {
+        print_success(&format!(
+            "Layered QR code split across {} QR codes: {}",
+            outputs.len(),
+            outputs
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    } else {
+        print_success(&format!(
+            "Layered QR code saved to {}",
+            outputs[0].display()
+        ));
+    }
+    Ok(json!({ "output": outputs }))
+}
+
+/// Report encrypted/share payload sizes and QR sizing at every error
+/// correction level, without writing or scanning anything. Actually builds
+/// an `EncryptedData` (and Shamir shares, if asked) under a throwaway
+/// password purely to measure their real encoded sizes, then discards them.
+fn handle_estimate(
+    secret: Option<String>,
+    secret_file: Option<&Path>,
+    threshold: Option<u8>,
+    total: Option<u8>,
+    card: bool,
+) -> Result<Value> {
+    let secret = match secret_file {
+        Some(path) => SecretData::from_bytes(std::fs::read(path)?),
+        None => read_secret(secret)?,
+    };
+
+    let encrypted = Crypto::encrypt(secret.as_bytes(), "estimate")?;
+    let encrypted_payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted))?;
+
+    let mut result = json!({
+        "secret_bytes": secret.as_bytes().len(),
+        "encrypted_payload_bytes": encrypted_payload.len(),
+        "encrypted": estimate_levels(encrypted_payload.len(), card),
+    });
+
+    if let (Some(threshold), Some(total)) = (threshold, total) {
+        let shares = shamir::split_secret(secret.as_bytes(), threshold, total)?;
+        let share_payload = QRGenerator::encode_payload(&QRData::ShamirShare(shares[0].clone()))?;
+        result["share_payload_bytes"] = json!(share_payload.len());
+        result["share"] = estimate_levels(share_payload.len(), card);
+    }
+
+    print_success("Estimate (nothing was written):");
+    if !is_json_mode() {
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+    }
+    Ok(result)
+}
+
+/// Per-error-correction-level sizing for a payload of `payload_len` bytes:
+/// whether it fits in a single QR code, the version/module count if so, and
+/// (with `card`) the physical module size in millimetres on an 8.5cm x
+/// 5.5cm card at `qr::DEFAULT_CARD_DPI`.
+fn estimate_levels(payload_len: usize, card: bool) -> Value {
+    let entries: Vec<Value> = [EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H]
+        .into_iter()
+        .map(|level| {
+            let capacity = QRGenerator::estimate_capacity(payload_len, level);
+            let module_size_mm = card
+                .then_some(capacity.modules)
+                .flatten()
+                .and_then(|modules| {
+                    QRGenerator::card_module_size_mm(
+                        modules,
+                        qr::DEFAULT_BORDER_MODULES,
+                        qr::DEFAULT_CARD_DPI,
+                    )
+                });
+            json!({
+                "level": format!("{level:?}"),
+                "fits": capacity.fits,
+                "version": capacity.version,
+                "modules": capacity.modules,
+                "card_module_size_mm": module_size_mm,
+            })
+        })
+        .collect();
+    Value::Array(entries)
+}
+
+/// Warn (but don't fail) if `text` doesn't parse as a BIP39 mnemonic. Used
+/// by `decrypt --expect-bip39` to catch a wrong password that happened to
+/// decrypt to valid-looking UTF-8 rather than erroring out outright.
+fn warn_if_not_bip39(text: &str) {
+    if let Err(e) = derive::validate_full_bip39_mnemonic(text) {
+        let preview: String = text.chars().take(12).collect();
+        print_warning(&format!(
+            "decrypted secret does not look like a valid BIP39 mnemonic ({e}); \
+             got {} character(s) starting with \"{preview}\" -- the password may be wrong",
+            text.chars().count()
+        ));
+    }
+}
+
+/// Read one password per line from `path`, in share-index order. Errors if
+/// the line count doesn't match `total`, since a mismatch means at least
+/// one share would end up with no password or the wrong one.
+fn read_share_password_file(path: &Path, total: u8) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)?;
+    let passwords: Vec<String> = contents.lines().map(str::to_string).collect();
+    if passwords.len() != total as usize {
+        return Err(QRCryptError::InvalidFormat(format!(
+            "{} has {} password(s), expected {total} (one per share)",
+            path.display(),
+            passwords.len()
+        )));
+    }
+    Ok(passwords)
+}
+
+/// Encrypt each share in `shares` with its own password, taken from
+/// `password_file` if given or prompted for otherwise, in share-index order.
+fn apply_share_passwords(
+    shares: &mut [shamir::ShamirShare],
+    password_file: Option<&Path>,
+) -> Result<()> {
+    let passwords = match password_file {
+        Some(path) => read_share_password_file(path, shares.len() as u8)?,
+        None => shares
+            .iter()
+            .map(|share| {
+                utils::prompt_password(&format!("Enter a password for share {}: ", share.index))
+                    .map_err(QRCryptError::Io)
+            })
+            .collect::<Result<Vec<String>>>()?,
+    };
+
+    for (share, password) in shares.iter_mut().zip(passwords.iter()) {
+        shamir::encrypt_share(share, password)?;
+    }
+    Ok(())
+}
+
+/// Sign every share in `shares` with the Ed25519 key at `sign_key`, or do
+/// nothing if `--sign-key` wasn't given. Called last, after every other
+/// mutation (labels, `--share-passwords` encryption) a share will carry, so
+/// the signature covers exactly what ends up on the card.
+fn sign_shares(shares: &mut [shamir::ShamirShare], sign_key: Option<&Path>) -> Result<()> {
+    let Some(path) = sign_key else {
+        return Ok(());
+    };
+    let signing_key = signing::load_signing_key(path)?;
+    for share in shares.iter_mut() {
+        signing::sign_share(share, &signing_key);
+    }
+    Ok(())
+}
+
+/// Render and save each of `shares` as a `split --stealth` QR: its metadata
+/// is encrypted with `password` via `shamir::stealth_encrypt_share` and
+/// wrapped in the same `QRData::Encrypted` envelope `encrypt` produces, then
+/// rendered as a bare QR code with `QRGenerator::generate_qr` rather than a
+/// captioned card -- a caption reading "Share 2 of 5" would give away
+/// exactly what stealth is meant to hide. Filenames still follow the normal
+/// `<prefix>-share-N.png` convention, for the holder's own bookkeeping.
+fn save_stealth_share_qrs(
+    shares: &[shamir::ShamirShare],
+    output_dir: &Path,
+    password: &str,
+    colors: QrColors,
+    ec: EcLevel,
+) -> Result<Vec<std::path::PathBuf>> {
+    let mut paths = Vec::with_capacity(shares.len());
+    for share in shares {
+        let encrypted = shamir::stealth_encrypt_share(share, password)?;
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted))?;
+        let path = output_dir.join(QRGenerator::shamir_card_filename(share, "qrcrypt"));
+        QRGenerator::generate_qr(&payload, &path, colors, ec, qrcrypt::qr::Symbology::Qr)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+struct SplitOptions {
+    threshold: Option<u8>,
+    total: Option<u8>,
+    ids: Option<String>,
+    share_encoding: ShareEncodingArg,
+    group: Vec<String>,
+    groups_required: Option<u8>,
+    format: ShareFormat,
+    share_passwords: bool,
+    share_password_file: Option<std::path::PathBuf>,
+    labels: Option<String>,
+    parity: u8,
+    qr_color: Option<String>,
+    qr_background: Option<String>,
+    invert: bool,
+    module_style: ModuleStyleArg,
+    fill_ratio: f32,
+    border: u32,
+    error_correction: Option<EcLevelArg>,
+    dpi: u32,
+    font: Option<std::path::PathBuf>,
+    card_title: Option<String>,
+    card_subtitle: Option<String>,
+    plain_qr: bool,
+    no_info: bool,
+    info: Option<std::path::PathBuf>,
+    dry_run: bool,
+    stealth: bool,
+    words_only: bool,
+    pdf: Option<std::path::PathBuf>,
+    per_page: u8,
+    sign_key: Option<std::path::PathBuf>,
+    sheet: Option<std::path::PathBuf>,
+    paper_size: PaperSizeArg,
+    card_back: Option<std::path::PathBuf>,
+    card_back_text: Option<String>,
+    symbology: qrcrypt::qr::Symbology,
+    with_verify: bool,
+    password: bool,
+    allow_weak_password: bool,
+}
+
+/// Parse one `--group` entry of the form "<threshold>of<total>", e.g. "2of3".
+fn parse_group_spec(spec: &str) -> Result<(u8, u8)> {
+    let (threshold, total) = spec.split_once("of").ok_or_else(|| {
+        QRCryptError::InvalidFormat(format!(
+            "\"{spec}\" is not a group spec; expected \"<threshold>of<total>\", e.g. \"2of3\""
+        ))
+    })?;
+    let parse = |s: &str| -> Result<u8> {
+        s.trim().parse().map_err(|_| {
+            QRCryptError::InvalidFormat(format!("\"{spec}\" is not a valid group spec"))
+        })
+    };
+    Ok((parse(threshold)?, parse(total)?))
+}
+
+/// Parse a `--qr-color "rrggbb"` style hex string (an optional leading `#`
+/// is also accepted) into an opaque `Rgba<u8>`.
+fn parse_hex_color(hex: &str) -> Result<image::Rgba<u8>> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+        return Err(QRCryptError::InvalidFormat(format!(
+            "\"{hex}\" is not a 6-digit hex color (expected e.g. \"1a2b3c\")"
+        )));
+    }
+    let byte = |i: usize| -> Result<u8> {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|_| QRCryptError::InvalidFormat(format!("\"{hex}\" is not a valid hex color")))
+    };
+    Ok(image::Rgba([byte(0)?, byte(2)?, byte(4)?, 255]))
+}
+
+/// Resolve `--qr-color`/`--qr-background`/`--invert` into a `QrColors` with
+/// the given card quiet zone width (in modules), falling back to the
+/// black-on-white default for whichever color is absent, then swapping
+/// foreground and background if `invert` is set (defaults included, so
+/// plain `--invert` alone gives white-on-black). Rejects the final pair up
+/// front if it's too low-contrast to scan.
+fn resolve_qr_colors(
+    fg: Option<String>,
+    bg: Option<String>,
+    border: u32,
+    invert: bool,
+    module_style: ModuleStyleArg,
+    fill_ratio: f32,
+) -> Result<QrColors> {
+    let default = QrColors::default();
+    let mut fg = fg
+        .map(|s| parse_hex_color(&s))
+        .transpose()?
+        .unwrap_or(default.fg);
+    let mut bg = bg
+        .map(|s| parse_hex_color(&s))
+        .transpose()?
+        .unwrap_or(default.bg);
+    if invert {
+        std::mem::swap(&mut fg, &mut bg);
+    }
+    let colors = QrColors {
+        fg,
+        bg,
+        border,
+        module_style: module_style.into(),
+        fill_ratio,
+    };
+    colors.validate()?;
+    Ok(colors)
+}
+
+/// Split `--labels`'s comma-separated holder names into one per share, in
+/// share-index order. Errors if the count doesn't match `total`, since a
+/// mismatch would silently mislabel or skip a share.
+fn parse_labels(labels: &str, total: u8) -> Result<Vec<String>> {
+    let labels: Vec<String> = labels.split(',').map(|s| s.trim().to_string()).collect();
+    if labels.len() != total as usize {
+        return Err(QRCryptError::InvalidFormat(format!(
+            "--labels has {} name(s), expected {total} (one per share)",
+            labels.len()
+        )));
+    }
+    Ok(labels)
+}
+
+/// Parse `--ids`'s comma-separated share ids into a `Vec<u8>`. Only checks
+/// syntax; `shamir::split_secret_with_ids` checks they're unique, nonzero,
+/// and enough to meet the threshold.
+fn parse_ids(ids: &str) -> Result<Vec<u8>> {
+    ids.split(',')
+        .map(|s| {
+            s.trim().parse().map_err(|_| {
+                QRCryptError::InvalidFormat(format!(
+                    "\"{s}\" is not a valid share id (expected a number from 1 to 255)"
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Where `handle_split` should write info.txt: `--info <path>` if given,
+/// `<output_dir>/info.txt` by default, or `None` if `--no-info` was passed
+/// to skip it entirely. Every share card QR already carries its own
+/// threshold/total/index, so info.txt is a convenience, not a requirement.
+fn info_destination(
+    no_info: bool,
+    info: Option<std::path::PathBuf>,
+    output_dir: &Path,
+) -> Option<std::path::PathBuf> {
+    if no_info {
+        None
+    } else {
+        Some(info.unwrap_or_else(|| output_dir.join("info.txt")))
+    }
+}
+
+/// Write `info` to `destination` (see `info_destination`), or do nothing if
+/// `--no-info` left it `None`.
+fn write_info(destination: &Option<std::path::PathBuf>, info: impl AsRef<[u8]>) -> Result<()> {
+    match destination {
+        Some(path) => std::fs::write(path, info).map_err(Into::into),
+        None => Ok(()),
+    }
+}
+
+/// Print and return the planned output of a `split --dry-run`: the share
+/// card filenames `handle_split` would have written, plus the fixed card
+/// pixel dimensions every one of them would be rendered at.
+fn report_split_dry_run(
+    output_dir: &Path,
+    filenames: &[std::path::PathBuf],
+    summary: &str,
+    dpi: u32,
+) -> Value {
+    let (width, height) = QRGenerator::card_pixel_dimensions(dpi);
+    print_info(&format!(
+        "Would split into {summary} in {} ({width}x{height}px cards at {dpi} DPI):",
+        output_dir.display()
+    ));
+    if !is_json_mode() {
+        for filename in filenames {
+            println!("  {}", filename.display());
+        }
+    }
+    json!({
+        "dry_run": true,
+        "output_dir": output_dir,
+        "shares": filenames,
+        "card_width": width,
+        "card_height": height,
+    })
+}
+
+fn handle_split(
+    secret: Option<String>,
+    binary: bool,
+    input: Option<&Path>,
+    output_dir: &Path,
+    opts: SplitOptions,
+) -> Result<Value> {
+    let SplitOptions {
+        threshold,
+        total,
+        ids,
+        share_encoding,
+        group,
+        groups_required,
+        format,
+        share_passwords,
+        share_password_file,
+        labels,
+        parity,
+        qr_color,
+        qr_background,
+        invert,
+        module_style,
+        fill_ratio,
+        border,
+        error_correction,
+        dpi,
+        font,
+        card_title,
+        card_subtitle,
+        plain_qr,
+        no_info,
+        info,
+        dry_run,
+        stealth,
+        words_only,
+        pdf,
+        per_page,
+        sign_key,
+        sheet,
+        paper_size,
+        card_back,
+        card_back_text,
+        symbology,
+        with_verify,
+        password,
+        allow_weak_password,
+    } = opts;
+    let font = font.as_deref();
+    let card_title = card_title.as_deref();
+    let card_subtitle = card_subtitle.as_deref();
+    let info_destination = info_destination(no_info, info, output_dir);
+    let colors = resolve_qr_colors(qr_color, qr_background, border, invert, module_style, fill_ratio)?;
+    let ec = resolve_ec_level(error_correction);
+
+    if sign_key.is_some() && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--sign-key is only supported with --format custom".to_string(),
+        ));
+    }
+
+    if pdf.is_some() && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--pdf is only supported with --format custom".to_string(),
+        ));
+    }
+    if pdf.is_some() && dry_run {
+        return Err(QRCryptError::InvalidFormat(
+            "--pdf and --dry-run can't be combined; --dry-run writes nothing to render a PDF from"
+                .to_string(),
+        ));
+    }
+
+    if sheet.is_some() && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--sheet is only supported with --format custom".to_string(),
+        ));
+    }
+    if sheet.is_some() && dry_run {
+        return Err(QRCryptError::InvalidFormat(
+            "--sheet and --dry-run can't be combined; --dry-run writes nothing to compose a sheet \
+             from"
+                .to_string(),
+        ));
+    }
+
+    if card_back.is_some() && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--card-back is only supported with --format custom".to_string(),
+        ));
+    }
+    if card_back.is_some() && dry_run {
+        return Err(QRCryptError::InvalidFormat(
+            "--card-back and --dry-run can't be combined; --dry-run writes nothing to render a \
+             back card from"
+                .to_string(),
+        ));
+    }
+
+    if stealth && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--stealth is only supported with --format custom".to_string(),
+        ));
+    }
+    if plain_qr && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--plain-qr is only supported with --format custom".to_string(),
+        ));
+    }
+
+    if with_verify && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--with-verify is only supported with --format custom".to_string(),
+        ));
+    }
+
+    if with_verify && !password {
+        return Err(QRCryptError::InvalidFormat(
+            "--with-verify requires --password: its commitment is a bare unsalted SHA-256, \
+             which would let anyone holding a single share's verification QR run an offline \
+             dictionary attack against a low-entropy secret without a single Shamir share"
+                .to_string(),
+        ));
+    }
+
+    if password && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--password is only supported with --format custom".to_string(),
+        ));
+    }
+
+    if !dry_run
+        && output_dir.exists()
+        && output_dir
+            .read_dir()
+            .map(|mut d| d.next().is_some())
+            .unwrap_or(false)
+    {
+        let proceed = utils::confirm(&format!(
+            "{} is not empty; existing share files with matching names will be overwritten. Continue?",
+            output_dir.display()
+        ))?;
+        if !proceed {
+            print_info("Aborted.");
+            return Ok(json!({ "aborted": true }));
+        }
+    }
+
+    if share_passwords && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--share-passwords is only supported with --format custom".to_string(),
+        ));
+    }
+    if labels.is_some() && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--labels is only supported with --format custom".to_string(),
+        ));
+    }
+    if parity > 0 && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--parity is only supported with --format custom".to_string(),
+        ));
+    }
+    if plain_qr && parity > 0 {
+        return Err(QRCryptError::InvalidFormat(
+            "--plain-qr and --parity can't be combined; parity shares don't have a plain QR \
+             rendering yet"
+                .to_string(),
+        ));
+    }
+
+    if !group.is_empty() {
+        if format != ShareFormat::Custom {
+            return Err(QRCryptError::InvalidFormat(
+                "--group is only supported with --format custom".to_string(),
+            ));
+        }
+        if share_passwords || labels.is_some() || parity > 0 {
+            return Err(QRCryptError::InvalidFormat(
+                "--share-passwords, --labels, and --parity are not supported with --group"
+                    .to_string(),
+            ));
+        }
+        let groups_required =
+            groups_required.expect("clap requires --groups-required with --group");
+        let groups: Vec<(u8, u8)> = group
+            .iter()
+            .map(|spec| parse_group_spec(spec))
+            .collect::<Result<_>>()?;
+
+        let secret = if binary {
+            let path = input.expect("clap requires --input alongside --binary");
+            SecretData::from_bytes(std::fs::read(path)?)
+        } else {
+            read_secret(secret)?
+        };
+
+        let mut shares =
+            shamir::split_secret_with_groups(secret.as_bytes(), &groups, groups_required)?;
+        let filenames: Vec<std::path::PathBuf> = shares
+            .iter()
+            .map(|share| output_dir.join(QRGenerator::shamir_card_filename(share, "qrcrypt")))
+            .collect();
+
+        if dry_run {
+            return Ok(report_split_dry_run(
+                output_dir,
+                &filenames,
+                &format!("{} group(s) (any {groups_required} required)", groups.len()),
+                dpi,
+            ));
+        }
+        sign_shares(&mut shares, sign_key.as_deref())?;
+
+        std::fs::create_dir_all(output_dir)?;
+        let filenames = if plain_qr {
+            QRGenerator::save_shamir_qrs_captioned(
+                &shares,
+                output_dir,
+                "qrcrypt",
+                colors,
+                ec,
+                font,
+                card_subtitle,
+                symbology,
+            )?
+        } else {
+            QRGenerator::save_shamir_card_qrs(
+                &shares,
+                output_dir,
+                "qrcrypt",
+                colors,
+                ec,
+                dpi,
+                font,
+                card_title,
+                card_subtitle,
+                None,
+                None,
+                None,
+            )?
+        };
+        let info = QRGenerator::generate_grouped_info_text(&shares, &filenames, secret.as_bytes());
+        write_info(&info_destination, info)?;
+
+        print_success(&format!(
+            "Split secret into {} group(s) (any {groups_required} required) in {}",
+            groups.len(),
+            output_dir.display()
+        ));
+        return Ok(json!({
+            "output_dir": output_dir,
+            "groups": group,
+            "groups_required": groups_required,
+            "shares": filenames,
+        }));
+    }
+    let threshold = threshold.expect("clap requires --threshold without --group");
+    let ids = ids.map(|spec| parse_ids(&spec)).transpose()?;
+    if ids.is_some() && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--ids is only supported with --format custom".to_string(),
+        ));
+    }
+    if !matches!(share_encoding, ShareEncodingArg::Gf256) && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--share-encoding is only supported with --format custom".to_string(),
+        ));
+    }
+    if words_only && format != ShareFormat::Custom {
+        return Err(QRCryptError::InvalidFormat(
+            "--words-only is only supported with --format custom".to_string(),
+        ));
+    }
+    if words_only && !matches!(share_encoding, ShareEncodingArg::Gf256) {
+        return Err(QRCryptError::InvalidFormat(
+            "--words-only requires --share-encoding gf256; word encoding doesn't support \
+             gf65536 shares"
+                .to_string(),
+        ));
+    }
+    let total = match &ids {
+        Some(ids) => u8::try_from(ids.len()).map_err(|_| {
+            QRCryptError::InvalidFormat("--ids has too many entries (max 255)".to_string())
+        })?,
+        None => total.expect("clap requires --total without --group or --ids"),
+    };
+
+    let secret = if binary {
+        let path = input.expect("clap requires --input alongside --binary");
+        SecretData::from_bytes(std::fs::read(path)?)
+    } else {
+        read_secret(secret)?
+    };
+    let secret = if password {
+        let split_password =
+            utils::prompt_password("Enter a password to encrypt the secret before splitting: ")?;
+        if !utils::check_password_strength(&split_password, allow_weak_password)? {
+            print_info("Aborted.");
+            return Ok(json!({ "aborted": true }));
+        }
+        SecretData::from_bytes(shamir::encrypt_split_secret(
+            secret.as_bytes(),
+            &split_password,
+        )?)
+    } else {
+        secret
+    };
+    if !dry_run {
+        std::fs::create_dir_all(output_dir)?;
+    }
+
+    match format {
+        ShareFormat::Custom => {
+            let mut shares = match &ids {
+                Some(ids) => shamir::split_secret_with_ids(secret.as_bytes(), threshold, ids)?,
+                None => {
+                    let encoding = match share_encoding {
+                        ShareEncodingArg::Gf256 => shamir::ShareEncoding::Gf256,
+                        ShareEncodingArg::Gf65536 => shamir::ShareEncoding::Gf65536,
+                    };
+                    shamir::split_secret_with_encoding(
+                        secret.as_bytes(),
+                        threshold,
+                        total,
+                        encoding,
+                    )?
+                }
+            };
+            if let Some(labels) = labels {
+                let labels = parse_labels(&labels, total)?;
+                for (share, label) in shares.iter_mut().zip(labels) {
+                    share.label = Some(label);
+                }
+            }
+            let verify_info = with_verify
+                .then(|| shamir::build_verification_info(secret.as_bytes(), &shares));
+            if parity > 0 {
+                // Parity must cover the unencrypted shares, since a password
+                // withholds a share's data from everyone but its holder --
+                // including the parity encoding.
+                let parity_shares = shamir::generate_parity_shares(&shares, parity)?;
+                if dry_run {
+                    let mut filenames: Vec<std::path::PathBuf> = shares
+                        .iter()
+                        .map(|share| {
+                            output_dir.join(QRGenerator::shamir_card_filename(share, "qrcrypt"))
+                        })
+                        .collect();
+                    filenames.extend(
+                        (0..parity_shares.len()).map(|i| {
+                            output_dir.join(QRGenerator::parity_card_filename(i, "qrcrypt"))
+                        }),
+                    );
+                    return Ok(report_split_dry_run(
+                        output_dir,
+                        &filenames,
+                        &format!(
+                            "{total} shares (threshold {threshold}) plus {parity} parity share(s)"
+                        ),
+                        dpi,
+                    ));
+                }
+                if share_passwords {
+                    apply_share_passwords(&mut shares, share_password_file.as_deref())?;
+                }
+                sign_shares(&mut shares, sign_key.as_deref())?;
+                let mut filenames = QRGenerator::save_shamir_card_qrs(
+                    &shares, output_dir, "qrcrypt", colors, ec, dpi, font, card_title,
+                    card_subtitle, card_back.as_deref(), card_back_text.as_deref(),
+                    verify_info.as_ref(),
+                )?;
+                let parity_filenames = QRGenerator::save_shamir_parity_qrs(
+                    &parity_shares,
+                    output_dir,
+                    "qrcrypt",
+                    colors,
+                    ec,
+                    dpi,
+                    font,
+                    card_title,
+                    card_subtitle,
+                )?;
+                filenames.extend(parity_filenames);
+
+                if let Some(sheet_path) = &sheet {
+                    let mut captions: Vec<String> = shares
+                        .iter()
+                        .map(|share| {
+                            card_subtitle
+                                .map(str::to_string)
+                                .unwrap_or_else(|| QRGenerator::shamir_caption(share))
+                        })
+                        .collect();
+                    captions.extend(parity_shares.iter().enumerate().map(|(i, p)| {
+                        card_subtitle
+                            .map(str::to_string)
+                            .unwrap_or_else(|| QRGenerator::parity_caption(i, p))
+                    }));
+                    QRGenerator::save_sheet_from_paths(
+                        &filenames,
+                        &captions,
+                        sheet_path,
+                        paper_size.into(),
+                        dpi,
+                    )?;
+                }
+
+                let info = QRGenerator::generate_info_text(
+                    &shares,
+                    &filenames,
+                    parity as usize,
+                    secret.as_bytes(),
+                );
+                write_info(&info_destination, &info)?;
+
+                if let Some(pdf_path) = &pdf {
+                    pdf::write_shares_pdf(&filenames, &info, per_page as usize, pdf_path, dpi)?;
+                }
+
+                print_success(&format!(
+                    "Split secret into {total} shares (threshold {threshold}) plus {parity} parity share(s) in {}",
+                    output_dir.display()
+                ));
+                return Ok(json!({
+                    "output_dir": output_dir,
+                    "threshold": threshold,
+                    "total": total,
+                    "parity": parity,
+                    "shares": filenames,
+                    "secret_commitment": verify_info.as_ref().map(|v| &v.secret_commitment),
+                }));
+            }
+
+            if dry_run {
+                let filenames: Vec<std::path::PathBuf> = shares
+                    .iter()
+                    .map(|share| {
+                        output_dir.join(QRGenerator::shamir_card_filename(share, "qrcrypt"))
+                    })
+                    .collect();
+                return Ok(report_split_dry_run(
+                    output_dir,
+                    &filenames,
+                    &format!("{total} shares (threshold {threshold})"),
+                    dpi,
+                ));
+            }
+
+            if words_only {
+                let info = QRGenerator::generate_words_only_info_text(&shares, secret.as_bytes())?;
+                write_info(&info_destination, info)?;
+
+                print_success(&format!(
+                    "Split secret into {total} shares (threshold {threshold}) as words in {}",
+                    output_dir.display()
+                ));
+                return Ok(json!({
+                    "output_dir": output_dir,
+                    "threshold": threshold,
+                    "total": total,
+                    "words_only": true,
+                }));
+            }
+
+            if stealth {
+                sign_shares(&mut shares, sign_key.as_deref())?;
+                let password =
+                    utils::prompt_password("Enter a password to protect the share metadata: ")?;
+                let filenames = save_stealth_share_qrs(&shares, output_dir, &password, colors, ec)?;
+
+                let info =
+                    QRGenerator::generate_info_text(&shares, &filenames, 0, secret.as_bytes());
+                write_info(&info_destination, info)?;
+
+                print_success(&format!(
+                    "Split secret into {total} stealth shares (threshold {threshold}) in {}",
+                    output_dir.display()
+                ));
+                return Ok(json!({
+                    "output_dir": output_dir,
+                    "threshold": threshold,
+                    "total": total,
+                    "stealth": true,
+                    "shares": filenames,
+                }));
+            }
+
+            if share_passwords {
+                apply_share_passwords(&mut shares, share_password_file.as_deref())?;
+            }
+            sign_shares(&mut shares, sign_key.as_deref())?;
+            let filenames = if plain_qr {
+                QRGenerator::save_shamir_qrs_captioned(
+                    &shares,
+                    output_dir,
+                    "qrcrypt",
+                    colors,
+                    ec,
+                    font,
+                    card_subtitle,
+                    symbology,
+                )?
+            } else {
+                QRGenerator::save_shamir_card_qrs(
+                    &shares,
+                    output_dir,
+                    "qrcrypt",
+                    colors,
+                    ec,
+                    dpi,
+                    font,
+                    card_title,
+                    card_subtitle,
+                    card_back.as_deref(),
+                    card_back_text.as_deref(),
+                    verify_info.as_ref(),
+                )?
+            };
+
+            let info = QRGenerator::generate_info_text(&shares, &filenames, 0, secret.as_bytes());
+            write_info(&info_destination, &info)?;
+
+            if let Some(pdf_path) = &pdf {
+                pdf::write_shares_pdf(&filenames, &info, per_page as usize, pdf_path, dpi)?;
+            }
+
+            if let Some(sheet_path) = &sheet {
+                let captions: Vec<String> = shares
+                    .iter()
+                    .map(|share| {
+                        card_subtitle
+                            .map(str::to_string)
+                            .unwrap_or_else(|| QRGenerator::shamir_caption(share))
+                    })
+                    .collect();
+                QRGenerator::save_sheet_from_paths(
+                    &filenames,
+                    &captions,
+                    sheet_path,
+                    paper_size.into(),
+                    dpi,
+                )?;
+            }
+
+            print_success(&format!(
+                "Split secret into {total} shares (threshold {threshold}) in {}",
+                output_dir.display()
+            ));
+            Ok(json!({
+                "output_dir": output_dir,
+                "threshold": threshold,
+                "total": total,
+                "shares": filenames,
+                "secret_commitment": verify_info.as_ref().map(|v| &v.secret_commitment),
+            }))
+        }
+        ShareFormat::Slip39 => {
+            if dry_run {
+                let filenames: Vec<std::path::PathBuf> = (0..total as usize)
+                    .map(|i| output_dir.join(QRGenerator::slip39_card_filename(i, "qrcrypt")))
+                    .collect();
+                return Ok(report_split_dry_run(
+                    output_dir,
+                    &filenames,
+                    &format!("{total} SLIP-39 shares (threshold {threshold})"),
+                    dpi,
+                ));
+            }
+
+            let passphrase = utils::prompt_password(
+                "Enter a SLIP-39 passphrase (optional, press Enter for none): ",
+            )?;
+            let mnemonics = slip39::split_secret(secret.as_bytes(), threshold, total, &passphrase)?;
+            let filenames = QRGenerator::save_slip39_card_qrs(
+                &mnemonics, output_dir, "qrcrypt", colors, ec, dpi, font,
+            )?;
+
+            let info = QRGenerator::generate_slip39_info_text(
+                threshold,
+                total,
+                &filenames,
+                secret.as_bytes(),
+            );
+            write_info(&info_destination, info)?;
+
+            print_success(&format!(
+                "Split secret into {total} SLIP-39 shares (threshold {threshold}) in {}",
+                output_dir.display()
+            ));
+            Ok(json!({
+                "output_dir": output_dir,
+                "threshold": threshold,
+                "total": total,
+                "shares": filenames,
+            }))
+        }
+        ShareFormat::Ssss => {
+            let filenames: Vec<std::path::PathBuf> = (1..=total)
+                .map(|i| output_dir.join(format!("qrcrypt-share-{i}.txt")))
+                .collect();
+
+            if dry_run {
+                print_info(&format!(
+                    "Would split into {total} ssss-format shares (threshold {threshold}) in {}:",
+                    output_dir.display()
+                ));
+                if !is_json_mode() {
+                    for filename in &filenames {
+                        println!("  {}", filename.display());
+                    }
+                }
+                return Ok(json!({
+                    "dry_run": true,
+                    "output_dir": output_dir,
+                    "shares": filenames,
+                }));
+            }
+
+            let shares = split_secret(secret.as_bytes(), threshold, total)?;
+            for (share, path) in shares.iter().zip(&filenames) {
+                std::fs::write(path, shamir::format_ssss_share(share))?;
+            }
+            let info = QRGenerator::generate_ssss_info_text(
+                threshold,
+                total,
+                &filenames,
+                secret.as_bytes(),
+            );
+            write_info(&info_destination, info)?;
+
+            print_success(&format!(
+                "Split secret into {total} ssss-format shares (threshold {threshold}) in {}",
+                output_dir.display()
+            ));
+            Ok(json!({
+                "output_dir": output_dir,
+                "threshold": threshold,
+                "total": total,
+                "shares": filenames,
+            }))
+        }
+        ShareFormat::Sskr => Err(QRCryptError::InvalidFormat(
+            "--format sskr isn't implemented yet: a real ur:crypto-sskr/... encoder needs to be \
+             byte-exact with Blockchain Commons' bytewords and CBOR encoding and checked against \
+             their published test vectors, or shares that look right would silently fail to scan \
+             on an actual Keystone/SeedSigner"
+                .to_string(),
+        )),
+    }
+}
+
+/// Load shares from a mix of QR code images and plain JSON/text share files.
+/// All of `paths` must be the same format; a directory mixing the two is
+/// rejected rather than silently picking one. Uses the same
+/// `qr::ShareCollector` as `QRScanner::scan_directory`, so the same share
+/// given twice (e.g. `--shares a.json a.json`, or two photos of the same
+/// card) is collapsed to one instead of being handed to
+/// `reconstruct_secret` as two shares with the same x-coordinate, which
+/// breaks Lagrange interpolation, and a set with mismatched
+/// threshold/total is rejected up front regardless of where it came from.
+fn load_shares(paths: &[std::path::PathBuf]) -> Result<ScannedShares> {
+    let mut collector = qr::ShareCollector::default();
+    for path in paths {
+        let items = if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+        {
+            QRScanner::scan_pdf(path)?
+        } else {
+            vec![QRScanner::scan_path(path)?]
+        };
+
+        for item in items {
+            match collector.add(item) {
+                qr::AddOutcome::Added => {}
+                qr::AddOutcome::DuplicateShare(index) => print_warning(&format!(
+                    "{} is a duplicate of share {index}; ignoring it",
+                    path.display()
+                )),
+                qr::AddOutcome::DuplicateParity(index) => print_warning(&format!(
+                    "{} is a duplicate of parity share {index}; ignoring it",
+                    path.display()
+                )),
+                qr::AddOutcome::DuplicateMnemonic => print_warning(&format!(
+                    "{} is a duplicate share; ignoring it",
+                    path.display()
+                )),
+                qr::AddOutcome::NotAShare => {
+                    return Err(QRCryptError::InvalidFormat(format!(
+                        "{} does not contain a share",
+                        path.display()
+                    )))
+                }
+            }
+        }
+    }
+    collector.finish()
+}
+
+/// If `parity` isn't empty and fewer than `shares`'s `total` original shares
+/// are present, rebuild the missing ones via `shamir::repair_missing_shares`
+/// before `shares` goes anywhere near `reconstruct_secret` or
+/// `validate_shares`. A no-op otherwise.
+fn repair_if_needed(
+    shares: Vec<shamir::ShamirShare>,
+    parity: Vec<shamir::ParityShare>,
+) -> Result<Vec<shamir::ShamirShare>> {
+    if parity.is_empty() {
+        return Ok(shares);
+    }
+    let total = shares
+        .first()
+        .map(|s| s.total)
+        .or_else(|| parity.first().map(|p| p.total))
+        .ok_or_else(|| QRCryptError::Shamir("no shares or parity shares provided".to_string()))?;
+    if shares.len() >= total as usize {
+        return Ok(shares);
+    }
+    print_info(&format!(
+        "{} of {total} original shares present; repairing the rest from {} parity share(s)",
+        shares.len(),
+        parity.len()
+    ));
+    shamir::repair_missing_shares(&shares, &parity, total)
+}
+
+/// Prompt for and apply the password of every share `split --share-passwords`
+/// encrypted, in place, so `reconstruct_secret` sees plaintext `data`. Shares
+/// that weren't encrypted are left untouched.
+fn decrypt_encrypted_shares(shares: &mut [shamir::ShamirShare]) -> Result<()> {
+    for share in shares.iter_mut() {
+        if share.encryption.is_some() {
+            let password =
+                utils::prompt_password(&format!("Enter the password for share {}: ", share.index))?;
+            shamir::decrypt_share(share, &password)?;
+        }
+    }
+    Ok(())
+}
+
+/// Cross-check `shares` with `shamir::diagnose_shares` and print what it
+/// finds. Purely informational: `reconstruct_secret` still runs its own
+/// checks regardless, so this can't make a reconstruction succeed or fail
+/// that wouldn't otherwise.
+fn report_diagnosis(shares: &[shamir::ShamirShare]) -> Result<()> {
+    match shamir::diagnose_shares(shares)? {
+        None => print_info("Diagnosis: not enough shares over the threshold to cross-check"),
+        Some(diag) if diag.consistent => {
+            print_info("Diagnosis: all shares agree; no damaged share found")
+        }
+        Some(diag) => {
+            let suspects: Vec<String> =
+                diag.suspect_indices.iter().map(|i| i.to_string()).collect();
+            print_warning(&format!(
+                "Diagnosis: share(s) {} look damaged; excluding them still reconstructs a consistent secret",
+                suspects.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Print which groups of a grouped share set already have enough shares and
+/// which are still short, so a holder collecting cards piece by piece knows
+/// what's missing without attempting a reconstruction that will just fail.
+fn report_group_progress(shares: &[shamir::ShamirShare]) -> Result<()> {
+    let progress = shamir::group_progress(shares)?;
+    print_info(&format!(
+        "Groups: {} of {} required, {} complete",
+        progress.groups_required,
+        progress.group_count,
+        progress.complete_groups.len()
+    ));
+    for g in &progress.incomplete_groups {
+        print_warning(&format!(
+            "group {} has {}/{} shares needed",
+            g.group_id, g.have, g.need
+        ));
+    }
+    Ok(())
+}
+
+/// Repair, decrypt, and reconstruct a set of custom-format shares, whether
+/// they're a flat threshold/total split or a grouped one from
+/// `split --group`. `diagnose` only applies to the flat case -- cross
+/// checking threshold-sized subsets doesn't make sense once shares are
+/// partitioned into groups with their own thresholds.
+fn reconstruct_custom_shares(
+    shares: Vec<shamir::ShamirShare>,
+    parity: Vec<shamir::ParityShare>,
+    diagnose: bool,
+) -> Result<SecretData> {
+    let mut shares = repair_if_needed(shares, parity)?;
+    decrypt_encrypted_shares(&mut shares)?;
+
+    if shares.iter().any(|s| s.group_id.is_some()) {
+        report_group_progress(&shares)?;
+        shamir::reconstruct_grouped_secret(&shares)
+    } else {
+        if diagnose {
+            report_diagnosis(&shares)?;
+        }
+        reconstruct_secret(&shares)
+    }
+}
+
+/// Prompt for shares typed as words, one share per line, until a blank line
+/// ends the list.
+fn prompt_word_shares() -> Result<Vec<shamir::ShamirShare>> {
+    let mut shares = Vec::new();
+    loop {
+        let line = utils::prompt_line(&format!(
+            "Share {} words (blank line to finish): ",
+            shares.len() + 1
+        ))?;
+        if line.is_empty() {
+            break;
+        }
+        let words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        shares.push(shamir::decode_share_words(&words)?);
+    }
+    Ok(shares)
+}
+
+/// Load shares saved by `split --stealth`: each path must scan as a plain
+/// `QRData::Encrypted`, which `password` (the metadata password, not any
+/// individual share password) decrypts back into a `ShamirShare` via
+/// `shamir::stealth_decrypt_share`. Deduped by index, mirroring `load_shares`.
+fn load_stealth_shares(
+    paths: &[std::path::PathBuf],
+    password: &str,
+) -> Result<Vec<shamir::ShamirShare>> {
+    let mut shares = Vec::with_capacity(paths.len());
+    let mut seen_indices = std::collections::HashSet::new();
+    for path in paths {
+        let encrypted = match QRScanner::scan_path(path)? {
+            QRData::Encrypted(data) => data,
+            _ => {
+                return Err(QRCryptError::InvalidFormat(format!(
+                    "{} does not look like a stealth share (expected an encrypted payload)",
+                    path.display()
+                )))
+            }
+        };
+        let share = shamir::stealth_decrypt_share(&encrypted, password)?;
+        if seen_indices.insert(share.index) {
+            shares.push(share);
+        } else {
+            print_warning(&format!(
+                "{} is a duplicate of share {}; ignoring it",
+                path.display(),
+                share.index
+            ));
+        }
+    }
+    Ok(shares)
+}
+
+/// Read the shares saved by `split --format ssss` (or the real `ssss-split`
+/// tool): each path holds one "index-hexshare" line, parsed with
+/// `threshold` since that plain-text format doesn't record one itself.
+fn load_ssss_shares(
+    paths: &[std::path::PathBuf],
+    threshold: u8,
+) -> Result<Vec<shamir::ShamirShare>> {
+    let total = paths.len() as u8;
+    paths
+        .iter()
+        .map(|path| {
+            let line = std::fs::read_to_string(path)?;
+            shamir::parse_ssss_share(&line, threshold, total)
+        })
+        .collect()
+}
+
+/// Options for `handle_reconstruct` beyond the share paths themselves,
+/// grouped into a struct so the function doesn't accumulate an ever-growing
+/// flat argument list as import formats are added.
+struct ReconstructOptions {
+    diagnose: bool,
+    stealth: bool,
+    password: bool,
+    ssss: bool,
+    ssss_threshold: Option<u8>,
+    verify_only: bool,
+    info: Option<std::path::PathBuf>,
+    no_mask: bool,
+    shred: bool,
+    encoding: SecretEncoding,
+}
+
+/// Replace every word but the first and last with `•••`, for previewing a
+/// reconstructed secret without fully exposing it. A one- or two-word
+/// secret has nothing to hide in the middle, so it's shown in full.
+fn mask_secret_preview(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    match words.as_slice() {
+        [] | [_] | [_, _] => text.to_string(),
+        [first, .., last] => format!("{first} ••• {last}"),
+    }
+}
+
+/// The fingerprint `split` recorded in `info_path`'s "Secret fingerprint: "
+/// line, for `reconstruct --verify-only` to check a reconstruction against
+/// without ever printing the secret itself.
+fn read_recorded_secret_fingerprint(info_path: &Path) -> Result<String> {
+    let info_text = std::fs::read_to_string(info_path)?;
+    info_text
+        .lines()
+        .find_map(|line| line.strip_prefix("Secret fingerprint: "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            QRCryptError::InvalidFormat(format!(
+                "{} does not record a secret fingerprint; it may predate `reconstruct --verify-only` support",
+                info_path.display()
+            ))
+        })
+}
+
+fn handle_reconstruct(
+    paths: &[std::path::PathBuf],
+    scan_dir: Option<&Path>,
+    words: bool,
+    binary: bool,
+    output: Option<&Path>,
+    opts: ReconstructOptions,
+) -> Result<Value> {
+    let ReconstructOptions {
+        diagnose,
+        stealth,
+        password,
+        ssss,
+        ssss_threshold,
+        verify_only,
+        info,
+        no_mask,
+        shred,
+        encoding,
+    } = opts;
+    let secret = if ssss {
+        let threshold = ssss_threshold.expect("clap requires --threshold with --ssss");
+        let shares = load_ssss_shares(paths, threshold)?;
+        reconstruct_custom_shares(shares, Vec::new(), diagnose)?
+    } else if stealth {
+        if scan_dir.is_some() || words {
+            return Err(QRCryptError::InvalidFormat(
+                "--stealth is only supported together with --shares".to_string(),
+            ));
+        }
+        let password =
+            utils::prompt_password("Enter the password protecting the share metadata: ")?;
+        let shares = load_stealth_shares(paths, &password)?;
+        reconstruct_custom_shares(shares, Vec::new(), diagnose)?
+    } else if words {
+        let shares = prompt_word_shares()?;
+        reconstruct_custom_shares(shares, Vec::new(), diagnose)?
+    } else {
+        match scan_dir {
+            Some(dir) => match QRScanner::scan_directory_for_reconstruction(dir)? {
+                ScannedShares::Custom { shares, parity } => {
+                    reconstruct_custom_shares(shares, parity, diagnose)?
+                }
+                ScannedShares::Slip39(mnemonics) => {
+                    let passphrase = utils::prompt_password(
+                        "Enter the SLIP-39 passphrase (press Enter if none was set): ",
+                    )?;
+                    slip39::reconstruct_secret(&mnemonics, &passphrase)?
+                }
+            },
+            None => match load_shares(paths)? {
+                ScannedShares::Custom { shares, parity } => {
+                    reconstruct_custom_shares(shares, parity, diagnose)?
+                }
+                ScannedShares::Slip39(mnemonics) => {
+                    let passphrase = utils::prompt_password(
+                        "Enter the SLIP-39 passphrase (press Enter if none was set): ",
+                    )?;
+                    slip39::reconstruct_secret(&mnemonics, &passphrase)?
+                }
+            },
+        }
+    };
+
+    if verify_only {
+        let info_path = info.expect("clap requires --info with --verify-only");
+        let recorded = read_recorded_secret_fingerprint(&info_path)?;
+        let actual = QRGenerator::secret_fingerprint(secret.as_bytes());
+        drop(secret);
+        if actual == recorded {
+            print_success("Reconstruction succeeded, fingerprint matches");
+            return Ok(json!({ "verified": true, "fingerprint": actual }));
+        }
+        print_error(&format!(
+            "Reconstruction succeeded but the fingerprint does not match: info.txt recorded \
+             {recorded}, the reconstructed secret is {actual}"
+        ));
+        return Err(QRCryptError::InvalidFormat(
+            "reconstructed secret's fingerprint does not match the one recorded in info.txt"
+                .to_string(),
+        ));
+    }
+
+    let secret = if password {
+        let split_password = utils::prompt_password(
+            "Enter the password used to encrypt the secret at split time: ",
+        )?;
+        SecretData::from_bytes(
+            shamir::decrypt_split_secret(secret.as_bytes(), &split_password)?.to_vec(),
+        )
+    } else {
+        secret
+    };
+
+    if binary {
+        let path = output.expect("clap requires --output alongside --binary");
+        std::fs::write(path, secret.as_bytes())?;
+        print_success(&format!(
+            "Reconstructed secret written to {}",
+            path.display()
+        ));
+        if shred {
+            for share_path in paths {
+                utils::secure_delete(share_path)?;
+            }
+            print_info("Shredded the share input files");
+        }
+        return Ok(json!({ "output": path }));
+    }
+
+    if encoding != SecretEncoding::Utf8 {
+        let text = match encoding {
+            SecretEncoding::Hex => hex::encode(secret.as_bytes()),
+            SecretEncoding::Base64 => STANDARD.encode(secret.as_bytes()),
+            SecretEncoding::Utf8 => unreachable!("handled above"),
+        };
+        print_success("Reconstructed secret:");
+        if !is_json_mode() {
+            println!("{text}");
+        }
+        return Ok(json!({ "secret": text }));
+    }
+
+    let text = secret.as_str().ok_or_else(|| {
+        QRCryptError::InvalidFormat(
+            "reconstructed secret is not valid UTF-8; use --binary --output to write it to a \
+             file, or --encoding hex/base64 to print it as text"
+                .to_string(),
+        )
+    })?;
+
+    if no_mask || is_json_mode() {
+        print_success("Reconstructed secret:");
+        if !is_json_mode() {
+            println!("{text}");
+        }
+        return Ok(json!({ "secret": text }));
+    }
+
+    let word_count = text.split_whitespace().count();
+    let fingerprint = QRGenerator::secret_fingerprint(text.as_bytes());
+    print_success("Reconstructed secret (masked preview):");
+    println!("  {}", mask_secret_preview(text));
+    println!("  {word_count} word(s), fingerprint {fingerprint}");
+    if !utils::confirm("Reveal full secret?")? {
+        print_info("Not revealed; rerun with --no-mask to print it immediately.");
+        return Ok(json!({ "word_count": word_count, "fingerprint": fingerprint }));
+    }
+    println!("{text}");
+    Ok(json!({ "secret": text }))
+}
+
+/// Check every share in `shares` against `verify_key` (a 32-byte raw
+/// Ed25519 public key file), if `--verify-key` was given; a no-op
+/// otherwise. Fails on the first share that's missing a signature or whose
+/// signature doesn't match, naming it, rather than reporting only that some
+/// share among several failed.
+fn verify_share_signatures(
+    shares: &[shamir::ShamirShare],
+    verify_key: Option<&Path>,
+) -> Result<()> {
+    let Some(path) = verify_key else {
+        return Ok(());
+    };
+    let verifying_key = signing::load_verifying_key(path)?;
+    for share in shares {
+        signing::verify_share(share, &verifying_key)?;
+    }
+    Ok(())
+}
+
+fn handle_validate(
+    paths: &[std::path::PathBuf],
+    scan_dir: Option<&Path>,
+    count: Option<usize>,
+    deep: bool,
+    verify_key: Option<&Path>,
+) -> Result<Value> {
+    if count.is_some() && scan_dir.is_none() {
+        return Err(QRCryptError::InvalidFormat(
+            "--count requires --scan-dir".to_string(),
+        ));
+    }
+    let loaded = match scan_dir {
+        Some(dir) => QRScanner::scan_directory_for_validation(dir, count)?,
+        None => load_shares(paths)?,
+    };
+    let loaded_for_deep = deep.then(|| loaded.clone());
+    let (result, labels) = match loaded {
+        ScannedShares::Custom { shares, parity } => match repair_if_needed(shares, parity) {
+            Ok(shares) => {
+                let labels: Vec<Value> = shares
+                    .iter()
+                    .map(|s| json!({ "index": s.index, "label": s.label }))
+                    .collect();
+                let result = if shares.iter().any(|s| s.group_id.is_some()) {
+                    shamir::validate_grouped_shares(&shares)
+                } else {
+                    shamir::validate_shares(&shares)
+                }
+                .and_then(|()| verify_share_signatures(&shares, verify_key));
+                (result, labels)
+            }
+            Err(e) => (Err(e), Vec::new()),
+        },
+        ScannedShares::Slip39(mnemonics) => {
+            if verify_key.is_some() {
+                (
+                    Err(QRCryptError::InvalidFormat(
+                        "--verify-key is only supported for --format custom shares".to_string(),
+                    )),
+                    Vec::new(),
+                )
+            } else {
+                (slip39::validate_shares(&mnemonics), Vec::new())
+            }
+        }
+    };
+    match result {
+        Ok(()) => {
+            print_success("Shares can reconstruct the secret");
+            if !is_json_mode() {
+                for label in &labels {
+                    if let Some(name) = label.get("label").and_then(Value::as_str) {
+                        println!("  Share {}: {name}", label["index"]);
+                    }
+                }
+            }
+            let mut value = json!({ "valid": true, "shares": labels });
+            if let Some(loaded) = loaded_for_deep {
+                value["deep"] = run_deep_validation(loaded)?;
+            }
+            Ok(value)
+        }
+        Err(e) => {
+            print_info(&format!("Shares cannot reconstruct the secret: {e}"));
+            Err(e)
+        }
+    }
+}
+
+/// `validate --deep`'s fire-drill: actually reconstruct `loaded` in memory,
+/// check the result's shape (BIP39 mnemonic, other UTF-8 text, or binary),
+/// and zeroize it immediately -- it's never printed or returned.
+fn run_deep_validation(loaded: ScannedShares) -> Result<Value> {
+    let secret = match loaded {
+        ScannedShares::Custom { shares, parity } => {
+            reconstruct_custom_shares(shares, parity, false)?
+        }
+        ScannedShares::Slip39(mnemonics) => {
+            let passphrase = utils::prompt_password(
+                "Enter the SLIP-39 passphrase (press Enter if none was set): ",
+            )?;
+            slip39::reconstruct_secret(&mnemonics, &passphrase)?
+        }
+    };
+
+    let report = match secret.as_str() {
+        Some(text)
+            if derive::validate_bip39_words(text).is_ok()
+                && derive::validate_full_bip39_mnemonic(text).is_ok() =>
+        {
+            print_success("Reconstruction succeeded: a valid BIP39 mnemonic");
+            json!({ "reconstructed": true, "format": "bip39" })
+        }
+        Some(_) => {
+            print_success("Reconstruction succeeded: valid UTF-8 text (not a BIP39 mnemonic)");
+            json!({ "reconstructed": true, "format": "utf8" })
+        }
+        None => {
+            print_success("Reconstruction succeeded: valid binary data (not UTF-8 text)");
+            json!({ "reconstructed": true, "format": "binary" })
+        }
+    };
+    drop(secret);
+    Ok(report)
+}
+
+/// Recompute a share card's hash and compare it against the one `split`
+/// recorded for that share's index in `info.txt`, to catch a card that was
+/// reprinted, hand-edited, or corrupted since the split.
+fn handle_verify_share(share_path: &Path, info_path: &Path) -> Result<Value> {
+    let share = match QRScanner::scan_path(share_path)? {
+        QRData::ShamirShare(share) => share,
+        _ => {
+            return Err(QRCryptError::InvalidFormat(format!(
+                "{} does not look like a Shamir share",
+                share_path.display()
+            )))
+        }
+    };
+    let actual = QRGenerator::share_fingerprint(&share)?;
+
+    let info_text = std::fs::read_to_string(info_path)?;
+    let lines: Vec<&str> = info_text.lines().collect();
+    let marker = format!("Share {}", share.index);
+    let recorded = lines.iter().enumerate().find_map(|(i, line)| {
+        let rest = line.strip_prefix(&marker)?;
+        if rest.starts_with(':') || rest.starts_with(' ') || rest.starts_with('(') {
+            lines
+                .get(i + 1)?
+                .trim()
+                .strip_prefix("SHA-256:")
+                .map(|h| h.trim().to_string())
+        } else {
+            None
+        }
+    });
+
+    match recorded {
+        Some(hash) if hash == actual => {
+            print_success(&format!(
+                "Share {} matches the hash recorded in {}",
+                share.index,
+                info_path.display()
+            ));
+            Ok(json!({ "valid": true, "index": share.index, "hash": actual }))
+        }
+        Some(hash) => {
+            print_error(&format!(
+                "Share {} does not match: info.txt recorded {hash}, the card is {actual}",
+                share.index
+            ));
+            Err(QRCryptError::InvalidFormat(format!(
+                "share {} does not match the hash recorded in {}",
+                share.index,
+                info_path.display()
+            )))
+        }
+        None => Err(QRCryptError::InvalidFormat(format!(
+            "{} does not record a hash for share {}",
+            info_path.display(),
+            share.index
+        ))),
+    }
+}
+
+/// Generate an Ed25519 keypair for `encrypt --sign-key`/`qrcrypt verify`,
+/// writing the signing key to `output` and the matching public key
+/// alongside it at `<output>.pub`.
+fn handle_keygen(output: &Path) -> Result<Value> {
+    let signing_key = signing::generate_signing_key();
+    let pub_path = {
+        let mut name = output.as_os_str().to_os_string();
+        name.push(".pub");
+        std::path::PathBuf::from(name)
+    };
+    std::fs::write(output, signing_key.to_bytes())?;
+    std::fs::write(&pub_path, signing_key.verifying_key().to_bytes())?;
+
+    print_success(&format!(
+        "Keypair saved: signing key {}, public key {}",
+        output.display(),
+        pub_path.display()
+    ));
+    print_info(&format!(
+        "Fingerprint: {}",
+        signing::key_fingerprint(&signing_key.verifying_key())
+    ));
+    Ok(json!({
+        "signing_key": output,
+        "public_key": pub_path,
+        "fingerprint": signing::key_fingerprint(&signing_key.verifying_key()),
+    }))
+}
+
+/// Check an `encrypt --sign-key` payload signature: scan `payload` and
+/// `signature` the same way `decrypt` scans a QR code, then verify the
+/// signature against `pubkey` without needing the payload's password.
+fn handle_verify(payload: &Path, signature: &Path, pubkey: &Path) -> Result<Value> {
+    let data = QRScanner::interactive_scan(payload)?;
+    let signature_data = match QRScanner::interactive_scan(signature)? {
+        QRData::PayloadSignature(sig) => sig,
+        _ => {
+            return Err(QRCryptError::InvalidFormat(format!(
+                "{} does not contain a payload signature",
+                signature.display()
+            )))
+        }
+    };
+    let verifying_key = signing::load_verifying_key(pubkey)?;
+
+    match signing::verify_payload(&data, &signature_data, &verifying_key) {
+        Ok(()) => {
+            print_success(&format!(
+                "{} is signed by the holder of {}",
+                payload.display(),
+                pubkey.display()
+            ));
+            Ok(json!({
+                "valid": true,
+                "key_fingerprint": signature_data.key_fingerprint,
+            }))
+        }
+        Err(e) => {
+            print_error(&e.to_string());
+            Err(e)
+        }
+    }
+}
+
+/// Report the `qrcrypt:meta` PNG metadata chunk at `path`, if any, without
+/// decoding the QR code it contains. Never an error just because the chunk
+/// is missing -- an older qrcrypt file predating this feature, or a PNG
+/// that was never qrcrypt's, should report that plainly rather than fail.
+fn handle_inspect(path: &Path) -> Result<Value> {
+    // Best-effort: a scan failure here (e.g. a PNG with no QR code, or one
+    // qrcrypt can't decode) shouldn't fail `inspect` outright -- the PNG
+    // metadata chunk below still might have something to say.
+    let envelope = match QRScanner::scan_path(path) {
+        Ok(QRData::Encrypted(data)) => Some(data),
+        _ => None,
+    };
+    let envelope_created_at = envelope.as_ref().and_then(|e| e.created_at);
+    let envelope_label = envelope.as_ref().and_then(|e| e.label.clone());
+    if !is_json_mode() && (envelope_created_at.is_some() || envelope_label.is_some()) {
+        if let Some(created_at) = envelope_created_at {
+            println!("  Envelope created: {created_at}");
+        }
+        if let Some(label) = &envelope_label {
+            println!("  Envelope label: {label}");
+        }
+    }
+
+    match QRGenerator::read_png_metadata(path)? {
+        Some(metadata) => {
+            print_success(&format!("{} is a qrcrypt PNG:", path.display()));
+            if !is_json_mode() {
+                println!("  Type: {}", metadata.data_type);
+                println!("  Format version: {}", metadata.format_version);
+                println!("  Created: {}", metadata.created);
+                println!("  Payload fingerprint: {}", metadata.payload_fingerprint);
+            }
+            Ok(json!({
+                "has_metadata": true,
+                "data_type": metadata.data_type,
+                "format_version": metadata.format_version,
+                "created": metadata.created,
+                "payload_fingerprint": metadata.payload_fingerprint,
+                "envelope_created_at": envelope_created_at,
+                "envelope_label": envelope_label,
+            }))
+        }
+        None => {
+            print_info(&format!(
+                "{} has no qrcrypt metadata chunk (an older file, or not a qrcrypt PNG)",
+                path.display()
+            ));
+            Ok(json!({
+                "has_metadata": false,
+                "envelope_created_at": envelope_created_at,
+                "envelope_label": envelope_label,
+            }))
+        }
+    }
+}
+
+/// Reconstruct a secret from existing shares and immediately re-split it
+/// into a fresh set under a new `set_id`, without ever writing the
+/// plaintext to disk. The reconstructed `SecretData` zeroizes itself on
+/// drop at the end of this function, same as everywhere else it's used.
+#[allow(clippy::too_many_arguments)]
+fn handle_reshare(
+    paths: &[std::path::PathBuf],
+    threshold: u8,
+    total: Option<u8>,
+    ids: Option<String>,
+    output_dir: &Path,
+    dpi: u32,
+    font: Option<&Path>,
+    card_title: Option<&str>,
+    card_subtitle: Option<&str>,
+) -> Result<Value> {
+    let ids = ids.map(|spec| parse_ids(&spec)).transpose()?;
+    let (shares, parity) = match load_shares(paths)? {
+        ScannedShares::Custom { shares, parity } => (shares, parity),
+        ScannedShares::Slip39(_) => {
+            return Err(QRCryptError::InvalidFormat(
+                "reshare only supports QRCrypt's own share format, not SLIP-39".to_string(),
+            ))
+        }
+    };
+    let mut shares = repair_if_needed(shares, parity)?;
+    decrypt_encrypted_shares(&mut shares)?;
+    let secret = reconstruct_secret(&shares)?;
+
+    std::fs::create_dir_all(output_dir)?;
+    let new_shares = match &ids {
+        Some(ids) => shamir::split_secret_with_ids(secret.as_bytes(), threshold, ids)?,
+        None => {
+            let total = total.expect("clap requires --total without --ids");
+            split_secret(secret.as_bytes(), threshold, total)?
+        }
+    };
+    let total = new_shares.len() as u8;
+    let filenames = QRGenerator::save_shamir_card_qrs(
+        &new_shares,
+        output_dir,
+        "qrcrypt",
+        QrColors::default(),
+        EcLevel::M,
+        dpi,
+        font,
+        card_title,
+        card_subtitle,
+        None,
+        None,
+        None,
+    )?;
+    let info = QRGenerator::generate_info_text(&new_shares, &filenames, 0, secret.as_bytes());
+    std::fs::write(output_dir.join("info.txt"), info)?;
+
+    print_success(&format!(
+        "Resplit secret into {total} new shares (threshold {threshold}) in {}",
+        output_dir.display()
+    ));
+    print_warning(
+        "the shares you just reconstructed from are now part of a superseded set; destroy \
+         them, or anyone who keeps one can combine it with enough others to recover this secret",
+    );
+    Ok(json!({
+        "output_dir": output_dir,
+        "threshold": threshold,
+        "total": total,
+        "shares": filenames,
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_encrypt_file(
+    input: &Path,
+    output_dir: &Path,
+    allow_weak_password: bool,
+    qr_color: Option<String>,
+    qr_background: Option<String>,
+    invert: bool,
+    module_style: ModuleStyleArg,
+    fill_ratio: f32,
+    structured_append: bool,
+) -> Result<Value> {
+    if structured_append {
+        return Err(QRCryptError::QRGeneration(
+            "--structured-append isn't implemented yet: the qrcode crate only exposes the \
+             structured-append mode indicator, not the sequence/total/parity fields that must \
+             follow it, and those are pushed with bit-level helpers the crate keeps private, so \
+             there's no way to emit a real ISO/IEC 18004 structured-append header without \
+             forking it"
+                .to_string(),
+        ));
+    }
+    let colors = resolve_qr_colors(
+        qr_color,
+        qr_background,
+        qr::DEFAULT_BORDER_MODULES,
+        invert,
+        module_style,
+        fill_ratio,
+    )?;
+    let plaintext = std::fs::read(input)?;
+    let password = utils::prompt_password("Enter password: ")?;
+    if !utils::check_password_strength(&password, allow_weak_password)? {
+        print_info("Aborted.");
+        return Ok(json!({ "aborted": true }));
+    }
+
+    let message = format!(
+        "Deriving key ({})... this may take a few seconds",
+        describe_kdf_cost(&KdfParams::Argon2id)
+    );
+    let encrypted = utils::with_kdf_progress(&message, || Crypto::encrypt(&plaintext, &password))?;
+    std::fs::create_dir_all(output_dir)?;
+
+    let filenames = QRGenerator::save_file_parts(
+        &encrypted.hidden.ciphertext,
+        &encrypted.hidden.salt,
+        &encrypted.hidden.nonce,
+        &encrypted.kdf,
+        output_dir,
+        "qrcrypt-file",
+        colors,
+        EcLevel::M,
+    )?;
+
+    let info = QRGenerator::generate_file_part_info_text(&filenames);
+    std::fs::write(output_dir.join("info.txt"), info)?;
+
+    print_success(&format!(
+        "Encrypted {} into {} part(s) in {}",
+        input.display(),
+        filenames.len(),
+        output_dir.display()
+    ));
+    Ok(json!({
+        "output_dir": output_dir,
+        "parts": filenames,
+    }))
+}
+
+fn handle_decrypt_file(input_dir: &Path, output: &Path, shred: bool) -> Result<Value> {
+    let parts = QRScanner::scan_file_parts(input_dir)?;
+    let password = utils::prompt_password("Enter password: ")?;
+
+    let first = &parts[0];
+    let ciphertext: Vec<u8> = parts.iter().flat_map(|p| p.data.clone()).collect();
+    let encrypted = EncryptedData {
+        hidden: Layer {
+            salt: first.salt.clone(),
+            nonce: first.nonce.clone(),
+            ciphertext,
+            // `FilePart` doesn't carry a key-commitment tag either; a wrong
+            // password here is only caught by AES-GCM's tag check.
+            key_commitment: None,
+        },
+        decoy: None,
+        fido2_challenge: None,
+        kdf: first.kdf.clone(),
+        // `FilePart` doesn't carry the Argon2 algorithm/version stamp the
+        // way `EncryptedData` does; fall back to whatever `Argon2::default()`
+        // produces now, same as a legacy `EncryptedData` without these fields.
+        kdf_algorithm: None,
+        kdf_version: None,
+        created_at: None,
+        label: None,
+    };
+
+    let message = format!(
+        "Deriving key ({})... this may take a few seconds",
+        describe_kdf_cost(&encrypted.kdf)
+    );
+    let plaintext = utils::with_kdf_progress(&message, || Crypto::decrypt(&encrypted, &password))?;
+    std::fs::write(output, plaintext.as_slice())?;
+
+    print_success(&format!("Decrypted file written to {}", output.display()));
+
+    if shred {
+        for entry in std::fs::read_dir(input_dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                utils::secure_delete(&path)?;
+            }
+        }
+        print_info("Shredded the part QR images in the input directory");
+    }
+
+    Ok(json!({ "output": output }))
+}
+
+fn handle_validate_phrase(mnemonic: Option<String>, with_passphrase: bool) -> Result<Value> {
+    let mnemonic = read_secret(mnemonic)?;
+    let mnemonic = mnemonic.as_str().ok_or_else(|| {
+        QRCryptError::InvalidFormat("mnemonic must be valid UTF-8 text".to_string())
+    })?;
+    derive::validate_bip39_words(mnemonic)?;
+    derive::validate_full_bip39_mnemonic(mnemonic)?;
+    print_success("Mnemonic is valid");
+
+    if with_passphrase {
+        let passphrase = utils::prompt_password("Enter BIP39 passphrase (25th word): ")?;
+        let fingerprint = derive::seed_fingerprint(mnemonic, &passphrase)?;
+        print_info(&format!("Seed fingerprint: {fingerprint}"));
+        return Ok(json!({ "valid": true, "seed_fingerprint": fingerprint }));
+    }
+
+    Ok(json!({ "valid": true }))
+}
+
+/// Benchmark Argon2id at doubling memory costs (keeping the default time and
+/// parallelism costs fixed, per OWASP's tuning guidance) until a single
+/// derivation takes at least `target_ms`, then report that memory cost as a
+/// starting point for this machine. Nothing is stored or changed; encryption
+/// always uses `argon2::Params::default()` regardless of this result.
+fn handle_calibrate(target_ms: u64) -> Result<Value> {
+    use argon2::{Algorithm, Argon2, Params, Version};
+
+    const MAX_M_COST_KIB: u32 = 4 * 1024 * 1024; // 4 GiB, a sane upper bound
+
+    let time_cost = Params::DEFAULT_T_COST;
+    let parallelism = Params::DEFAULT_P_COST;
+    let mut m_cost = Params::DEFAULT_M_COST;
+    let mut elapsed_ms;
+
+    loop {
+        let params = Params::new(m_cost, time_cost, parallelism, None)
+            .map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+        let mut key = [0u8; 32];
+        let start = std::time::Instant::now();
+        argon2
+            .hash_password_into(b"qrcrypt-calibration-benchmark", &[0u8; 16], &mut key)
+            .map_err(|e| QRCryptError::KeyDerivation(e.to_string()))?;
+        elapsed_ms = start.elapsed().as_millis() as u64;
+
+        if elapsed_ms >= target_ms || m_cost >= MAX_M_COST_KIB {
+            break;
+        }
+        m_cost = (m_cost * 2).min(MAX_M_COST_KIB);
+    }
+
+    let m_cost_mib = m_cost / 1024;
+    print_success(&format!(
+        "Argon2id at {m_cost_mib} MiB, {time_cost} iteration(s) took {elapsed_ms} ms on this machine"
+    ));
+    print_info("qrcrypt doesn't yet expose a flag to use this memory cost for encryption; this is a reference point for judging how strong the current default feels on your hardware.");
+
+    Ok(json!({
+        "target_ms": target_ms,
+        "measured_ms": elapsed_ms,
+        "recommended_m_cost_mib": m_cost_mib,
+        "recommended_t_cost": time_cost,
+    }))
+}
+
+/// Prompt for a secret, hidden, and if it looks like a BIP39 mnemonic (see
+/// `looks_like_bip39_mnemonic`) validate it on the spot, looping until the
+/// user either fixes it or explicitly chooses to use it anyway -- the
+/// "live BIP39 checking" `setup` promises, since a typo is worth catching
+/// before it's sealed into a QR code rather than after.
+fn prompt_seed_phrase_for_setup() -> Result<String> {
+    loop {
+        let text = utils::prompt_password("Enter your secret (seed phrase or anything else): ")?;
+        if !looks_like_bip39_mnemonic(&text) {
+            return Ok(text);
+        }
+        let normalized = derive::normalize_seed_phrase(&text);
+        let validation = derive::validate_bip39_words(&normalized)
+            .and_then(|_| derive::validate_full_bip39_mnemonic(&normalized));
+        match validation {
+            Ok(()) => {
+                print_success("Looks like a valid BIP39 mnemonic (checksum matches).");
+                return Ok(text);
+            }
+            Err(e) => {
+                print_warning(&format!(
+                    "this looks like a BIP39 mnemonic but isn't valid ({e})"
+                ));
+                if utils::confirm("Use it anyway?")? {
+                    return Ok(text);
+                }
+            }
+        }
+    }
+}
+
+/// Prompt for a path, showing `default` as a fallback for an empty answer.
+fn prompt_setup_path(prompt: &str, default: &str) -> std::io::Result<std::path::PathBuf> {
+    let input = utils::prompt_line(&format!("{prompt} [{default}]: "))?;
+    Ok(std::path::PathBuf::from(if input.is_empty() {
+        default.to_string()
+    } else {
+        input
+    }))
+}
+
+/// Ask for a Shamir threshold and total share count, with a one-line
+/// explanation of what they mean, looping on anything out of range.
+fn prompt_threshold_and_total() -> std::io::Result<(u8, u8)> {
+    print_info(
+        "Threshold is how many shares are needed to recover the secret; total is how many \
+         shares you'll create. E.g. 2-of-3 means any 2 of the 3 shares reconstruct it, and \
+         losing one is fine.",
+    );
+    let total = loop {
+        let input = utils::prompt_line("Total number of shares to create: ")?;
+        match input.parse::<u8>() {
+            Ok(n) if n >= 2 => break n,
+            _ => print_warning("enter a whole number of at least 2"),
+        }
+    };
+    let threshold = loop {
+        let input = utils::prompt_line(&format!("Threshold needed to reconstruct (2-{total}): "))?;
+        match input.parse::<u8>() {
+            Ok(n) if (2..=total).contains(&n) => break n,
+            _ => print_warning(&format!("enter a whole number between 2 and {total}")),
+        }
+    };
+    Ok((threshold, total))
+}
+
+/// Interactive guided setup for first-time users: choose encrypt or split,
+/// enter the secret with live BIP39 checking, and pick where to save --
+/// then hand off to `handle_encrypt`/`handle_split` for everything else
+/// (including the password prompt and its strength check), the same as the
+/// `encrypt`/`split` subcommands use directly.
+fn handle_setup() -> Result<Value> {
+    print_info("This wizard walks through encrypting or splitting a secret step by step.");
+    print_info("Skip it any time by using `encrypt` or `split` directly with explicit flags.");
+
+    let encrypt = loop {
+        let answer = utils::prompt_line(
+            "What would you like to do?\n  1) Encrypt a secret into a single QR code\n  2) \
+             Split a secret into multiple shares (Shamir's Secret Sharing)\nEnter 1 or 2: ",
+        )?;
+        match answer.as_str() {
+            "1" => break true,
+            "2" => break false,
+            _ => print_warning("please enter 1 or 2"),
+        }
+    };
+
+    let secret = prompt_seed_phrase_for_setup()?;
+    utils::set_wizard_mode(true);
+    let result = if encrypt {
+        let output = prompt_setup_path("Where should the encrypted QR code be saved?", "encrypted.png")?;
+        print_info(
+            "Next you'll be asked for a password; a weak one gets a warning with an estimated \
+             crack time.",
+        );
+        handle_encrypt(
+            Some(secret),
+            &output,
+            EncryptOptions {
+                decoy_secret: None,
+                decoy_password: None,
+                decoy_bip85_index: None,
+                with_passphrase: false,
+                fido2: false,
+                test_vector: false,
+                kdf: KdfChoice::Argon2id,
+                scrypt_n: 1 << 20,
+                scrypt_r: 8,
+                scrypt_p: 1,
+                allow_weak_password: false,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                error_correction: None,
+                logo: None,
+                logo_max_fraction: qr::DEFAULT_LOGO_MAX_FRACTION,
+                dry_run: false,
+                animated: None,
+                frames: 60,
+                fps: 4,
+                max_fragment: 60,
+                size_mm: None,
+                dpi: qr::DEFAULT_CARD_DPI,
+                min_module_mm: qr::DEFAULT_MIN_MODULE_MM,
+                max_qr_version: qr::DEFAULT_MAX_QR_VERSION,
+                symbology: qr::Symbology::Qr,
+                sign_key: None,
+                raw_payload: false,
+                label: None,
+                no_timestamp: false,
+            },
+        )
+    } else {
+        let (threshold, total) = prompt_threshold_and_total()?;
+        let output_dir = prompt_setup_path("Where should the share QR codes be saved?", "shares")?;
+        handle_split(
+            Some(secret),
+            false,
+            None,
+            &output_dir,
+            SplitOptions {
+                threshold: Some(threshold),
+                total: Some(total),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: qr::DEFAULT_BORDER_MODULES,
+                error_correction: None,
+                dpi: qr::DEFAULT_CARD_DPI,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                symbology: qr::Symbology::Qr,
+                password: false,
+                allow_weak_password: false,
+            },
+        )
+    };
+    utils::set_wizard_mode(false);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shamir::ShamirShare;
+
+    fn write_share(dir: &Path, filename: &str, share: &ShamirShare) -> std::path::PathBuf {
+        let path = dir.join(filename);
+        std::fs::write(
+            &path,
+            serde_json::to_string(&QRData::ShamirShare(share.clone())).unwrap(),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn mask_secret_preview_hides_every_word_but_the_first_and_last() {
+        assert_eq!(
+            mask_secret_preview("abandon ability able about above absent absorb"),
+            "abandon ••• absorb"
+        );
+        assert_eq!(mask_secret_preview("onlyword"), "onlyword");
+        assert_eq!(mask_secret_preview("two words"), "two words");
+    }
+
+    #[test]
+    fn validate_deep_actually_reconstructs_and_reports_the_secret_shape() {
+        let secret = b"deep validate test".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-validate-deep-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = vec![
+            write_share(&dir, "share1.json", &shares[0]),
+            write_share(&dir, "share2.json", &shares[1]),
+        ];
+
+        let result = handle_validate(&paths, None, None, true, None).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result["valid"], true);
+        assert_eq!(result["deep"]["reconstructed"], true);
+        assert_eq!(result["deep"]["format"], "utf8");
+    }
+
+    #[test]
+    fn load_shares_dedupes_and_reconstructs_a_3_of_5_split() {
+        let secret = b"load shares dedup test".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-load-shares-dedup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = vec![
+            write_share(&dir, "share1.json", &shares[0]),
+            write_share(&dir, "share1-again.json", &shares[0]),
+            write_share(&dir, "share2.json", &shares[1]),
+            write_share(&dir, "share3.json", &shares[2]),
+        ];
+
+        let loaded = load_shares(&paths).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        match loaded {
+            ScannedShares::Custom {
+                shares: loaded_shares,
+                ..
+            } => {
+                assert_eq!(loaded_shares.len(), 3);
+                let recovered = reconstruct_secret(&loaded_shares).unwrap();
+                assert_eq!(recovered.as_bytes(), secret.as_slice());
+            }
+            ScannedShares::Slip39(_) => panic!("expected custom shares"),
+        }
+    }
+
+    #[test]
+    fn load_shares_dedupes_the_same_path_given_twice() {
+        let secret = b"load shares same path twice test".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-load-shares-same-path-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let share1 = write_share(&dir, "share1.json", &shares[0]);
+        let share2 = write_share(&dir, "share2.json", &shares[1]);
+        let paths = vec![share1.clone(), share1, share2];
+
+        let loaded = load_shares(&paths).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        match loaded {
+            ScannedShares::Custom {
+                shares: loaded_shares,
+                ..
+            } => {
+                assert_eq!(loaded_shares.len(), 2);
+                let recovered = reconstruct_secret(&loaded_shares).unwrap();
+                assert_eq!(recovered.as_bytes(), secret.as_slice());
+            }
+            ScannedShares::Slip39(_) => panic!("expected custom shares"),
+        }
+    }
+
+    #[test]
+    fn load_shares_rejects_shares_with_mismatched_threshold_total() {
+        let shares_a = split_secret(b"set a", 2, 3).unwrap();
+        let shares_b = split_secret(b"set b", 3, 4).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-load-shares-mismatched-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = vec![
+            write_share(&dir, "share1.json", &shares_a[0]),
+            write_share(&dir, "share2.json", &shares_b[1]),
+        ];
+
+        let err = load_shares(&paths).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            err.to_string().contains("different threshold/total"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn load_shares_still_reconstructs_when_more_than_threshold_unique_shares_are_given() {
+        let secret = b"extra shares test".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-load-shares-extra-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = vec![
+            write_share(&dir, "share1.json", &shares[0]),
+            write_share(&dir, "share2.json", &shares[1]),
+            write_share(&dir, "share3.json", &shares[2]),
+            write_share(&dir, "share4.json", &shares[3]),
+        ];
+
+        let loaded = load_shares(&paths).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        match loaded {
+            ScannedShares::Custom {
+                shares: loaded_shares,
+                ..
+            } => {
+                assert_eq!(loaded_shares.len(), 4);
+                let recovered = reconstruct_secret(&loaded_shares).unwrap();
+                assert_eq!(recovered.as_bytes(), secret.as_slice());
+            }
+            ScannedShares::Slip39(_) => panic!("expected custom shares"),
+        }
+    }
+
+    #[test]
+    fn split_dry_run_reports_planned_filenames_without_writing_anything() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-split-dry-run-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("dry run test secret".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: true,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        assert!(!dir.exists());
+        assert_eq!(result["dry_run"], true);
+        assert_eq!(result["shares"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn split_no_info_skips_info_txt_and_info_writes_it_to_an_explicit_path() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-split-no-info-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let base_opts = SplitOptions {
+            threshold: Some(2),
+            total: Some(3),
+            ids: None,
+            share_encoding: ShareEncodingArg::Gf256,
+            group: Vec::new(),
+            groups_required: None,
+            format: ShareFormat::Custom,
+            share_passwords: false,
+            share_password_file: None,
+            labels: None,
+            parity: 0,
+            qr_color: None,
+            qr_background: None,
+            invert: false,
+            module_style: ModuleStyleArg::Square,
+            fill_ratio: 1.0,
+            border: 4,
+            error_correction: None,
+            dpi: 300,
+            font: None,
+            card_title: None,
+            card_subtitle: None,
+            plain_qr: false,
+            no_info: true,
+            info: None,
+            dry_run: false,
+            stealth: false,
+            words_only: false,
+            pdf: None,
+            per_page: 1,
+            sign_key: None,
+            sheet: None,
+            paper_size: PaperSizeArg::A4,
+            card_back: None,
+            card_back_text: None,
+            with_verify: false,
+            password: false,
+            allow_weak_password: false,
+            symbology: qr::Symbology::Qr,
+        };
+        handle_split(
+            Some("no info test secret".to_string()),
+            false,
+            None,
+            &dir,
+            base_opts,
+        )
+        .unwrap();
+        assert!(!dir.join("info.txt").exists());
+        assert!(dir.join("qrcrypt-share-1.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let custom_info_path = std::env::temp_dir().join(format!(
+            "qrcrypt-split-custom-info-{}.txt",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&custom_info_path);
+        handle_split(
+            Some("custom info path test secret".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: Some(custom_info_path.clone()),
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+        assert!(!dir.join("info.txt").exists());
+        assert!(custom_info_path.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_file(&custom_info_path).unwrap();
+    }
+
+    #[test]
+    fn split_with_custom_ids_round_trips_and_reports_the_given_total() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-split-ids-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("custom id test".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: None,
+                ids: Some("9,40,255".to_string()),
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result["total"], 3);
+        assert_eq!(result["shares"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn split_with_a_high_error_correction_level_round_trips() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-split-ec-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("error correction test".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: Some(EcLevelArg::H),
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result["total"], 3);
+        assert_eq!(result["shares"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn split_with_gf65536_encoding_round_trips_through_reconstruct() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-split-gf65536-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("ab".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf65536,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        let paths: Vec<std::path::PathBuf> = result["shares"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| std::path::PathBuf::from(v.as_str().unwrap()))
+            .take(2)
+            .collect();
+
+        let reconstructed = handle_reconstruct(
+            &paths,
+            None,
+            false,
+            false,
+            None,
+            ReconstructOptions {
+                diagnose: false,
+                stealth: false,
+                password: false,
+                ssss: false,
+                ssss_threshold: None,
+                verify_only: false,
+                info: None,
+                no_mask: true,
+                shred: false,
+                encoding: SecretEncoding::Utf8,
+            },
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(reconstructed["secret"], "ab");
+    }
+
+    #[test]
+    fn reconstruct_encoding_prints_a_non_utf8_secret_as_hex_or_base64() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-split-encoding-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let output_dir = dir.join("shares");
+
+        let secret_bytes: &[u8] = &[0xff, 0x00, 0xd8, 0x7f, 0x01];
+        let input_path = dir.join("secret.bin");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&input_path, secret_bytes).unwrap();
+
+        let result = handle_split(
+            None,
+            true,
+            Some(&input_path),
+            &output_dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        let paths: Vec<std::path::PathBuf> = result["shares"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| std::path::PathBuf::from(v.as_str().unwrap()))
+            .take(2)
+            .collect();
+
+        let reconstruct_with = |encoding: SecretEncoding| {
+            handle_reconstruct(
+                &paths,
+                None,
+                false,
+                false,
+                None,
+                ReconstructOptions {
+                    diagnose: false,
+                    stealth: false,
+                    password: false,
+                    ssss: false,
+                    ssss_threshold: None,
+                    verify_only: false,
+                    info: None,
+                    no_mask: true,
+                    shred: false,
+                    encoding,
+                },
+            )
+            .unwrap()
+        };
+
+        let hex_result = reconstruct_with(SecretEncoding::Hex);
+        assert_eq!(hex_result["secret"], hex::encode(secret_bytes));
+
+        let base64_result = reconstruct_with(SecretEncoding::Base64);
+        assert_eq!(base64_result["secret"], STANDARD.encode(secret_bytes));
+
+        let utf8_err = handle_reconstruct(
+            &paths,
+            None,
+            false,
+            false,
+            None,
+            ReconstructOptions {
+                diagnose: false,
+                stealth: false,
+                password: false,
+                ssss: false,
+                ssss_threshold: None,
+                verify_only: false,
+                info: None,
+                no_mask: true,
+                shred: false,
+                encoding: SecretEncoding::Utf8,
+            },
+        );
+        assert!(utf8_err.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn split_words_only_writes_no_qr_cards_and_reconstruct_words_decodes_them() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-split-words-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("words only test".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: true,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        assert!(result["shares"].is_null());
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            1,
+            "only info.txt should be written, no QR card PNGs"
+        );
+
+        let info = std::fs::read_to_string(dir.join("info.txt")).unwrap();
+        let words: Vec<String> = info
+            .lines()
+            .filter(|line| line.starts_with("  "))
+            .take(2)
+            .map(|line| line.trim().to_string())
+            .collect();
+        assert_eq!(words.len(), 2);
+
+        let shares: Vec<shamir::ShamirShare> = words
+            .iter()
+            .map(|w| {
+                shamir::decode_share_words(&w.split(' ').map(str::to_string).collect::<Vec<_>>())
+            })
+            .collect::<Result<_>>()
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let secret = shamir::reconstruct_secret(&shares).unwrap();
+        assert_eq!(secret.as_bytes(), b"words only test");
+    }
+
+    #[test]
+    fn split_plain_qr_writes_captioned_qrs_smaller_than_a_full_card() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-split-plain-qr-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("plain qr split test".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: true,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        let filenames: Vec<std::path::PathBuf> = result["shares"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| std::path::PathBuf::from(v.as_str().unwrap()))
+            .collect();
+        assert_eq!(filenames.len(), 3);
+
+        let (card_width, card_height) = QRGenerator::card_pixel_dimensions(300);
+        for path in &filenames {
+            let image = image::open(path).unwrap();
+            assert!(
+                image.width() < card_width && image.height() < card_height,
+                "a plain captioned QR should be far smaller than a full card"
+            );
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn split_with_sheet_composes_a_printable_grid_png_alongside_the_cards() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-split-sheet-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let sheet_path = dir.join("sheet.png");
+
+        let result = handle_split(
+            Some("sheet split test".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: Some(sheet_path.clone()),
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        let filenames: Vec<std::path::PathBuf> = result["shares"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| std::path::PathBuf::from(v.as_str().unwrap()))
+            .collect();
+        assert_eq!(filenames.len(), 3);
+        for path in &filenames {
+            assert!(path.exists(), "share cards should still be written normally");
+        }
+
+        assert!(sheet_path.exists(), "expected a sheet PNG to be written");
+        let sheet = image::open(&sheet_path).unwrap();
+        assert!(sheet.width() > 0 && sheet.height() > 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn split_with_card_back_renders_one_back_card_per_share() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-split-card-back-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let back_path = dir.join("back.png");
+
+        let result = handle_split(
+            Some("card back test".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: Some(back_path.clone()),
+                card_back_text: Some("Stored in the kitchen safe.".to_string()),
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        let filenames: Vec<std::path::PathBuf> = result["shares"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| std::path::PathBuf::from(v.as_str().unwrap()))
+            .collect();
+        assert_eq!(filenames.len(), 3);
+
+        assert!(back_path.exists(), "the first share's back card keeps --card-back's own path");
+        assert!(dir.join("back-share-2-back.png").exists());
+        assert!(dir.join("back-share-3-back.png").exists());
+
+        let (card_width, card_height) = QRGenerator::card_pixel_dimensions(300);
+        let back = image::open(&back_path).unwrap();
+        assert_eq!(back.width(), card_width);
+        assert_eq!(back.height(), card_height);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn with_verify_is_rejected_without_password() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-split-with-verify-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let err = handle_split(
+            Some("with-verify test secret".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: true,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap_err()
+        .to_string();
+
+        // A bare unsalted SHA-256 commitment is only safe to put on a share
+        // card when it covers high-entropy ciphertext; --with-verify must
+        // require --password rather than trust the caller's secret to
+        // already be high-entropy.
+        assert!(err.contains("--with-verify"), "unexpected error: {err}");
+        assert!(err.contains("--password"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn with_verify_is_rejected_outside_format_custom() {
+        let dir = std::env::temp_dir()
+            .join(format!("qrcrypt-split-with-verify-rejected-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let err = handle_split(
+            Some("rejected".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Slip39,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: true,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("--with-verify"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn password_is_rejected_outside_format_custom() {
+        let dir = std::env::temp_dir()
+            .join(format!("qrcrypt-split-password-rejected-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let err = handle_split(
+            Some("rejected".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Slip39,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: true,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("--password"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn encrypt_dry_run_reports_capacity_without_writing_anything() {
+        let output = std::env::temp_dir().join(format!(
+            "qrcrypt-encrypt-dry-run-{}.png",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&output);
+
+        let result = handle_encrypt(
+            Some("dry run test secret".to_string()),
+            &output,
+            EncryptOptions {
+                decoy_secret: None,
+                decoy_password: None,
+                decoy_bip85_index: None,
+                with_passphrase: false,
+                fido2: false,
+                test_vector: false,
+                kdf: KdfChoice::Argon2id,
+                scrypt_n: 1 << 20,
+                scrypt_r: 8,
+                scrypt_p: 1,
+                allow_weak_password: false,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                error_correction: None,
+                logo: None,
+                logo_max_fraction: qr::DEFAULT_LOGO_MAX_FRACTION,
+                dry_run: true,
+                animated: None,
+                frames: 60,
+                fps: 4,
+                max_fragment: 60,
+                size_mm: None,
+                dpi: qr::DEFAULT_CARD_DPI,
+                min_module_mm: qr::DEFAULT_MIN_MODULE_MM,
+                max_qr_version: qr::DEFAULT_MAX_QR_VERSION,
+                symbology: qr::Symbology::Qr,
+                sign_key: None,
+                raw_payload: false,
+                label: None,
+                no_timestamp: false,
+            },
+        )
+        .unwrap();
+
+        assert!(!output.exists());
+        assert_eq!(result["dry_run"], true);
+        assert_eq!(result["fits"], true);
+    }
+
+    #[test]
+    fn merge_layers_adds_a_decoy_without_touching_the_hidden_secret() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-merge-layers-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let hidden = Crypto::encrypt(b"the real secret", "hidden-pw").unwrap();
+        QRGenerator::save_payload_auto(
+            &QRGenerator::encode_payload(&QRData::Encrypted(hidden)).unwrap(),
+            &dir,
+            "hidden",
+            QrColors::default(),
+            EcLevel::M,
+            qr::DEFAULT_MAX_QR_VERSION,
+            qr::Symbology::Qr,
+        )
+        .unwrap();
+        let input = dir.join("hidden.png");
+
+        let output = dir.join("layered.png");
+        let result = handle_merge_layers(
+            &input,
+            &output,
+            Some("the decoy secret".to_string()),
+            Some("decoy-pw".to_string()),
+            None,
+            None,
+            false,
+            ModuleStyleArg::Square,
+            1.0,
+            None,
+            qr::DEFAULT_MAX_QR_VERSION,
+        )
+        .unwrap();
+        assert!(output.exists());
+        assert!(result["output"].is_array());
+
+        let layered = match QRScanner::interactive_scan(&output).unwrap() {
+            QRData::Encrypted(data) => data,
+            other => panic!("expected an encrypted payload, got {other:?}"),
+        };
+        assert_eq!(
+            Crypto::decrypt_layered(&layered, "hidden-pw")
+                .unwrap()
+                .as_slice(),
+            b"the real secret"
+        );
+        assert_eq!(
+            Crypto::decrypt_layered(&layered, "decoy-pw")
+                .unwrap()
+                .as_slice(),
+            b"the decoy secret"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn estimate_reports_sizing_without_writing_anything_and_never_double_counts_a_file_secret() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-estimate-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let secret_file = dir.join("seed.txt");
+        std::fs::write(&secret_file, "estimate test secret").unwrap();
+
+        let result = handle_estimate(None, Some(&secret_file), Some(3), Some(5), true).unwrap();
+
+        assert_eq!(result["secret_bytes"], 20);
+        assert!(result["encrypted_payload_bytes"].as_u64().unwrap() > 0);
+        let encrypted_levels = result["encrypted"].as_array().unwrap();
+        assert_eq!(encrypted_levels.len(), 4);
+        for level in encrypted_levels {
+            assert_eq!(level["fits"], true);
+            assert!(level["card_module_size_mm"].as_f64().unwrap() > 0.0);
+        }
+        assert!(result["share_payload_bytes"].as_u64().unwrap() > 0);
+        assert_eq!(result["share"].as_array().unwrap().len(), 4);
+
+        // `handle_estimate` should only have read `secret_file`, not written
+        // anything alongside it.
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            1,
+            "estimate must not write any files"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stealth_shares_round_trip_through_split_and_reconstruct() {
+        let secret = b"stealth test".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+        let password = "stealth metadata password";
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-stealth-round-trip-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths =
+            save_stealth_share_qrs(&shares, &dir, password, QrColors::default(), EcLevel::M)
+                .unwrap();
+
+        for path in &paths {
+            match QRScanner::scan_path(path).unwrap() {
+                QRData::Encrypted(_) => {}
+                other => {
+                    panic!("expected a stealth share to scan as QRData::Encrypted, got {other:?}")
+                }
+            }
+        }
+
+        let recovered_shares = load_stealth_shares(&paths[0..2], password).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let recovered = reconstruct_secret(&recovered_shares).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn verify_share_accepts_an_untouched_card_and_rejects_a_tampered_one() {
+        let shares = split_secret(b"verify share test secret", 2, 3).unwrap();
+        let filenames: Vec<std::path::PathBuf> = (1..=3)
+            .map(|i| std::path::PathBuf::from(format!("qrcrypt-share-{i}.png")))
+            .collect();
+        let info =
+            QRGenerator::generate_info_text(&shares, &filenames, 0, b"verify share test secret");
+
+        let dir = std::env::temp_dir().join(format!("qrcrypt-verify-share-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let info_path = dir.join("info.txt");
+        std::fs::write(&info_path, &info).unwrap();
+
+        let share_path = dir.join("share-1.json");
+        std::fs::write(
+            &share_path,
+            QRGenerator::encode_payload(&QRData::ShamirShare(shares[0].clone())).unwrap(),
+        )
+        .unwrap();
+
+        let ok = handle_verify_share(&share_path, &info_path).unwrap();
+        assert_eq!(ok["valid"], true);
+
+        let mut tampered = shares[0].clone();
+        tampered.note = Some("edited after the fact".to_string());
+        let tampered_path = dir.join("share-1-tampered.json");
+        std::fs::write(
+            &tampered_path,
+            QRGenerator::encode_payload(&QRData::ShamirShare(tampered)).unwrap(),
+        )
+        .unwrap();
+
+        let err = handle_verify_share(&tampered_path, &info_path).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(err.to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn inspect_reports_the_metadata_chunk_and_tolerates_a_png_without_one() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-inspect-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let encrypted = crate::crypto::Crypto::encrypt(b"inspect test secret", "password123").unwrap();
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+        let qr_path = dir.join("secret.png");
+        QRGenerator::generate_qr(&payload, &qr_path, QrColors::default(), EcLevel::M, qr::Symbology::Qr)
+            .unwrap();
+
+        let result = handle_inspect(&qr_path).unwrap();
+        assert_eq!(result["has_metadata"], true);
+        assert_eq!(result["data_type"], "encrypted");
+
+        let plain_path = dir.join("screenshot.png");
+        image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 255, 255, 255]))
+            .save(&plain_path)
+            .unwrap();
+        let result = handle_inspect(&plain_path).unwrap();
+        assert_eq!(result["has_metadata"], false);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn split_format_ssss_round_trips_through_reconstruct() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-ssss-cli-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("ssss cli round trip secret".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(3),
+                total: Some(5),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Ssss,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        let paths: Vec<std::path::PathBuf> = result["shares"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| std::path::PathBuf::from(v.as_str().unwrap()))
+            .take(3)
+            .collect();
+        assert!(std::fs::read_to_string(&paths[0])
+            .unwrap()
+            .starts_with("1-"));
+
+        let reconstructed = handle_reconstruct(
+            &paths,
+            None,
+            false,
+            false,
+            None,
+            ReconstructOptions {
+                diagnose: false,
+                stealth: false,
+                password: false,
+                ssss: true,
+                ssss_threshold: Some(3),
+                verify_only: false,
+                info: None,
+                no_mask: true,
+                shred: false,
+                encoding: SecretEncoding::Utf8,
+            },
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(reconstructed["secret"], "ssss cli round trip secret");
+    }
+
+    #[test]
+    fn reconstruct_verify_only_checks_the_fingerprint_without_returning_the_secret() {
+        let dir = std::env::temp_dir().join(format!("qrcrypt-verify-only-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let result = handle_split(
+            Some("verify ok".to_string()),
+            false,
+            None,
+            &dir,
+            SplitOptions {
+                threshold: Some(2),
+                total: Some(3),
+                ids: None,
+                share_encoding: ShareEncodingArg::Gf256,
+                group: Vec::new(),
+                groups_required: None,
+                format: ShareFormat::Custom,
+                share_passwords: false,
+                share_password_file: None,
+                labels: None,
+                parity: 0,
+                qr_color: None,
+                qr_background: None,
+                invert: false,
+                module_style: ModuleStyleArg::Square,
+                fill_ratio: 1.0,
+                border: 4,
+                error_correction: None,
+                dpi: 300,
+                font: None,
+                card_title: None,
+                card_subtitle: None,
+                plain_qr: false,
+                no_info: false,
+                info: None,
+                dry_run: false,
+                stealth: false,
+                words_only: false,
+                pdf: None,
+                per_page: 1,
+                sign_key: None,
+                sheet: None,
+                paper_size: PaperSizeArg::A4,
+                card_back: None,
+                card_back_text: None,
+                with_verify: false,
+                password: false,
+                allow_weak_password: false,
+                symbology: qr::Symbology::Qr,
+            },
+        )
+        .unwrap();
+
+        let paths: Vec<std::path::PathBuf> = result["shares"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| std::path::PathBuf::from(v.as_str().unwrap()))
+            .take(2)
+            .collect();
+        let info_path = dir.join("info.txt");
+
+        let verified = handle_reconstruct(
+            &paths,
+            None,
+            false,
+            false,
+            None,
+            ReconstructOptions {
+                diagnose: false,
+                stealth: false,
+                password: false,
+                ssss: false,
+                ssss_threshold: None,
+                verify_only: true,
+                info: Some(info_path.clone()),
+                no_mask: false,
+                shred: false,
+                encoding: SecretEncoding::Utf8,
+            },
+        )
+        .unwrap();
+        assert_eq!(verified["verified"], true);
+        assert!(verified.get("secret").is_none());
+
+        // Corrupt the recorded fingerprint; verification should now fail.
+        let tampered_info = std::fs::read_to_string(&info_path)
+            .unwrap()
+            .replace("Secret fingerprint: ", "Secret fingerprint: deadbeef");
+        std::fs::write(&info_path, tampered_info).unwrap();
+
+        let err = handle_reconstruct(
+            &paths,
+            None,
+            false,
+            false,
+            None,
+            ReconstructOptions {
+                diagnose: false,
+                stealth: false,
+                password: false,
+                ssss: false,
+                ssss_threshold: None,
+                verify_only: true,
+                info: Some(info_path),
+                no_mask: false,
+                shred: false,
+                encoding: SecretEncoding::Utf8,
+            },
+        )
+        .unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(err.to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn calibrate_reports_a_measured_time_and_a_memory_suggestion() {
+        // A target of 0ms means the very first (default-params) run already
+        // clears it, so this stays a single Argon2id derivation.
+        let result = handle_calibrate(0).unwrap();
+        assert!(result["measured_ms"].as_u64().unwrap() > 0);
+        assert!(result["recommended_m_cost_mib"].as_u64().unwrap() > 0);
+        assert_eq!(result["recommended_t_cost"], argon2::Params::DEFAULT_T_COST);
+    }
+}
@@ -0,0 +1,266 @@
+//! PDF reading and writing for shares: `render_pages` rasterizes a scanned
+//! share PDF back into images for `QRScanner`, and `write_shares_pdf` does
+//! the reverse, laying already-rendered share card PNGs out into a single
+//! printable PDF for `split --pdf`. Requires the `pdf` feature so default
+//! builds don't need to load a native pdfium library or link printpdf.
+
+#[cfg(feature = "pdf")]
+use image::DynamicImage;
+#[cfg(feature = "pdf")]
+use pdfium_render::prelude::*;
+
+use crate::error::{QRCryptError, Result};
+
+/// Render every page of the PDF at `path` to a `DynamicImage`, at a high
+/// enough resolution that a card-sized QR code stays scannable.
+#[cfg(feature = "pdf")]
+pub fn render_pages(path: &std::path::Path) -> Result<Vec<DynamicImage>> {
+    let pdfium = Pdfium::default();
+    let document = pdfium
+        .load_pdf_from_file(path, None)
+        .map_err(|e| QRCryptError::QRScan(format!("could not open {}: {e}", path.display())))?;
+
+    let render_config = PdfRenderConfig::new()
+        .set_target_width(2000)
+        .set_maximum_height(2000);
+
+    document
+        .pages()
+        .iter()
+        .map(|page| {
+            let bitmap = page
+                .render_with_config(&render_config)
+                .map_err(|e| QRCryptError::QRScan(format!("could not render PDF page: {e}")))?;
+            bitmap
+                .as_image()
+                .map_err(|e| QRCryptError::QRScan(format!("could not render PDF page: {e}")))
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn render_pages(_path: &std::path::Path) -> Result<Vec<image::DynamicImage>> {
+    Err(QRCryptError::QRScan(
+        "reading a PDF requires qrcrypt to be rebuilt with --features pdf".to_string(),
+    ))
+}
+
+/// A4 page size for laying out `write_shares_pdf`.
+#[cfg(feature = "pdf")]
+const PAGE_WIDTH_MM: f32 = 210.0;
+#[cfg(feature = "pdf")]
+const PAGE_HEIGHT_MM: f32 = 297.0;
+#[cfg(feature = "pdf")]
+const MARGIN_MM: f32 = 10.0;
+
+/// Render one PDF containing every share card PNG (already written by
+/// `save_shamir_card_qrs`/`save_shamir_parity_qrs` at `dpi`) on its own page
+/// (or `per_page` cards stacked per page), followed by `info_text` as plain
+/// text on however many pages it needs. Embedding the already-rendered PNGs
+/// rather than re-rendering the cards keeps the PDF's QR content byte-for-byte
+/// identical to what's on disk. `dpi` must match the density the cards were
+/// actually rendered at (printpdf scales a raw image with no explicit DPI of
+/// its own onto the page), or the cards come out the wrong physical size.
+#[cfg(feature = "pdf")]
+pub fn write_shares_pdf(
+    card_paths: &[std::path::PathBuf],
+    info_text: &str,
+    per_page: usize,
+    path: &std::path::Path,
+    dpi: u32,
+) -> Result<()> {
+    use printpdf::*;
+
+    let (card_width_px, card_height_px) = crate::qr::QRGenerator::card_pixel_dimensions(dpi);
+    let card_width_mm = card_width_px as f32 / dpi as f32 * 25.4;
+    let card_height_mm = card_height_px as f32 / dpi as f32 * 25.4;
+
+    let mut doc = PdfDocument::new("qrcrypt shares");
+    let mut pages = Vec::new();
+
+    for chunk in card_paths.chunks(per_page.max(1)) {
+        let mut ops = Vec::new();
+        let mut y = PAGE_HEIGHT_MM - MARGIN_MM - card_height_mm;
+        for card_path in chunk {
+            let card = image::open(card_path).map_err(|e| {
+                QRCryptError::QRGeneration(format!(
+                    "could not re-read {} for the PDF: {e}",
+                    card_path.display()
+                ))
+            })?;
+            let rgba = card.to_rgba8();
+            let (width, height) = rgba.dimensions();
+            let raw = RawImage {
+                pixels: RawImageData::U8(rgba.into_raw()),
+                width: width as usize,
+                height: height as usize,
+                data_format: RawImageFormat::RGBA8,
+                tag: Vec::new(),
+            };
+            let image_id = doc.add_image(&raw);
+            ops.push(Op::UseXobject {
+                id: image_id,
+                transform: XObjectTransform {
+                    translate_x: Some(Mm((PAGE_WIDTH_MM - card_width_mm) / 2.0).into()),
+                    translate_y: Some(Mm(y).into()),
+                    ..Default::default()
+                },
+            });
+            y -= card_height_mm + MARGIN_MM;
+        }
+        pages.push(PdfPage::new(Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), ops));
+    }
+
+    pages.extend(info_text_pages(info_text));
+
+    let mut warnings = Vec::new();
+    let bytes = doc
+        .with_pages(pages)
+        .save(&PdfSaveOptions::default(), &mut warnings);
+    std::fs::write(path, bytes)
+        .map_err(|e| QRCryptError::QRGeneration(format!("could not write {}: {e}", path.display())))
+}
+
+/// Lay `info_text` out as monospaced text, one `PdfPage` per screenful, so
+/// the reconstruction instructions travel inside the same PDF as the cards
+/// instead of only in the separate `info.txt`.
+#[cfg(feature = "pdf")]
+fn info_text_pages(info_text: &str) -> Vec<printpdf::PdfPage> {
+    use printpdf::*;
+
+    const FONT_SIZE: f32 = 10.0;
+    const LINE_HEIGHT: f32 = 13.0;
+    const MAX_LINE_CHARS: usize = 95;
+
+    let lines: Vec<String> = info_text
+        .lines()
+        .flat_map(|line| wrap_line(line, MAX_LINE_CHARS))
+        .collect();
+
+    let lines_per_page =
+        (((PAGE_HEIGHT_MM - 2.0 * MARGIN_MM) * 72.0 / 25.4) / LINE_HEIGHT) as usize;
+    let lines_per_page = lines_per_page.max(1);
+
+    lines
+        .chunks(lines_per_page)
+        .map(|chunk| {
+            let mut ops = vec![
+                Op::StartTextSection,
+                Op::SetTextCursor {
+                    pos: Point {
+                        x: Mm(MARGIN_MM).into(),
+                        y: Mm(PAGE_HEIGHT_MM - MARGIN_MM).into(),
+                    },
+                },
+                Op::SetLineHeight {
+                    lh: Pt(LINE_HEIGHT),
+                },
+                Op::SetFont {
+                    font: PdfFontHandle::Builtin(BuiltinFont::Courier),
+                    size: Pt(FONT_SIZE),
+                },
+            ];
+            for line in chunk {
+                ops.push(Op::ShowText {
+                    items: vec![TextItem::Text(line.clone())],
+                });
+                ops.push(Op::AddLineBreak);
+            }
+            ops.push(Op::EndTextSection);
+            PdfPage::new(Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), ops)
+        })
+        .collect()
+}
+
+/// Word-wrap `line` to at most `max_chars` per output line. A single word
+/// longer than `max_chars` is left as its own (overflowing) line rather than
+/// split mid-word, since info.txt lines are mostly share words/fingerprints
+/// that are only meaningful intact.
+#[cfg(feature = "pdf")]
+fn wrap_line(line: &str, max_chars: usize) -> Vec<String> {
+    if line.len() <= max_chars {
+        return vec![line.to_string()];
+    }
+    let mut wrapped = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        if !current.is_empty() && current.len() + 1 + word.len() > max_chars {
+            wrapped.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        wrapped.push(current);
+    }
+    wrapped
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn write_shares_pdf(
+    _card_paths: &[std::path::PathBuf],
+    _info_text: &str,
+    _per_page: usize,
+    _path: &std::path::Path,
+    _dpi: u32,
+) -> Result<()> {
+    Err(QRCryptError::QRGeneration(
+        "writing a PDF requires qrcrypt to be rebuilt with --features pdf".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "pdf")]
+    fn write_shares_pdf_embeds_every_card_and_the_instructions() {
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-write-shares-pdf-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut card_paths = Vec::new();
+        for i in 0..3 {
+            let path = dir.join(format!("card-{i}.png"));
+            crate::qr::QRGenerator::generate_qr(
+                &format!("share {i}"),
+                &path,
+                crate::qr::QrColors::default(),
+                qrcode::EcLevel::M,
+                crate::qr::Symbology::Qr,
+            )
+            .unwrap();
+            card_paths.push(path);
+        }
+
+        let pdf_path = dir.join("shares.pdf");
+        write_shares_pdf(
+            &card_paths,
+            "reconstruct with: qrcrypt reconstruct",
+            2,
+            &pdf_path,
+            crate::qr::DEFAULT_CARD_DPI,
+        )
+        .unwrap();
+
+        let bytes = std::fs::read(&pdf_path).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+        assert!(bytes.len() > 1000);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(not(feature = "pdf"))]
+    fn write_shares_pdf_reports_the_missing_feature() {
+        let err = write_shares_pdf(&[], "", 1, std::path::Path::new("shares.pdf"), 300)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--features pdf"), "unexpected error: {err}");
+    }
+}
@@ -0,0 +1,5047 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use image::imageops::{resize, FilterType};
+use image::{DynamicImage, ImageBuffer, Luma, Rgba, RgbaImage};
+use imageproc::drawing::{draw_filled_circle_mut, draw_filled_rect_mut, draw_text_mut};
+use imageproc::rect::Rect;
+use qrcode::{EcLevel, QrCode, Version};
+use rand::RngCore;
+use rand_core::OsRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::{EncryptedData, KdfParams};
+use crate::error::{QRCryptError, Result};
+use crate::shamir::{ParityShare, ShamirShare, ShareVerificationInfo};
+
+/// The JSON envelope stored inside a generated QR code. Tagged so a scanner
+/// can tell an encrypted secret apart from a Shamir share without trying to
+/// parse both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum QRData {
+    #[serde(rename = "encrypted")]
+    Encrypted(EncryptedData),
+    #[serde(rename = "shamir_share")]
+    ShamirShare(ShamirShare),
+    /// A single SLIP-39 share mnemonic. The mnemonic carries its own
+    /// metadata and checksum, so unlike `ShamirShare` there's no separate
+    /// struct to wrap it in.
+    #[serde(rename = "slip39_share")]
+    Slip39Share(String),
+    #[serde(rename = "file_part")]
+    FilePart(FilePart),
+    /// An outer erasure-coding symbol from `split --parity`, letting a lost
+    /// `ShamirShare` be rebuilt without touching the secret.
+    #[serde(rename = "parity_share")]
+    ParityShare(ParityShare),
+    /// One QR-sized slice of another, already-encoded `QRData` payload that
+    /// didn't fit a single code -- unlike `FilePart`, which only ever wraps
+    /// file ciphertext, this wraps the encoded payload itself, so it applies
+    /// to any oversized `encrypt`/`split` output. See `save_payload_auto`.
+    #[serde(rename = "payload_part")]
+    PayloadPart(PayloadPart),
+    /// One frame of an `encrypt --animated` fountain-coded GIF. See
+    /// `crate::ur` for the encoding and `QRScanner::scan_animated` for
+    /// reassembly.
+    #[serde(rename = "fountain_frame")]
+    FountainFrame(crate::ur::FountainFrame),
+    /// A detached Ed25519 signature over `encrypt`'s main payload, from
+    /// `encrypt --sign-key`. Scanned by `verify` alongside the payload QR
+    /// itself, never embedded in it -- see `crate::signing::PayloadSignature`.
+    #[serde(rename = "payload_signature")]
+    PayloadSignature(crate::signing::PayloadSignature),
+    /// Public, non-secret split metadata from `split --with-verify`,
+    /// carried on its own QR code alongside (never inside) a share's --
+    /// see `crate::shamir::ShareVerificationInfo`.
+    #[serde(rename = "share_verification")]
+    ShareVerification(ShareVerificationInfo),
+}
+
+/// One QR-sized slice of a file encrypted by `encrypt-file`. `salt`, `nonce`
+/// and `kdf` describe the single (non-layered) encryption used for the
+/// whole file and are duplicated on every part, so any one part carries
+/// enough to start decrypting once the rest arrive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilePart {
+    pub index: u32,
+    pub total: u32,
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub kdf: KdfParams,
+    #[serde(with = "part_data_encoding")]
+    pub data: Vec<u8>,
+}
+
+/// (De)serializes `FilePart::data` as base64 instead of a JSON array of
+/// numbers, for the same reason `shamir::ShamirShare::data` does: a file
+/// part can be a few hundred bytes, and base64 is far more compact than a
+/// JSON number array once per-element overhead is accounted for.
+mod part_data_encoding {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(&encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// One QR-sized slice of an encoded `QRData` payload too large for a single
+/// code. `payload_id` is a random fingerprint shared by every part of one
+/// split (mirroring `ShamirShare::set_id`), so `resolve_payload_parts` can
+/// tell which sibling files belong together even if a directory holds more
+/// than one split payload. `checksum` is the whole reassembled payload's
+/// checksum, duplicated on every part so a truncated or corrupted part is
+/// caught even before all of them have arrived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadPart {
+    pub payload_id: u32,
+    pub index: u32,
+    pub total: u32,
+    pub checksum: u32,
+    #[serde(with = "part_data_encoding")]
+    pub data: Vec<u8>,
+}
+
+/// A truncated-SHA-256 checksum of `data`: the first 4 bytes of the digest,
+/// as a big-endian `u32`. Mirrors `shamir::checksum_of`.
+fn checksum_of(data: &[u8]) -> u32 {
+    let digest = Sha256::digest(data);
+    u32::from_be_bytes(digest[..4].try_into().expect("digest is at least 4 bytes"))
+}
+
+/// `generate_with_logo`'s `max_fraction` default, and what `--logo` uses
+/// unless overridden by `--logo-max-fraction`. `EcLevel::H` can in
+/// principle recover ~30% of a code's modules, but a solid block in the
+/// middle stresses a scanner's module-sampling grid far more than
+/// scattered bit errors do, so this is kept under that theoretical
+/// ceiling to stay reliably scannable.
+pub const DEFAULT_LOGO_MAX_FRACTION: f64 = 0.2;
+
+/// `--dpi`'s default, and what every card-generating caller without a DPI
+/// flag of its own (e.g. tests) passes.
+pub const DEFAULT_CARD_DPI: u32 = 300;
+const CARD_WIDTH_CM: f32 = 8.5;
+const CARD_HEIGHT_CM: f32 = 5.5;
+
+/// `--min-module-mm`'s default for `generate_qr_physical_size`: below this,
+/// a printed module is unlikely to survive a typical phone camera's
+/// autofocus or a printer's dot gain.
+pub const DEFAULT_MIN_MODULE_MM: f32 = 0.33;
+
+/// The QR spec's own ceiling -- version 40 is the largest code it defines.
+const MAX_QR_VERSION: i16 = 40;
+
+/// `--max-qr-version`'s default: no cap beyond the spec's own ceiling.
+/// Cheap handheld scanners that choke on dense codes should pass a lower
+/// value explicitly; this default preserves the pre-existing behavior of
+/// using whatever version a payload needs, up to version 40.
+pub const DEFAULT_MAX_QR_VERSION: i16 = MAX_QR_VERSION;
+
+/// Ciphertext bytes per `FilePart`, picked comfortably under a QR code's
+/// practical scanning limit once base64 and the surrounding JSON envelope
+/// are accounted for.
+const FILE_PART_CHUNK_BYTES: usize = 500;
+
+/// Payload bytes per `PayloadPart`, picked comfortably under a QR code's
+/// practical scanning limit once the `PayloadPart` JSON envelope (plus the
+/// inner payload's own base64) is accounted for. Smaller than
+/// `FILE_PART_CHUNK_BYTES` because a `PayloadPart` also base64-encodes an
+/// already-base64-ish inner payload, roughly doubling per-byte overhead.
+const PAYLOAD_PART_CHUNK_BYTES: usize = 250;
+
+/// Hard ceiling on how much text `QRScanner::parse_qr_data` will attempt to
+/// parse. A version-40 QR code tops out at a few KB, so this is far above
+/// anything a real scan or raw-payload file could hold; it exists only to
+/// reject an absurdly large paste or mis-scanned blob before it reaches
+/// `serde_json`/`ciborium`, not to constrain real payloads.
+const MAX_PARSE_INPUT_BYTES: usize = 1 << 20;
+
+/// Page size `QRGenerator::compose_sheet` lays share cards out onto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperSize {
+    A4,
+    Letter,
+}
+
+impl PaperSize {
+    fn dimensions_mm(self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+fn cm_to_px(cm: f32, dpi: u32) -> u32 {
+    (cm / 2.54 * dpi as f32) as u32
+}
+
+/// Result of `QRGenerator::estimate_capacity`: whether a payload of the
+/// given length fits in a single QR code at the error correction level
+/// `generate_qr`/`generate_card_qr` use, and if so the smallest QR version
+/// (1-40) and resulting module count (modules per side) that would hold it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityInfo {
+    pub fits: bool,
+    pub version: Option<i16>,
+    pub modules: Option<i16>,
+}
+
+/// Foreground/background color pair for rendered QR codes and cards, plus
+/// the card path's quiet zone width. Defaults to pure black on white with a
+/// standard 4-module border, matching QR codes before this existed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QrColors {
+    pub fg: Rgba<u8>,
+    pub bg: Rgba<u8>,
+    /// Quiet zone width around the card's QR code, in QR modules (not
+    /// pixels), so it scales with however large `generate_card_qr` ends up
+    /// rendering each module. Only used by the card path; `generate_qr`'s
+    /// plain QR keeps the `qrcode` crate's own built-in quiet zone.
+    pub border: u32,
+    /// How to draw each dark module. `Square` (the default) matches QR
+    /// codes before this existed; `Dot`/`Rounded` are for engraving onto
+    /// metal, where square corners blur together under a fiber laser.
+    pub module_style: ModuleStyle,
+    /// Fraction (0 exclusive, 1 inclusive) of a module's pitch that
+    /// `Dot`/`Rounded` modules actually draw, leaving the rest as
+    /// background -- smaller values mean more daylight between marks.
+    /// Ignored by `Square`, which always fills the whole module.
+    pub fill_ratio: f32,
+}
+
+/// How `QrColors` draws a dark module. `Dot` and `Rounded` exist for fiber
+/// laser engraving shops, who asked for "rounded/dot modules with 80%
+/// fill" since square modules with sharp corners blur together when
+/// etched into metal. Purely cosmetic: the underlying QR data and error
+/// correction don't care what shape a module is drawn as, so a scanner
+/// reads any style exactly the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModuleStyle {
+    #[default]
+    Square,
+    Dot,
+    Rounded,
+}
+
+/// Which 2D barcode symbology a generated code uses. `Qr` (the default) is
+/// what every caller used before this existed; `DataMatrix` trades QR's
+/// dot/rounded module styles and logo overlay for a smaller quiet zone and
+/// denser packing, which is what industrial engravers expect for tiny metal
+/// tags. See `crate::datamatrix` for the encoder/decoder this dispatches to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Symbology {
+    #[default]
+    Qr,
+    DataMatrix,
+}
+
+/// `QrColors::border`'s default, and what every non-card caller of
+/// `resolve_qr_colors` passes since they have no `--border` flag of their
+/// own.
+pub const DEFAULT_BORDER_MODULES: u32 = 4;
+
+impl Default for QrColors {
+    fn default() -> Self {
+        QrColors {
+            fg: Rgba([0, 0, 0, 255]),
+            bg: Rgba([255, 255, 255, 255]),
+            border: DEFAULT_BORDER_MODULES,
+            module_style: ModuleStyle::default(),
+            fill_ratio: 1.0,
+        }
+    }
+}
+
+/// Minimum ITU-R BT.601 luminance difference (0-255 scale) `QrColors`
+/// requires between `fg` and `bg`. Below this, a scanner can mistake one
+/// module color for the other under less-than-ideal lighting.
+const MIN_LUMINANCE_CONTRAST: i32 = 64;
+
+/// Smallest `QrColors::fill_ratio` `Dot`/`Rounded` modules are allowed to
+/// shrink to. Below this, a module's mark covers too little of its pitch
+/// for a scanner to reliably tell it apart from the quiet background.
+const MIN_FILL_RATIO: f32 = 0.2;
+
+/// `qrcode`'s own quiet zone around a rendered code: 4 modules on each
+/// side of the core matrix (2 for micro codes, which this crate never
+/// produces); see `QrCode::render`.
+const QUIET_ZONE_MARGIN_MODULES: u32 = 4;
+
+impl QrColors {
+    /// Reject a foreground/background pair whose luminance is too close to
+    /// scan reliably, or a `fill_ratio` outside `(MIN_FILL_RATIO, 1.0]`.
+    pub fn validate(&self) -> Result<()> {
+        fn luminance(c: Rgba<u8>) -> i32 {
+            let [r, g, b, _] = c.0;
+            (299 * r as i32 + 587 * g as i32 + 114 * b as i32) / 1000
+        }
+        let contrast = (luminance(self.fg) - luminance(self.bg)).abs();
+        if contrast < MIN_LUMINANCE_CONTRAST {
+            return Err(QRCryptError::QRGeneration(format!(
+                "foreground/background colors are too close in luminance ({contrast}/255, need \
+                 at least {MIN_LUMINANCE_CONTRAST}) to scan reliably"
+            )));
+        }
+        if self.fill_ratio > 1.0 || self.fill_ratio < MIN_FILL_RATIO {
+            return Err(QRCryptError::QRGeneration(format!(
+                "--fill-ratio {:.2} is out of range; it needs to be between {MIN_FILL_RATIO:.1} \
+                 and 1.0 to stay scannable",
+                self.fill_ratio
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Seconds since the Unix epoch, for the "Created" line in an info file and
+/// for `EncryptedData::created_at` (see `Crypto::encrypt_with_kdf`). Kept as
+/// a plain integer rather than a formatted date since qrcrypt doesn't
+/// otherwise depend on a calendar/timezone crate.
+pub fn unix_timestamp_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The `tEXt` chunk keyword `save_qr_png` writes `PngMetadata` under, and
+/// `read_png_metadata` looks for when reading one back. Namespaced so it
+/// can't collide with a keyword some other tool already wrote.
+const PNG_METADATA_KEYWORD: &str = "qrcrypt:meta";
+
+/// `PngMetadata::format_version`'s current value. Bumped whenever the
+/// shape of `PngMetadata` itself changes, independent of the wire format's
+/// own `COMPACT_WIRE_MAGIC`/CBOR versioning.
+const PNG_METADATA_FORMAT_VERSION: u32 = 1;
+
+/// Embedded in every QR PNG's `qrcrypt:meta` tEXt chunk, so `inspect` can
+/// report what a file is without decoding the QR visually. Deliberately
+/// carries a fingerprint of the payload, never the payload itself -- this
+/// metadata is meant to survive sitting in a PNG viewer's "info" panel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PngMetadata {
+    pub data_type: String,
+    pub format_version: u32,
+    pub created: u64,
+    pub payload_fingerprint: String,
+}
+
+/// The `QRData` variant's wire tag (`"encrypted"`, `"shamir_share"`, ...),
+/// reusing the same strings `#[serde(tag = "type")]` already gives each
+/// variant on the wire instead of inventing a second label for the same
+/// thing.
+fn qr_data_type_label(data: &QRData) -> &'static str {
+    match data {
+        QRData::Encrypted(_) => "encrypted",
+        QRData::ShamirShare(_) => "shamir_share",
+        QRData::Slip39Share(_) => "slip39_share",
+        QRData::FilePart(_) => "file_part",
+        QRData::ParityShare(_) => "parity_share",
+        QRData::PayloadPart(_) => "payload_part",
+        QRData::FountainFrame(_) => "fountain_frame",
+        QRData::PayloadSignature(_) => "payload_signature",
+        QRData::ShareVerification(_) => "share_verification",
+    }
+}
+
+/// Which `QRData` variant a scan is looking for, for a caller that expects a
+/// specific kind of code among several on the same photo -- e.g. reading a
+/// `split --with-verify` card, which carries a share QR and a
+/// `ShareVerification` QR side by side. See `QRScanner::scan_from_image_as`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QRDataType {
+    Encrypted,
+    ShamirShare,
+    Slip39Share,
+    FilePart,
+    ParityShare,
+    PayloadPart,
+    FountainFrame,
+    PayloadSignature,
+    ShareVerification,
+}
+
+impl QRDataType {
+    fn matches(&self, data: &QRData) -> bool {
+        matches!(
+            (self, data),
+            (QRDataType::Encrypted, QRData::Encrypted(_))
+                | (QRDataType::ShamirShare, QRData::ShamirShare(_))
+                | (QRDataType::Slip39Share, QRData::Slip39Share(_))
+                | (QRDataType::FilePart, QRData::FilePart(_))
+                | (QRDataType::ParityShare, QRData::ParityShare(_))
+                | (QRDataType::PayloadPart, QRData::PayloadPart(_))
+                | (QRDataType::FountainFrame, QRData::FountainFrame(_))
+                | (QRDataType::PayloadSignature, QRData::PayloadSignature(_))
+                | (QRDataType::ShareVerification, QRData::ShareVerification(_))
+        )
+    }
+}
+
+/// Marks a QR payload as the compact wire format below rather than legacy
+/// JSON: these are the first two bytes, before Base45 encoding, of every
+/// payload `encode_payload` writes. Chosen so they (and anything CBOR-framed
+/// after them) only ever decode to characters outside Base45's alphabet when
+/// mistakenly read back as legacy JSON text, and vice versa -- JSON's
+/// lowercase keys and punctuation aren't valid Base45 characters, so the two
+/// formats can never be confused for each other.
+const COMPACT_WIRE_MAGIC: [u8; 2] = [0xD9, 0xF9];
+
+/// A QR code sized to fill a box up to some `max_width`x`max_height` pixels,
+/// ready for `QRGenerator::draw_fitted_qr` to draw at a chosen top-left
+/// corner -- computed separately from drawing so a caller
+/// (`generate_card_qr`'s single-QR layout, `generate_card_qr_with_verify`'s
+/// two-QR layout) can learn `final_size` first and use it to center the
+/// code before any pixels are touched.
+struct FittedQr {
+    code: QrCode,
+    qr_image: ImageBuffer<Luma<u8>, Vec<u8>>,
+    colors: QrColors,
+    scale: f32,
+    final_size: u32,
+}
+
+/// Every file `save_one_shamir_card` wrote for one share: its card (the
+/// path `save_shamir_card_qrs` reports back) plus whichever of
+/// `--card-back`'s back card and the word-encoded twin were also written,
+/// kept around purely so a later share's failure can delete this share's
+/// files too.
+struct ShareCardFiles {
+    card: PathBuf,
+    extra: Vec<PathBuf>,
+}
+
+/// Renders encrypted payloads and Shamir shares as QR code images, either
+/// as bare codes or as printable "cards" with a title and caption.
+pub struct QRGenerator;
+
+impl QRGenerator {
+    /// Serialize a `QRData` envelope to the compact wire string embedded in
+    /// the QR code: `COMPACT_WIRE_MAGIC` followed by CBOR, Base45-encoded so
+    /// the whole thing stays in the QR alphanumeric character set (Base45's
+    /// 45-character alphabet is exactly QR alphanumeric mode's). This is far
+    /// smaller than the legacy pretty-JSON format it replaces -- no field
+    /// names, no base64-inside-JSON, and a denser QR encoding mode to boot.
+    /// `QRScanner::parse_qr_data` still reads old JSON QRs made before this
+    /// existed.
+    pub fn encode_payload(data: &QRData) -> Result<String> {
+        let mut framed = COMPACT_WIRE_MAGIC.to_vec();
+        ciborium::into_writer(data, &mut framed)
+            .map_err(|e| QRCryptError::Serialization(e.to_string()))?;
+        Ok(base45::encode(framed))
+    }
+
+    /// Serialize `data` as plain JSON with no `QRData` envelope and no
+    /// compact CBOR framing, for `encrypt --raw-payload`: other tools and
+    /// hand-rolled scripts that read `EncryptedData`'s fields straight off
+    /// the wire, with no "type" tag or Base45/CBOR layer to strip first.
+    /// `QRScanner::parse_qr_data` reads this back via `parse_raw_payload`.
+    pub fn encode_raw_payload(data: &EncryptedData) -> Result<String> {
+        serde_json::to_string(data).map_err(|e| QRCryptError::Serialization(e.to_string()))
+    }
+
+    /// Render `payload` as a plain QR code at `ec` into an in-memory
+    /// `RgbaImage` in `colors.fg`/`colors.bg`. Shared by `generate_qr`
+    /// (which saves it as a PNG) and `save_animated_qr` (which collects one
+    /// per frame into a GIF instead).
+    ///
+    /// Goes straight through the `qrcode` crate's own `render::<Luma<u8>>()`
+    /// pixel renderer -- there's no char-string intermediate to remove here.
+    fn render_qr_image(payload: &str, colors: QrColors, ec: EcLevel) -> Result<RgbaImage> {
+        colors.validate()?;
+        Self::require_capacity(payload.len(), ec)?;
+        let code = QrCode::with_error_correction_level(payload.as_bytes(), ec)
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+        // `qrcode`'s own default module size when `module_dimensions` is
+        // never called; see `Pixel::default_unit_size`.
+        const DEFAULT_PX_PER_MODULE: u32 = 8;
+        let modules: ImageBuffer<Luma<u8>, Vec<u8>> = code.render::<Luma<u8>>().build();
+        Ok(Self::paint_modules(
+            &modules,
+            DEFAULT_PX_PER_MODULE,
+            colors,
+            &code,
+            QUIET_ZONE_MARGIN_MODULES,
+        ))
+    }
+
+    /// Render `payload` through `symbology`'s encoder, in `colors.fg`/
+    /// `colors.bg`. `ec` only affects the QR path -- DataMatrix has its own
+    /// fixed error correction (ECC 200) the `datamatrix` crate always
+    /// applies. `colors.module_style`/`fill_ratio` are QR-only cosmetics
+    /// (see `ModuleStyle`); `generate_qr`/`generate_captioned_qr` reject
+    /// combining them with `Symbology::DataMatrix` before this is reached.
+    fn render_payload_image(
+        payload: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        symbology: Symbology,
+    ) -> Result<RgbaImage> {
+        match symbology {
+            Symbology::Qr => Self::render_qr_image(payload, colors, ec),
+            Symbology::DataMatrix => {
+                // `qrcode`'s own default module size; matched here so a QR
+                // and a DataMatrix rendering of similarly sized payloads end
+                // up comparable in physical size.
+                const DEFAULT_PX_PER_MODULE: u32 = 8;
+                crate::datamatrix::render_image(
+                    payload.as_bytes(),
+                    DEFAULT_PX_PER_MODULE,
+                    colors.fg,
+                    colors.bg,
+                )
+            }
+        }
+    }
+
+    /// Reject `--module-style`/`--fill-ratio` combined with
+    /// `Symbology::DataMatrix`: those are QR-specific cosmetics (see
+    /// `ModuleStyle`) that DataMatrix's fixed ECC 200 layout has no
+    /// equivalent for.
+    fn require_qr_only_colors_for(symbology: Symbology, colors: QrColors) -> Result<()> {
+        if symbology == Symbology::DataMatrix
+            && (colors.module_style != ModuleStyle::Square || colors.fill_ratio != 1.0)
+        {
+            return Err(QRCryptError::QRGeneration(
+                "--module-style and --fill-ratio only apply to QR codes; they have no effect \
+                 with --symbology datamatrix"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether module `(mx, my)` -- in the full rendered grid, including
+    /// `margin` modules of quiet zone on each side -- is one of `code`'s
+    /// function patterns (finder patterns and their separators, timing
+    /// patterns, format/version info) rather than a data/error-correction
+    /// module. `paint_modules` always draws these as solid squares
+    /// regardless of `ModuleStyle`: a scanner's position-detection and
+    /// synchronization steps expect these as the solid, continuous shapes
+    /// the spec defines, and breaking them into a ring of dots or rounded
+    /// marks makes the whole code undetectable even though every data
+    /// module still round-trips fine on its own.
+    fn is_function_pattern(code: &QrCode, mx: u32, my: u32, margin: u32) -> bool {
+        let core_width = code.width() as u32;
+        if mx < margin || my < margin {
+            return false;
+        }
+        let (x, y) = (mx - margin, my - margin);
+        if x >= core_width || y >= core_width {
+            return false;
+        }
+        code.is_functional(x as usize, y as usize)
+    }
+
+    /// Paint `modules` (a `qrcode`-rendered dark/light buffer at
+    /// `px_per_module` pixels per module, `margin` modules of quiet zone
+    /// on each side around `code`'s core matrix) into an `RgbaImage`, in
+    /// `colors.fg`/`colors.bg`. `ModuleStyle::Square` just copies every
+    /// pixel across, identical to drawing the `qrcode` crate's own output
+    /// directly. `Dot`/`Rounded` instead draw one shape per dark data
+    /// module, sized to `colors.fill_ratio`, onto an otherwise
+    /// all-background image -- except inside `is_function_pattern`, which
+    /// always stays a solid square; see its doc comment for why.
+    fn paint_modules(
+        modules: &ImageBuffer<Luma<u8>, Vec<u8>>,
+        px_per_module: u32,
+        colors: QrColors,
+        code: &QrCode,
+        margin: u32,
+    ) -> RgbaImage {
+        if colors.module_style == ModuleStyle::Square {
+            let mut image: RgbaImage = ImageBuffer::new(modules.width(), modules.height());
+            for (x, y, module) in modules.enumerate_pixels() {
+                let color = if module.0[0] == 0 {
+                    colors.fg
+                } else {
+                    colors.bg
+                };
+                image.put_pixel(x, y, color);
+            }
+            return image;
+        }
+
+        let mut image: RgbaImage =
+            ImageBuffer::from_pixel(modules.width(), modules.height(), colors.bg);
+        let side = (px_per_module as f32 * colors.fill_ratio).max(1.0);
+        let module_count_x = modules.width() / px_per_module;
+        let module_count_y = modules.height() / px_per_module;
+        for my in 0..module_count_y {
+            for mx in 0..module_count_x {
+                let module = modules.get_pixel(mx * px_per_module, my * px_per_module);
+                if module.0[0] != 0 {
+                    continue;
+                }
+                let x0 = (mx * px_per_module) as i32;
+                let y0 = (my * px_per_module) as i32;
+                if Self::is_function_pattern(code, mx, my, margin) {
+                    draw_filled_rect_mut(
+                        &mut image,
+                        Rect::at(x0, y0).of_size(px_per_module, px_per_module),
+                        colors.fg,
+                    );
+                    continue;
+                }
+                let center_x = x0 + px_per_module as i32 / 2;
+                let center_y = y0 + px_per_module as i32 / 2;
+                match colors.module_style {
+                    ModuleStyle::Dot => {
+                        let radius = ((side / 2.0).round() as i32).max(1);
+                        draw_filled_circle_mut(&mut image, (center_x, center_y), radius, colors.fg);
+                    }
+                    ModuleStyle::Rounded => {
+                        Self::draw_rounded_module(&mut image, center_x, center_y, side, colors.fg);
+                    }
+                    ModuleStyle::Square => unreachable!("handled above"),
+                }
+            }
+        }
+        image
+    }
+
+    /// Draw a square of side `side` centered at `(center_x, center_y)` with
+    /// its corners rounded off, by unioning a cross of two overlapping
+    /// rectangles with a filled circle at each corner -- `imageproc` has no
+    /// rounded-rect primitive of its own to reach for here.
+    fn draw_rounded_module(image: &mut RgbaImage, center_x: i32, center_y: i32, side: f32, color: Rgba<u8>) {
+        let half = (side / 2.0).round() as i32;
+        let radius = ((side * 0.25).round() as i32).max(1).min(half.max(1));
+        let full = (2 * half).max(1) as u32;
+        let inset = (2 * (half - radius)).max(1) as u32;
+
+        draw_filled_rect_mut(
+            image,
+            Rect::at(center_x - half, center_y - radius).of_size(full, (2 * radius).max(1) as u32),
+            color,
+        );
+        draw_filled_rect_mut(
+            image,
+            Rect::at(center_x - half + radius, center_y - half).of_size(inset, full),
+            color,
+        );
+        for (dx, dy) in [(-1, -1), (1, -1), (-1, 1), (1, 1)] {
+            let cx = center_x + dx * (half - radius);
+            let cy = center_y + dy * (half - radius);
+            draw_filled_circle_mut(image, (cx, cy), radius, color);
+        }
+    }
+
+    /// Render `payload` as a QR PNG at `path`, at `ec` (`EcLevel::M` unless
+    /// the caller overrides it, e.g. via `--error-correction`), in
+    /// `colors.fg`/`colors.bg` (plain black-and-white by default). Pass
+    /// `Symbology::DataMatrix` to render a DataMatrix symbol instead of a
+    /// QR code (see `crate::datamatrix`); `ec` is then ignored.
+    pub fn generate_qr(
+        payload: &str,
+        path: &Path,
+        colors: QrColors,
+        ec: EcLevel,
+        symbology: Symbology,
+    ) -> Result<()> {
+        Self::require_qr_only_colors_for(symbology, colors)?;
+        let image = Self::render_payload_image(payload, colors, ec, symbology)?;
+        Self::save_qr_png(&image, path, None, payload)
+    }
+
+    /// Render `payload` as a QR PNG at `path`, sized to `size_mm`
+    /// millimetres per side at `dpi` (mutually exclusive with a raw pixel
+    /// scale, since the two ways of asking "how big" would otherwise
+    /// disagree), with `dpi` embedded in the PNG's pHYs chunk so a print
+    /// driver or image viewer that honors physical size renders it at true
+    /// size rather than filling the page. Errors instead of silently
+    /// producing an unscannable code if the resulting module size would
+    /// fall below `min_module_mm` (`DEFAULT_MIN_MODULE_MM` unless the
+    /// caller overrides it via `--min-module-mm`).
+    pub fn generate_qr_physical_size(
+        payload: &str,
+        path: &Path,
+        colors: QrColors,
+        ec: EcLevel,
+        size_mm: f32,
+        dpi: u32,
+        min_module_mm: f32,
+    ) -> Result<()> {
+        colors.validate()?;
+        Self::require_capacity(payload.len(), ec)?;
+        let code = QrCode::with_error_correction_level(payload.as_bytes(), ec)
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+
+        let modules_with_quiet_zone = code.width() as u32 + 2 * QUIET_ZONE_MARGIN_MODULES;
+        let target_px = (size_mm / 25.4 * dpi as f32).round().max(1.0) as u32;
+        let module_px = (target_px / modules_with_quiet_zone).max(1);
+        let module_mm = module_px as f32 / dpi as f32 * 25.4;
+        if module_mm < min_module_mm {
+            return Err(QRCryptError::QRGeneration(format!(
+                "at {size_mm:.1}mm/{dpi} DPI each module would be {module_mm:.2}mm, below the \
+                 {min_module_mm:.2}mm minimum most scanners can read reliably; use a larger \
+                 --size-mm, a lower error correction level, or a shorter payload"
+            )));
+        }
+
+        let modules: ImageBuffer<Luma<u8>, Vec<u8>> = code
+            .render::<Luma<u8>>()
+            .module_dimensions(module_px, module_px)
+            .build();
+        let image = Self::paint_modules(&modules, module_px, colors, &code, QUIET_ZONE_MARGIN_MODULES);
+
+        Self::save_qr_png(&image, path, Some(dpi), payload)
+    }
+
+    /// Save `image` (a rendered QR code for `payload`) as a PNG at `path`,
+    /// embedding a `qrcrypt:meta` tEXt chunk -- see `PngMetadata` -- and,
+    /// if `dpi` is given, a pHYs chunk the way `generate_qr_physical_size`
+    /// needs. `image::save` doesn't expose either, so this goes through
+    /// the `png` crate (already an `image` dependency) directly.
+    fn save_qr_png(image: &RgbaImage, path: &Path, dpi: Option<u32>, payload: &str) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(QRCryptError::Io)?;
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), image.width(), image.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        if let Some(dpi) = dpi {
+            let pixels_per_meter = (dpi as f64 / 0.0254).round() as u32;
+            encoder.set_pixel_dims(Some(png::PixelDimensions {
+                xppu: pixels_per_meter,
+                yppu: pixels_per_meter,
+                unit: png::Unit::Meter,
+            }));
+        }
+
+        let data_type = QRScanner::parse_qr_data(payload)
+            .map(|data| qr_data_type_label(&data).to_string())
+            .unwrap_or_else(|_| "unknown".to_string());
+        let metadata = PngMetadata {
+            data_type,
+            format_version: PNG_METADATA_FORMAT_VERSION,
+            created: unix_timestamp_now(),
+            payload_fingerprint: hex::encode(Sha256::digest(payload.as_bytes())),
+        };
+        let metadata_json = serde_json::to_string(&metadata)
+            .map_err(|e| QRCryptError::Serialization(e.to_string()))?;
+        encoder
+            .add_text_chunk(PNG_METADATA_KEYWORD.to_string(), metadata_json)
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+        writer
+            .write_image_data(image.as_raw())
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Read back the `qrcrypt:meta` tEXt chunk `save_qr_png` embeds, if
+    /// present. Returns `Ok(None)` rather than an error for a PNG that
+    /// simply never had one -- an older qrcrypt file, or an unrelated
+    /// screenshot someone pointed `inspect` at -- and for the rare case
+    /// its value isn't the JSON this version expects.
+    pub fn read_png_metadata(path: &Path) -> Result<Option<PngMetadata>> {
+        let file = std::fs::File::open(path).map_err(QRCryptError::Io)?;
+        let decoder = png::Decoder::new(std::io::BufReader::new(file));
+        let reader = decoder
+            .read_info()
+            .map_err(|e| QRCryptError::QRScan(e.to_string()))?;
+        let chunk = reader
+            .info()
+            .uncompressed_latin1_text
+            .iter()
+            .find(|chunk| chunk.keyword == PNG_METADATA_KEYWORD);
+        Ok(chunk.and_then(|chunk| serde_json::from_str(&chunk.text).ok()))
+    }
+
+    /// Render `payload` as a QR PNG at `path`, at `EcLevel::H` with `logo`
+    /// composited into a safe central area, in `colors.fg`/`colors.bg`.
+    /// `EcLevel::H` can recover roughly 30% of a code's modules, but finder
+    /// patterns, timing strips, and the quiet zone already eat into that
+    /// margin, so the logo is capped to `max_fraction` of the total modules
+    /// (e.g. `DEFAULT_LOGO_MAX_FRACTION`) and centered, well clear of the
+    /// functional patterns in the corners. Fails if even that capped area
+    /// would exceed the code's actual error-correction budget at its
+    /// version, or if the composited result fails a self-scan via
+    /// `QRScanner::scan_from_image`, rather than silently saving a QR code
+    /// that scanners can't reliably read.
+    pub fn generate_with_logo(
+        payload: &str,
+        logo: &DynamicImage,
+        path: &Path,
+        colors: QrColors,
+        max_fraction: f64,
+    ) -> Result<()> {
+        colors.validate()?;
+        let code = QrCode::with_error_correction_level(payload.as_bytes(), EcLevel::H)
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+        let modules: ImageBuffer<Luma<u8>, Vec<u8>> = code.render::<Luma<u8>>().build();
+        let module_count = code.width() as u32;
+        let px_per_module = modules.width() / (module_count + 2 * QUIET_ZONE_MARGIN_MODULES);
+        let mut image = Self::paint_modules(&modules, px_per_module, colors, &code, QUIET_ZONE_MARGIN_MODULES);
+        let max_logo_modules = ((module_count as f64).powi(2) * max_fraction).sqrt() as u32;
+        let covered_modules = max_logo_modules * max_logo_modules;
+        // `max_allowed_errors()` counts correctable codeword bytes (8
+        // modules each), so this is its nominal module budget; it's only a
+        // cheap up-front sanity bound, not the final word -- the self-scan
+        // check below catches whatever this estimate gets wrong because
+        // Reed-Solomon interleaving scatters a codeword's modules across the
+        // grid instead of leaving a solid covered block aligned with them.
+        let error_budget_modules = (code.max_allowed_errors() * 8) as u32;
+        if covered_modules > error_budget_modules {
+            return Err(QRCryptError::QRGeneration(format!(
+                "a centered logo would cover about {covered_modules} modules, more than this \
+                 code's error-correction budget of roughly {error_budget_modules} modules at \
+                 EcLevel::H; use a smaller logo, a lower --logo-max-fraction, or a longer \
+                 payload to push the QR to a higher version"
+            )));
+        }
+
+        let logo_side = max_logo_modules * px_per_module;
+        let logo_rgba = logo.to_rgba8();
+        let resized = image::imageops::resize(
+            &logo_rgba,
+            logo_side,
+            logo_side,
+            image::imageops::FilterType::Lanczos3,
+        );
+        let offset_x = ((image.width() - logo_side) / 2) as i64;
+        let offset_y = ((image.height() - logo_side) / 2) as i64;
+        image::imageops::overlay(&mut image, &resized, offset_x, offset_y);
+
+        QRScanner::scan_from_image(&DynamicImage::ImageRgba8(image.clone())).map_err(|e| {
+            QRCryptError::QRGeneration(format!(
+                "logo overlay made the QR code undecodable in a self-scan check ({e}); use a \
+                 smaller logo, a lower --logo-max-fraction, or a longer payload to push the QR \
+                 to a higher version"
+            ))
+        })?;
+
+        Self::save_qr_png(&image, path, None, payload)
+    }
+
+    /// Check whether a payload of `data_len` bytes would fit in a single QR
+    /// code at `ec`, without spending the time to build and render one.
+    /// Lets `handle_encrypt` warn before doing the (slow) KDF work that a
+    /// ciphertext's encoded payload won't scan, rather than after.
+    pub fn estimate_capacity(data_len: usize, ec: EcLevel) -> CapacityInfo {
+        Self::estimate_capacity_capped(data_len, ec, MAX_QR_VERSION)
+    }
+
+    /// Like `estimate_capacity`, but never considers a version above
+    /// `max_version` -- used by `--max-qr-version` to keep generated codes
+    /// readable by cheap handheld scanners that choke on the denser versions
+    /// near the top of the spec's range.
+    pub fn estimate_capacity_capped(
+        data_len: usize,
+        ec: EcLevel,
+        max_version: i16,
+    ) -> CapacityInfo {
+        // A lowercase byte forces byte-mode encoding (the mode our real
+        // JSON payloads use), rather than the denser numeric/alphanumeric
+        // modes the encoder would pick for an all-digit probe.
+        let probe = vec![b'x'; data_len];
+        for v in 1..=max_version.clamp(1, MAX_QR_VERSION) {
+            if QrCode::with_version(&probe, Version::Normal(v), ec).is_ok() {
+                return CapacityInfo {
+                    fits: true,
+                    version: Some(v),
+                    modules: Some(Version::Normal(v).width()),
+                };
+            }
+        }
+        CapacityInfo {
+            fits: false,
+            version: None,
+            modules: None,
+        }
+    }
+
+    /// The most payload bytes a single QR code can hold at `ec`, found by
+    /// binary-searching `estimate_capacity` rather than hand-maintaining the
+    /// spec's per-level version-40 byte-mode table.
+    fn max_capacity_bytes(ec: EcLevel) -> usize {
+        let mut lo = 0usize;
+        let mut hi = 4096usize; // comfortably above version 40's largest byte-mode capacity
+        while lo < hi {
+            let mid = lo + (hi - lo).div_ceil(2);
+            if Self::estimate_capacity(mid, ec).fits {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        lo
+    }
+
+    /// Check that `payload_len` bytes fit a single QR code at `ec`, and if
+    /// not, build an error naming the payload size, `ec`'s max capacity, and
+    /// which (if any) other levels would fit -- so a user who asked for
+    /// `--error-correction h` on a large payload gets told to drop to `m`
+    /// instead of a bare "doesn't fit".
+    fn require_capacity(payload_len: usize, ec: EcLevel) -> Result<()> {
+        if Self::estimate_capacity(payload_len, ec).fits {
+            return Ok(());
+        }
+        let fitting: Vec<String> = [EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H]
+            .into_iter()
+            .filter(|&level| level != ec && Self::estimate_capacity(payload_len, level).fits)
+            .map(|level| format!("{level:?}"))
+            .collect();
+        let advice = if fitting.is_empty() {
+            "no error correction level can fit this payload in a single QR code".to_string()
+        } else {
+            format!("it would fit at: {}", fitting.join(", "))
+        };
+        Err(QRCryptError::QRGeneration(format!(
+            "payload is {payload_len} bytes, which exceeds the {}-byte capacity of a single QR \
+             code at EcLevel::{ec:?}; {advice}",
+            Self::max_capacity_bytes(ec)
+        )))
+    }
+
+    /// DejaVu Sans Bold, embedded so card text renders the same way on every
+    /// platform instead of depending on a system font being installed --
+    /// a minimal Tails or Alpine install has no system fonts at all, which
+    /// used to mean a card with no title or caption text, silently.
+    const EMBEDDED_FONT_BYTES: &'static [u8] =
+        include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+    /// Load the font `add_text_to_card` draws with: `font_override` if given
+    /// (e.g. `--font`), otherwise the embedded default. A bad `--font` path
+    /// is reported with `print_warning` and falls back to the embedded font
+    /// rather than silently rendering a card with no text.
+    fn load_font(font_override: Option<&Path>) -> FontVec {
+        if let Some(path) = font_override {
+            match std::fs::read(path).map(FontVec::try_from_vec) {
+                Ok(Ok(font)) => return font,
+                Ok(Err(e)) => crate::utils::print_warning(&format!(
+                    "could not parse --font {}: {e}; using the built-in font instead",
+                    path.display()
+                )),
+                Err(e) => crate::utils::print_warning(&format!(
+                    "could not read --font {}: {e}; using the built-in font instead",
+                    path.display()
+                )),
+            }
+        }
+        FontVec::try_from_vec(Self::EMBEDDED_FONT_BYTES.to_vec())
+            .expect("the embedded font is a valid TTF")
+    }
+
+    /// The title `add_text_to_card` draws unless overridden by
+    /// `--card-title`.
+    const DEFAULT_CARD_TITLE: &'static str = "QRCrypt";
+
+    /// Left/right margin (in pixels) text is kept clear of on a card, so it
+    /// doesn't run into the card's edge.
+    const CARD_TEXT_MARGIN_PX: u32 = 40;
+
+    /// Floors for `shrink_text_scale`, below which text is illegible even if
+    /// it would still technically overflow the card at this size.
+    const MIN_TITLE_SCALE: f32 = 16.0;
+    const MIN_CAPTION_SCALE: f32 = 10.0;
+
+    /// Gap (in pixels) `generate_captioned_qr` leaves between a plain QR
+    /// code and the caption drawn beneath it, and between that caption and
+    /// the image's bottom edge. The QR code's own rendering already bakes
+    /// in its quiet zone, so this only needs to keep the caption clear of
+    /// it, not reproduce `colors.border` from the card path.
+    const PLAIN_CAPTION_MARGIN_PX: u32 = 12;
+
+    /// The vertical extent (in pixels, relative to the `y` that
+    /// `draw_text_mut` would be given) that rendering `text` at `scale`
+    /// actually paints into: `(min, max)`, both offsets from that `y`,
+    /// covering every glyph's real ink rather than the font's nominal
+    /// line height. `text_size` alone isn't enough here -- it reports a
+    /// glyph's bounding-box height, not where that box sits relative to the
+    /// baseline `draw_text_mut` positions it at, so it understates how far
+    /// descenders (and a line as a whole) actually reach below `y`.
+    fn text_vertical_extent(font: &FontVec, text: &str, scale: f32) -> (f32, f32) {
+        let scale = PxScale::from(scale);
+        let scaled_font = font.as_scaled(scale);
+        let mut x = 0.0;
+        let mut min_y = 0.0f32;
+        let mut max_y = 0.0f32;
+        let mut last = None;
+        for c in text.chars() {
+            let glyph_id = scaled_font.glyph_id(c);
+            if let Some(last) = last {
+                x += scaled_font.kern(last, glyph_id);
+            }
+            let glyph = glyph_id.with_scale_and_position(scale, point(x, scaled_font.ascent()));
+            x += scaled_font.h_advance(glyph_id);
+            last = Some(glyph_id);
+            if let Some(outlined) = scaled_font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                min_y = min_y.min(bounds.min.y);
+                max_y = max_y.max(bounds.max.y);
+            }
+        }
+        (min_y, max_y)
+    }
+
+    /// The largest scale no bigger than `max_scale` at which `text` is no
+    /// wider than `max_width` and no taller (top of ink to bottom of ink,
+    /// via `text_vertical_extent`) than `max_span_height` -- shrinking by
+    /// 1px steps down to `min_scale` and settling there if even that doesn't
+    /// fit, since clipping a few glyphs beats refusing to render the card at
+    /// all.
+    fn shrink_text_scale(
+        font: &FontVec,
+        text: &str,
+        max_scale: f32,
+        min_scale: f32,
+        max_width: u32,
+        max_span_height: u32,
+    ) -> f32 {
+        let mut scale = max_scale;
+        while scale > min_scale {
+            let (w, _) = imageproc::drawing::text_size(PxScale::from(scale), font, text);
+            let (extent_min, extent_max) = Self::text_vertical_extent(font, text, scale);
+            if w <= max_width && (extent_max - extent_min).ceil() as u32 <= max_span_height {
+                return scale;
+            }
+            scale -= 1.0;
+        }
+        min_scale
+    }
+
+    /// Draw `title` and `caption` on `card`, shrinking each to fit the
+    /// card's width and to stay clear of the QR code's quiet zone --
+    /// `[qr_top, qr_bottom)`, the vertical span `generate_card_qr` already
+    /// reserves for the code plus its border -- instead of the fixed 72pt/
+    /// 24pt sizes and hard-coded offsets that used to run a long
+    /// `--card-title`/`--card-subtitle` or caption off the card or into the
+    /// code itself.
+    fn add_text_to_card(
+        image: &mut RgbaImage,
+        font: &FontVec,
+        title: &str,
+        caption: &str,
+        qr_top: u32,
+        qr_bottom: u32,
+    ) {
+        let black = Rgba([0u8, 0, 0, 255]);
+        let max_width = image
+            .width()
+            .saturating_sub(2 * Self::CARD_TEXT_MARGIN_PX);
+
+        // The title's ink top is pinned to `title_top`; shrinking keeps its
+        // ink bottom from crossing into `qr_top`.
+        let title_top = 20i32;
+        let title_scale = Self::shrink_text_scale(
+            font,
+            title,
+            72.0,
+            Self::MIN_TITLE_SCALE,
+            max_width,
+            qr_top.saturating_sub(title_top as u32),
+        );
+        let (title_extent_min, _) = Self::text_vertical_extent(font, title, title_scale);
+        let title_y = title_top - title_extent_min.floor() as i32;
+        draw_text_mut(
+            image,
+            black,
+            Self::CARD_TEXT_MARGIN_PX as i32,
+            title_y,
+            PxScale::from(title_scale),
+            font,
+            title,
+        );
+
+        // The caption's ink bottom is pinned to `caption_bottom`; shrinking
+        // keeps its ink top from crossing above `qr_bottom`.
+        let caption_bottom = image.height() as i32 - 20;
+        let caption_scale = Self::shrink_text_scale(
+            font,
+            caption,
+            24.0,
+            Self::MIN_CAPTION_SCALE,
+            max_width,
+            (caption_bottom - qr_bottom as i32).max(0) as u32,
+        );
+        let (_, caption_extent_max) = Self::text_vertical_extent(font, caption, caption_scale);
+        let caption_y = caption_bottom - caption_extent_max.ceil() as i32;
+        draw_text_mut(
+            image,
+            black,
+            Self::CARD_TEXT_MARGIN_PX as i32,
+            caption_y,
+            PxScale::from(caption_scale),
+            font,
+            caption,
+        );
+    }
+
+    /// Render `payload` onto a printable card: a QR code plus a title and
+    /// caption, sized for an 8.5cm x 5.5cm card at `dpi` (`DEFAULT_CARD_DPI`
+    /// unless the caller overrides it, e.g. via `--dpi`), at `ec`
+    /// (`EcLevel::M` unless the caller overrides it, e.g. via
+    /// `--error-correction`), in `colors.fg`/`colors.bg` (plain
+    /// black-and-white by default), surrounded by a `colors.border`-module
+    /// quiet zone that scales with the module size the payload ends up
+    /// rendered at. The QR fills the card's available space exactly -- it's
+    /// scaled to a fractional pixels-per-module factor (see
+    /// `card_module_scale`) and resized with nearest-neighbor so it stays
+    /// crisp with no leftover space wasted to rounding, and fails with a
+    /// descriptive error instead of rendering an unscannable code if the
+    /// modules would come out smaller than `min_legible_module_scale`.
+    /// `font_override` is the path from `--font`, if any; `None` uses the
+    /// embedded default font, which is always available. `title` and
+    /// `caption` (e.g. `--card-title`/`--card-subtitle`) are shrunk by
+    /// `add_text_to_card` to fit the card's width and to stay clear of the
+    /// QR code's quiet zone, however long they are.
+    pub fn generate_card_qr(
+        payload: &str,
+        title: &str,
+        caption: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+    ) -> Result<RgbaImage> {
+        let card_width = cm_to_px(CARD_WIDTH_CM, dpi);
+        let card_height = cm_to_px(CARD_HEIGHT_CM, dpi);
+        let mut card: RgbaImage = ImageBuffer::from_pixel(card_width, card_height, colors.bg);
+
+        let available_width = card_width.saturating_sub(80); // reserve side margins
+        let available_height = card_height.saturating_sub(160); // reserve title/caption space
+        let fit = Self::fit_qr_in_box(payload, colors, ec, available_width, available_height, dpi)?;
+
+        let offset_x = (card_width - fit.final_size) / 2;
+        let offset_y = 90;
+        Self::draw_fitted_qr(&mut card, &fit, offset_x, offset_y);
+
+        let font = Self::load_font(font_override);
+        Self::add_text_to_card(
+            &mut card,
+            &font,
+            title,
+            caption,
+            offset_y,
+            offset_y + fit.final_size,
+        );
+
+        Ok(card)
+    }
+
+    /// Build `payload`'s QR code and pick the largest scale that fits it
+    /// into a `max_width`x`max_height` pixel box (accounting for
+    /// `colors.border`'s quiet zone) without falling below
+    /// `min_legible_module_scale` at `dpi`. Shared by `generate_card_qr` and
+    /// `generate_card_qr_with_verify` so both single- and two-QR cards pick
+    /// module size the same way.
+    fn fit_qr_in_box(
+        payload: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        max_width: u32,
+        max_height: u32,
+        dpi: u32,
+    ) -> Result<FittedQr> {
+        colors.validate()?;
+        Self::require_capacity(payload.len(), ec)?;
+        let code = QrCode::with_error_correction_level(payload.as_bytes(), ec)
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+        // Quiet zone is drawn ourselves below (scaled to `colors.border`
+        // modules) rather than relying on the crate's fixed built-in one.
+        // `module_dimensions(1, 1)` renders one pixel per module instead of
+        // the renderer's default 8, so `qr_image.width()` below is an actual
+        // module count -- matching `estimate_capacity`'s `modules` field.
+        // `ModuleStyle::Square` upscales to card size in one `resize`;
+        // `Dot`/`Rounded` instead draw each module's shape straight at the
+        // final scale, walking `qr_image` module-by-module.
+        let qr_image: ImageBuffer<Luma<u8>, Vec<u8>> = code
+            .render::<Luma<u8>>()
+            .quiet_zone(false)
+            .module_dimensions(1, 1)
+            .build();
+
+        let qr_width = qr_image.width();
+        let modules_with_border = qr_width + 2 * colors.border;
+        let scale = (max_width as f32 / modules_with_border as f32)
+            .min(max_height as f32 / modules_with_border as f32);
+        if scale <= 0.0 {
+            return Err(QRCryptError::QRGeneration(
+                "payload is too large to fit on a card QR code".to_string(),
+            ));
+        }
+        let min_scale = Self::min_legible_module_scale(dpi);
+        if scale < min_scale {
+            return Err(QRCryptError::QRGeneration(format!(
+                "payload only fits at {scale:.1} pixels per module on this card at {dpi} DPI, \
+                 below the ~{min_scale:.1}-pixel minimum most scanners can read reliably; try a \
+                 larger card, a lower error correction level, or a shorter payload"
+            )));
+        }
+        let final_size = (modules_with_border as f32 * scale).round() as u32;
+
+        Ok(FittedQr {
+            code,
+            qr_image,
+            colors,
+            scale,
+            final_size,
+        })
+    }
+
+    /// Draw `fit` onto `card` with its top-left corner (quiet zone
+    /// included) at `(x, y)`. Split out of `fit_qr_in_box` so a caller picks
+    /// where to place it -- centered for a single-QR card, side-by-side for
+    /// a two-QR one -- using `fit.final_size` before any pixels are drawn.
+    fn draw_fitted_qr(card: &mut RgbaImage, fit: &FittedQr, x: u32, y: u32) {
+        let FittedQr {
+            code,
+            qr_image,
+            colors,
+            scale,
+            ..
+        } = fit;
+        let colors = *colors;
+        let scale = *scale;
+        let qr_width = qr_image.width();
+        let border_px = (colors.border as f32 * scale).round() as u32;
+        let qr_x = x + border_px;
+        let qr_y = y + border_px;
+
+        if colors.module_style == ModuleStyle::Square {
+            let qr_pixel_size = (qr_width as f32 * scale).round() as u32;
+            let resized_qr = resize(qr_image, qr_pixel_size, qr_pixel_size, FilterType::Nearest);
+            for y in 0..resized_qr.height() {
+                for x in 0..resized_qr.width() {
+                    let pixel = resized_qr.get_pixel(x, y);
+                    let color = if pixel.0[0] < 128 {
+                        colors.fg
+                    } else {
+                        colors.bg
+                    };
+                    card.put_pixel(qr_x + x, qr_y + y, color);
+                }
+            }
+        } else {
+            // `qr_image` is 1 pixel per module, so walk it module-by-module
+            // rather than resizing it up first -- drawing a dot/rounded
+            // shape straight at `scale` keeps its edges as crisp as the
+            // Square path's nearest-neighbor resize keeps its corners.
+            let side = (scale * colors.fill_ratio).max(1.0);
+            for my in 0..qr_width {
+                for mx in 0..qr_width {
+                    if qr_image.get_pixel(mx, my).0[0] != 0 {
+                        continue;
+                    }
+                    let px = qr_x as i32 + (mx as f32 * scale).round() as i32;
+                    let py = qr_y as i32 + (my as f32 * scale).round() as i32;
+                    // `qr_image` has no quiet zone of its own (margin 0).
+                    if Self::is_function_pattern(code, mx, my, 0) {
+                        // Sized to the *next* module's rounded offset rather
+                        // than a flat `scale.round()`, so adjacent function
+                        // modules butt up with no 1px seam of background
+                        // peeking through a finder pattern that's supposed
+                        // to read as one solid square.
+                        let next_px = qr_x as i32 + ((mx + 1) as f32 * scale).round() as i32;
+                        let next_py = qr_y as i32 + ((my + 1) as f32 * scale).round() as i32;
+                        let w = (next_px - px).max(1) as u32;
+                        let h = (next_py - py).max(1) as u32;
+                        draw_filled_rect_mut(card, Rect::at(px, py).of_size(w, h), colors.fg);
+                        continue;
+                    }
+                    let center_x = px + (scale / 2.0).round() as i32;
+                    let center_y = py + (scale / 2.0).round() as i32;
+                    match colors.module_style {
+                        ModuleStyle::Dot => {
+                            let radius = ((side / 2.0).round() as i32).max(1);
+                            draw_filled_circle_mut(card, (center_x, center_y), radius, colors.fg);
+                        }
+                        ModuleStyle::Rounded => {
+                            Self::draw_rounded_module(card, center_x, center_y, side, colors.fg);
+                        }
+                        ModuleStyle::Square => unreachable!("handled above"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gap between the two QR codes on a `generate_card_qr_with_verify`
+    /// card, in pixels at `DEFAULT_CARD_DPI`; scaled by `sheet_scale` for
+    /// other DPIs the same way `save_sheet`'s cell spacing is.
+    const VERIFY_QR_GAP_PX_AT_300_DPI: f32 = 40.0;
+
+    /// Fraction of the two-QR row's available width given to the share QR;
+    /// the remainder (minus the gap) goes to the verification QR. The share
+    /// QR typically carries far more data than the handful of public fields
+    /// `shamir::ShareVerificationInfo` holds, so it gets the larger box.
+    const VERIFY_QR_SHARE_WIDTH_FRACTION: f32 = 0.62;
+
+    /// Render `payload` (a share, as `generate_card_qr` would alone) and
+    /// `verify_payload` (public, non-secret split metadata -- see
+    /// `shamir::ShareVerificationInfo`) side by side on one card, for `split
+    /// --card --with-verify`: the share QR on the left at its usual size,
+    /// a smaller verification QR on the right so whoever holds this card
+    /// can confirm which split it belongs to and, after reconstructing,
+    /// that they got the right secret, without needing a password or this
+    /// specific share's neighbors. Title and caption are drawn the same way
+    /// as a single-QR card, spanning the full card width above/below both
+    /// codes.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_card_qr_with_verify(
+        payload: &str,
+        verify_payload: &str,
+        title: &str,
+        caption: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+    ) -> Result<RgbaImage> {
+        let card_width = cm_to_px(CARD_WIDTH_CM, dpi);
+        let card_height = cm_to_px(CARD_HEIGHT_CM, dpi);
+        let mut card: RgbaImage = ImageBuffer::from_pixel(card_width, card_height, colors.bg);
+
+        let available_width = card_width.saturating_sub(80);
+        let available_height = card_height.saturating_sub(160);
+        let gap = (Self::VERIFY_QR_GAP_PX_AT_300_DPI * Self::sheet_scale(dpi)).round() as u32;
+        let share_box_width =
+            ((available_width.saturating_sub(gap)) as f32 * Self::VERIFY_QR_SHARE_WIDTH_FRACTION)
+                .round() as u32;
+        let verify_box_width = available_width
+            .saturating_sub(gap)
+            .saturating_sub(share_box_width);
+
+        let share_fit =
+            Self::fit_qr_in_box(payload, colors, ec, share_box_width, available_height, dpi)?;
+        let verify_fit = Self::fit_qr_in_box(
+            verify_payload,
+            colors,
+            ec,
+            verify_box_width,
+            available_height,
+            dpi,
+        )?;
+
+        let row_width = share_fit.final_size + gap + verify_fit.final_size;
+        let offset_x = (card_width.saturating_sub(row_width)) / 2;
+        let offset_y = 90;
+        let row_height = share_fit.final_size.max(verify_fit.final_size);
+
+        let share_y = offset_y + (row_height - share_fit.final_size) / 2;
+        Self::draw_fitted_qr(&mut card, &share_fit, offset_x, share_y);
+
+        let verify_x = offset_x + share_fit.final_size + gap;
+        let verify_y = offset_y + (row_height - verify_fit.final_size) / 2;
+        Self::draw_fitted_qr(&mut card, &verify_fit, verify_x, verify_y);
+
+        let font = Self::load_font(font_override);
+        Self::add_text_to_card(
+            &mut card,
+            &font,
+            title,
+            caption,
+            offset_y,
+            offset_y + row_height,
+        );
+
+        Ok(card)
+    }
+
+    /// Exact (fractional) pixels per QR module `generate_card_qr` would
+    /// render `modules_with_border` (a QR's module width plus its quiet
+    /// zone) at, onto a card of `card_width`x`card_height` pixels -- the
+    /// largest scale that fills the available space with nothing wasted to
+    /// rounding. `0.0` (or negative) means the payload is too large to fit
+    /// on a card at all.
+    fn card_module_scale(card_width: u32, card_height: u32, modules_with_border: u32) -> f32 {
+        let available_width = card_width.saturating_sub(80); // reserve side margins
+        let available_height = card_height.saturating_sub(160); // reserve title/caption space
+        (available_width as f32 / modules_with_border as f32)
+            .min(available_height as f32 / modules_with_border as f32)
+    }
+
+    /// A QR module needs roughly the same number of physical dots to stay
+    /// scannable no matter the print resolution, so this scales linearly
+    /// with `dpi` rather than being a fixed pixel count. `generate_card_qr`
+    /// refuses to render below this, since a smaller module is unlikely to
+    /// survive a phone camera's autofocus and a printer's dot gain.
+    const MIN_MODULE_PX_AT_300_DPI: f32 = 4.0;
+
+    fn min_legible_module_scale(dpi: u32) -> f32 {
+        Self::MIN_MODULE_PX_AT_300_DPI * dpi as f32 / 300.0
+    }
+
+    /// The physical size, in millimetres, of a single QR module on a card
+    /// rendered at `dpi` for a QR code with `modules` modules per side and a
+    /// `border`-module quiet zone. `None` if the code wouldn't fit on a card
+    /// at all -- unlike `generate_card_qr`, this does not reject a scale
+    /// that merely falls below the legible minimum, since its job is to
+    /// report the achievable size, not gatekeep it. Used by `estimate` to
+    /// report card legibility before committing to a split, without
+    /// rendering anything.
+    pub fn card_module_size_mm(modules: i16, border: u32, dpi: u32) -> Option<f32> {
+        let card_width = cm_to_px(CARD_WIDTH_CM, dpi);
+        let card_height = cm_to_px(CARD_HEIGHT_CM, dpi);
+        let modules_with_border = modules as u32 + 2 * border;
+        let scale = Self::card_module_scale(card_width, card_height, modules_with_border);
+        if scale <= 0.0 {
+            return None;
+        }
+        Some(scale / dpi as f32 * 25.4)
+    }
+
+    /// Render and save a card PNG for `payload` at `path`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_card_qr(
+        payload: &str,
+        title: &str,
+        caption: &str,
+        path: &Path,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+    ) -> Result<()> {
+        let card = Self::generate_card_qr(payload, title, caption, colors, ec, dpi, font_override)?;
+        Self::save_qr_png(&card, path, None, payload)
+    }
+
+    /// Like `save_card_qr`, but the card carries `verify_payload` on a
+    /// second, smaller QR code beside `payload`'s -- see
+    /// `generate_card_qr_with_verify` and `split --with-verify`. The PNG's
+    /// sidecar metadata chunk (see `save_qr_png`) still fingerprints only
+    /// `payload`, the share, since that's the QR code an `inspect` of this
+    /// file is actually about.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_card_qr_with_verify(
+        payload: &str,
+        verify_payload: &str,
+        title: &str,
+        caption: &str,
+        path: &Path,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+    ) -> Result<()> {
+        let card = Self::generate_card_qr_with_verify(
+            payload,
+            verify_payload,
+            title,
+            caption,
+            colors,
+            ec,
+            dpi,
+            font_override,
+        )?;
+        Self::save_qr_png(&card, path, None, payload)
+    }
+
+    /// Render `payload` as a plain QR code at `ec` (no card border, title,
+    /// or fixed physical size), then grow the canvas downward and draw
+    /// `caption` beneath it, shrunk with the same font machinery
+    /// `add_text_to_card` uses so it never reaches back up into the QR
+    /// code's own quiet zone. `font_override` is the path from `--font`, if
+    /// any; `None` uses the embedded default font.
+    pub fn generate_captioned_qr(
+        payload: &str,
+        caption: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        font_override: Option<&Path>,
+        symbology: Symbology,
+    ) -> Result<RgbaImage> {
+        Self::require_qr_only_colors_for(symbology, colors)?;
+        let qr = Self::render_payload_image(payload, colors, ec, symbology)?;
+        let font = Self::load_font(font_override);
+        let caption_scale = Self::shrink_text_scale(
+            &font,
+            caption,
+            24.0,
+            Self::MIN_CAPTION_SCALE,
+            qr.width(),
+            u32::MAX,
+        );
+        let (extent_min, extent_max) = Self::text_vertical_extent(&font, caption, caption_scale);
+        let caption_height = (extent_max - extent_min).ceil() as u32;
+
+        let mut image: RgbaImage = ImageBuffer::from_pixel(
+            qr.width(),
+            qr.height() + 2 * Self::PLAIN_CAPTION_MARGIN_PX + caption_height,
+            colors.bg,
+        );
+        image::imageops::overlay(&mut image, &qr, 0, 0);
+
+        let caption_top = qr.height() + Self::PLAIN_CAPTION_MARGIN_PX;
+        let caption_y = caption_top as i32 - extent_min.floor() as i32;
+        draw_text_mut(
+            &mut image,
+            Rgba([0u8, 0, 0, 255]),
+            0,
+            caption_y,
+            PxScale::from(caption_scale),
+            &font,
+            caption,
+        );
+
+        Ok(image)
+    }
+
+    /// Render and save a plain captioned QR PNG for `payload` at `path`.
+    pub fn save_captioned_qr(
+        payload: &str,
+        caption: &str,
+        path: &Path,
+        colors: QrColors,
+        ec: EcLevel,
+        font_override: Option<&Path>,
+        symbology: Symbology,
+    ) -> Result<()> {
+        let image =
+            Self::generate_captioned_qr(payload, caption, colors, ec, font_override, symbology)?;
+        Self::save_qr_png(&image, path, None, payload)
+    }
+
+    /// Turn a holder label into a filename-safe slug: lowercased, with
+    /// everything but letters, digits and `-` replaced by `-`.
+    fn slugify_label(label: &str) -> String {
+        label
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect()
+    }
+
+    /// The caption drawn beneath a Shamir share's QR code, on both its card
+    /// (`save_shamir_card_qrs`) and plain (`save_shamir_qrs_captioned`)
+    /// renderings: "Share 3 of 5 - threshold 3 - set ab12cd34", plus the
+    /// share's `label` if it has one. Derived from the share's own fields
+    /// rather than its filename, so it can't go stale if the file is
+    /// renamed.
+    pub fn shamir_caption(share: &ShamirShare) -> String {
+        let mut caption = format!(
+            "Share {} of {} - threshold {}",
+            share.index, share.total, share.threshold
+        );
+        if let Some(set_id) = share.set_id {
+            caption.push_str(&format!(" - set {set_id:08x}"));
+        }
+        if let Some(label) = &share.label {
+            caption.push_str(&format!(" - {label}"));
+        }
+        caption
+    }
+
+    /// The filename `save_shamir_card_qrs` gives a share's card, relative to
+    /// its output directory: `<prefix>-share-N.png`, or
+    /// `<prefix>-share-N-<label>.png` when the share has a `label`. Exposed
+    /// on its own so `split --dry-run` can report the planned file list
+    /// without rendering or writing anything.
+    pub fn shamir_card_filename(share: &ShamirShare, prefix: &str) -> String {
+        match &share.label {
+            Some(label) => format!(
+                "{prefix}-share-{}-{}.png",
+                share.index,
+                Self::slugify_label(label)
+            ),
+            None => format!("{prefix}-share-{}.png", share.index),
+        }
+    }
+
+    /// The fixed pixel dimensions `generate_card_qr` renders every card at,
+    /// from `CARD_WIDTH_CM`/`CARD_HEIGHT_CM` at `dpi`.
+    pub fn card_pixel_dimensions(dpi: u32) -> (u32, u32) {
+        (cm_to_px(CARD_WIDTH_CM, dpi), cm_to_px(CARD_HEIGHT_CM, dpi))
+    }
+
+    /// Where `--card-back`/`generate_card_back` points readers for help:
+    /// printed as-is on every back card, so it has to stay a real URL.
+    const PROJECT_URL: &str = "https://github.com/vblimits/QRCrypt";
+
+    /// Gap (in pixels) `generate_card_back` leaves between successive lines
+    /// of body text, and the margin above its first line.
+    const CARD_BACK_LINE_GAP_PX: i32 = 10;
+    const CARD_BACK_TEXT_TOP_PX: i32 = 40;
+    const CARD_BACK_HEADING_SCALE: f32 = 32.0;
+    const CARD_BACK_BODY_SCALE: f32 = 20.0;
+
+    /// Render a share card's back side: the project URL, the exact
+    /// `reconstruct` invocation needed to recover the secret, and the
+    /// share's threshold/total. Same physical dimensions as
+    /// `generate_card_qr`, so the two print back-to-back onto one card.
+    /// Not offered together with `--stealth`, whose whole point is a share
+    /// that doesn't look like a share -- a back card listing its
+    /// threshold/total would give that away. `extra_text`
+    /// (`--card-back-text`) is appended below, one line per line of
+    /// input. Reuses `add_text_to_card`'s font loading and text
+    /// measurement machinery rather than its fixed title/caption layout,
+    /// since a back card is just a stack of left-aligned lines.
+    pub fn generate_card_back(
+        threshold: u8,
+        total: u8,
+        extra_text: Option<&str>,
+        dpi: u32,
+        font_override: Option<&Path>,
+    ) -> Result<RgbaImage> {
+        let (card_width, card_height) = Self::card_pixel_dimensions(dpi);
+        let mut card: RgbaImage =
+            ImageBuffer::from_pixel(card_width, card_height, Rgba([255, 255, 255, 255]));
+        let font = Self::load_font(font_override);
+        let black = Rgba([0u8, 0, 0, 255]);
+        let max_width = card_width.saturating_sub(2 * Self::CARD_TEXT_MARGIN_PX);
+
+        let mut lines = vec![
+            ("QRCrypt recovery card".to_string(), Self::CARD_BACK_HEADING_SCALE),
+            (Self::PROJECT_URL.to_string(), Self::CARD_BACK_BODY_SCALE),
+            (
+                "This is one share of a Shamir-split secret. Gather the required \
+                 shares and run:"
+                    .to_string(),
+                Self::CARD_BACK_BODY_SCALE,
+            ),
+            (
+                "  qrcrypt reconstruct --scan-dir <folder of share images>".to_string(),
+                Self::CARD_BACK_BODY_SCALE,
+            ),
+            (
+                format!("Threshold: {threshold} of {total} shares required to reconstruct"),
+                Self::CARD_BACK_BODY_SCALE,
+            ),
+        ];
+        if let Some(extra) = extra_text {
+            lines.extend(
+                extra
+                    .lines()
+                    .map(|line| (line.to_string(), Self::CARD_BACK_BODY_SCALE)),
+            );
+        }
+
+        let mut y = Self::CARD_BACK_TEXT_TOP_PX;
+        for (text, max_scale) in lines {
+            let scale = Self::shrink_text_scale(
+                &font,
+                &text,
+                max_scale,
+                Self::MIN_CAPTION_SCALE,
+                max_width,
+                card_height,
+            );
+            let (extent_min, extent_max) = Self::text_vertical_extent(&font, &text, scale);
+            let draw_y = y - extent_min.floor() as i32;
+            draw_text_mut(
+                &mut card,
+                black,
+                Self::CARD_TEXT_MARGIN_PX as i32,
+                draw_y,
+                PxScale::from(scale),
+                &font,
+                &text,
+            );
+            y += (extent_max - extent_min).ceil() as i32 + Self::CARD_BACK_LINE_GAP_PX;
+        }
+
+        Ok(card)
+    }
+
+    /// Render and save a share's back card (see `generate_card_back`) to
+    /// `path`.
+    pub fn save_card_back(
+        threshold: u8,
+        total: u8,
+        extra_text: Option<&str>,
+        path: &Path,
+        dpi: u32,
+        font_override: Option<&Path>,
+    ) -> Result<()> {
+        let card = Self::generate_card_back(threshold, total, extra_text, dpi, font_override)?;
+        card.save(path)
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+        Ok(())
+    }
+
+    /// The path `save_shamir_card_qrs` writes share `index`'s back card
+    /// to, derived from `--card-back <path>` the same way `save_sheet`
+    /// derives paginated sheet filenames: the first share keeps `path`
+    /// exactly, later shares get `<stem>-share-N-back.<ext>` alongside it.
+    fn card_back_path(path: &Path, index: u8, is_first: bool) -> PathBuf {
+        if is_first {
+            return path.to_path_buf();
+        }
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("card-back");
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("png");
+        let filename = format!("{stem}-share-{index}-back.{extension}");
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.join(filename),
+            _ => PathBuf::from(filename),
+        }
+    }
+
+    /// Render and save one card per Shamir share, named `<prefix>-share-N.png`,
+    /// or `<prefix>-share-N-<label>.png` when the share has a `label`. The
+    /// label, if any, is also rendered on the card below the caption.
+    /// `title_override`/`subtitle_override` are `--card-title`/
+    /// `--card-subtitle`, if given; `None` keeps the default title and the
+    /// per-share generated caption. `verify_info` is `split --with-verify`'s
+    /// public split metadata; `Some` adds a second, smaller verification QR
+    /// to every card via `save_card_qr_with_verify` instead of `save_card_qr`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_shamir_card_qrs(
+        shares: &[ShamirShare],
+        output_dir: &Path,
+        prefix: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+        title_override: Option<&str>,
+        subtitle_override: Option<&str>,
+        card_back: Option<&Path>,
+        card_back_text: Option<&str>,
+        verify_info: Option<&ShareVerificationInfo>,
+    ) -> Result<Vec<PathBuf>> {
+        let title = title_override.unwrap_or(Self::DEFAULT_CARD_TITLE);
+        let verify_payload = verify_info
+            .map(|info| Self::encode_payload(&QRData::ShareVerification(info.clone())))
+            .transpose()?;
+
+        let results: Vec<Result<ShareCardFiles>> = shares
+            .par_iter()
+            .enumerate()
+            .map(|(i, share)| {
+                Self::save_one_shamir_card(
+                    share,
+                    i == 0,
+                    output_dir,
+                    prefix,
+                    title,
+                    subtitle_override,
+                    colors,
+                    ec,
+                    dpi,
+                    font_override,
+                    card_back,
+                    card_back_text,
+                    verify_payload.as_deref(),
+                )
+            })
+            .collect();
+
+        let files = Self::finish_share_batch(results, |files| {
+            let _ = std::fs::remove_file(&files.card);
+            for path in &files.extra {
+                let _ = std::fs::remove_file(path);
+            }
+        })?;
+        Ok(files.into_iter().map(|files| files.card).collect())
+    }
+
+    /// Render and write every file for one `save_shamir_card_qrs` share: its
+    /// card (or two-QR verification card), optional `--card-back` card, and
+    /// best-effort word-encoded twin. Run in parallel by
+    /// `save_shamir_card_qrs`, one call per share, so every path it touches
+    /// must be unique to this share -- true of all three, since each is
+    /// named from the share itself. Any failure is reported with this
+    /// share's id so a partial batch doesn't read as an anonymous I/O error.
+    #[allow(clippy::too_many_arguments)]
+    fn save_one_shamir_card(
+        share: &ShamirShare,
+        is_first: bool,
+        output_dir: &Path,
+        prefix: &str,
+        title: &str,
+        subtitle_override: Option<&str>,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+        card_back: Option<&Path>,
+        card_back_text: Option<&str>,
+        verify_payload: Option<&str>,
+    ) -> Result<ShareCardFiles> {
+        let mut written = Vec::new();
+        let mut write = || -> Result<PathBuf> {
+            let payload = Self::encode_payload(&QRData::ShamirShare(share.clone()))?;
+            let generated_caption = Self::shamir_caption(share);
+            let caption = subtitle_override.unwrap_or(&generated_caption);
+            let path = output_dir.join(Self::shamir_card_filename(share, prefix));
+            match verify_payload {
+                Some(verify_payload) => Self::save_card_qr_with_verify(
+                    &payload, verify_payload, title, caption, &path, colors, ec, dpi, font_override,
+                )?,
+                None => Self::save_card_qr(
+                    &payload, title, caption, &path, colors, ec, dpi, font_override,
+                )?,
+            }
+            written.push(path.clone());
+
+            if let Some(back_path) = card_back {
+                let back_path = Self::card_back_path(back_path, share.index, is_first);
+                Self::save_card_back(
+                    share.threshold, share.total, card_back_text, &back_path, dpi, font_override,
+                )?;
+                written.push(back_path);
+            }
+
+            // A word-encoded twin of the card, meant to be printed and kept
+            // alongside it: if the QR or JSON ever becomes unreadable, the
+            // share can be retyped by hand instead. Not every share can be
+            // word-encoded (grouped and password-encrypted shares aren't
+            // supported), so this is best-effort and skipped silently.
+            if let Ok(words) = crate::shamir::encode_share_words(share) {
+                let words_path = path.with_extension("words.txt");
+                std::fs::write(&words_path, words.join(" "))?;
+                written.push(words_path);
+            }
+
+            Ok(path)
+        };
+
+        match write() {
+            Ok(card) => Ok(ShareCardFiles {
+                card,
+                extra: written,
+            }),
+            Err(e) => {
+                for path in &written {
+                    let _ = std::fs::remove_file(path);
+                }
+                Err(QRCryptError::QRGeneration(format!(
+                    "share {}: {e}",
+                    share.index
+                )))
+            }
+        }
+    }
+
+    /// Turn one share-per-item batch of `Result`s from a `par_iter` render
+    /// into the overall `Result<Vec<T>>` a `save_shamir_*_qrs` function
+    /// returns, in `shares`' original order regardless of which thread
+    /// finished first. If any share failed, `cleanup` deletes every file the
+    /// *other*, already-succeeded shares wrote (so a failed batch doesn't
+    /// leave a directory half-full of orphaned cards) and the first failure
+    /// by share index -- not by whichever thread hit it first -- is
+    /// returned.
+    fn finish_share_batch<T>(
+        results: Vec<Result<T>>,
+        cleanup: impl Fn(&T),
+    ) -> Result<Vec<T>> {
+        if results.iter().all(Result::is_ok) {
+            return Ok(results.into_iter().map(|r| r.unwrap()).collect());
+        }
+        let mut first_err = None;
+        for result in results {
+            match result {
+                Ok(value) => cleanup(&value),
+                Err(e) => {
+                    first_err.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_err.expect("checked above that at least one result is an error"))
+    }
+
+    /// Like `save_shamir_card_qrs`, but each share is rendered as a plain
+    /// captioned QR code (`generate_captioned_qr`) instead of a full card --
+    /// no title, no fixed physical size -- for setups that want shares
+    /// told apart at a glance without paying for card-sized PNGs. The
+    /// caption is the same one `save_shamir_card_qrs` generates, derived
+    /// from the share's own fields rather than its filename, so it can't go
+    /// stale if a file is renamed. `subtitle_override` is `--card-subtitle`,
+    /// if given; `None` keeps the per-share generated caption.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_shamir_qrs_captioned(
+        shares: &[ShamirShare],
+        output_dir: &Path,
+        prefix: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        font_override: Option<&Path>,
+        subtitle_override: Option<&str>,
+        symbology: Symbology,
+    ) -> Result<Vec<PathBuf>> {
+        let results: Vec<Result<PathBuf>> = shares
+            .par_iter()
+            .map(|share| {
+                let write = || -> Result<PathBuf> {
+                    let payload = Self::encode_payload(&QRData::ShamirShare(share.clone()))?;
+                    let generated_caption = Self::shamir_caption(share);
+                    let caption = subtitle_override.unwrap_or(&generated_caption);
+                    let path = output_dir.join(Self::shamir_card_filename(share, prefix));
+                    Self::save_captioned_qr(
+                        &payload, caption, &path, colors, ec, font_override, symbology,
+                    )?;
+                    Ok(path)
+                };
+                write().map_err(|e| {
+                    QRCryptError::QRGeneration(format!("share {}: {e}", share.index))
+                })
+            })
+            .collect();
+
+        Self::finish_share_batch(results, |path| {
+            let _ = std::fs::remove_file(path);
+        })
+    }
+
+    /// Margin between a sheet's own edge and its grid of cards, in
+    /// millimetres, at any `dpi` -- scaled the same way `cm_to_px` scales a
+    /// physical size into pixels.
+    const SHEET_MARGIN_MM: f32 = 10.0;
+
+    /// Gap `compose_sheet` leaves between adjacent cells for the dashed cut
+    /// guide to run through, and the dash/gap lengths of that guide itself,
+    /// all in pixels at 300 DPI -- scaled with `dpi` like
+    /// `MIN_MODULE_PX_AT_300_DPI` so the sheet looks the same at any print
+    /// resolution.
+    const SHEET_CELL_GAP_PX_AT_300_DPI: f32 = 24.0;
+    const SHEET_CUT_DASH_PX_AT_300_DPI: f32 = 8.0;
+    const SHEET_CUT_GAP_PX_AT_300_DPI: f32 = 6.0;
+
+    /// Space `compose_sheet` reserves below each card for its caption, and
+    /// the gap between the card's own bottom edge and that caption, in
+    /// pixels at 300 DPI.
+    const SHEET_CAPTION_HEIGHT_PX_AT_300_DPI: f32 = 36.0;
+    const SHEET_CAPTION_GAP_PX_AT_300_DPI: f32 = 14.0;
+
+    /// Color the dashed cut guides between cells are drawn in: light enough
+    /// to read as a guide rather than part of a card, dark enough to still
+    /// show up on plain paper.
+    const SHEET_CUT_GUIDE_COLOR: Rgba<u8> = Rgba([160, 160, 160, 255]);
+
+    fn sheet_scale(dpi: u32) -> f32 {
+        dpi as f32 / DEFAULT_CARD_DPI as f32
+    }
+
+    /// Lay already-rendered card images out into a grid on one or more
+    /// `paper` pages at `dpi`, each image paired with the caption drawn in
+    /// the margin beneath it, with dashed cut guides running through the
+    /// gaps between cells so a printed sheet can be trimmed into individual
+    /// cards. Every cell is sized to the largest image given, so a mix of
+    /// card and parity images still lines up into a clean grid. Pages are
+    /// filled row-major in the order `images` is given; a split that
+    /// doesn't fit on one page spills onto as many more as it needs.
+    pub fn compose_sheet(
+        images: &[(DynamicImage, String)],
+        paper: PaperSize,
+        dpi: u32,
+    ) -> Result<Vec<RgbaImage>> {
+        if images.is_empty() {
+            return Err(QRCryptError::QRGeneration(
+                "compose_sheet needs at least one image".to_string(),
+            ));
+        }
+
+        let scale = Self::sheet_scale(dpi);
+        let margin = cm_to_px(Self::SHEET_MARGIN_MM / 10.0, dpi);
+        let cell_gap = (Self::SHEET_CELL_GAP_PX_AT_300_DPI * scale).round() as u32;
+        let caption_gap = (Self::SHEET_CAPTION_GAP_PX_AT_300_DPI * scale).round() as u32;
+        let caption_height = (Self::SHEET_CAPTION_HEIGHT_PX_AT_300_DPI * scale).round() as u32;
+        let caption_scale = (Self::MIN_CAPTION_SCALE * scale).max(Self::MIN_CAPTION_SCALE);
+
+        let image_width = images.iter().map(|(img, _)| img.width()).max().unwrap();
+        let image_height = images.iter().map(|(img, _)| img.height()).max().unwrap();
+        let cell_width = image_width;
+        let cell_height = image_height + caption_gap + caption_height;
+
+        let (page_width_mm, page_height_mm) = paper.dimensions_mm();
+        let page_width = cm_to_px(page_width_mm / 10.0, dpi);
+        let page_height = cm_to_px(page_height_mm / 10.0, dpi);
+        let usable_width = page_width.saturating_sub(2 * margin);
+        let usable_height = page_height.saturating_sub(2 * margin);
+        let cols = ((usable_width + cell_gap) / (cell_width + cell_gap)).max(1);
+        let rows = ((usable_height + cell_gap) / (cell_height + cell_gap)).max(1);
+        let per_page = (cols * rows) as usize;
+
+        let font = Self::load_font(None);
+        let grid_width = cols * cell_width + cols.saturating_sub(1) * cell_gap;
+        let grid_height = rows * cell_height + rows.saturating_sub(1) * cell_gap;
+        let start_x = margin + usable_width.saturating_sub(grid_width) / 2;
+        let start_y = margin + usable_height.saturating_sub(grid_height) / 2;
+
+        let pages = images
+            .chunks(per_page)
+            .map(|chunk| {
+                let mut page: RgbaImage =
+                    ImageBuffer::from_pixel(page_width, page_height, Rgba([255, 255, 255, 255]));
+                let rows_on_page = chunk.len().div_ceil(cols as usize) as u32;
+
+                for (i, (image, caption)) in chunk.iter().enumerate() {
+                    let col = (i as u32) % cols;
+                    let row = (i as u32) / cols;
+                    let cell_x = start_x + col * (cell_width + cell_gap);
+                    let cell_y = start_y + row * (cell_height + cell_gap);
+
+                    let rgba = image.to_rgba8();
+                    let image_x = cell_x + (cell_width.saturating_sub(rgba.width())) / 2;
+                    image::imageops::overlay(&mut page, &rgba, image_x as i64, cell_y as i64);
+
+                    let caption_y = cell_y + image_height + caption_gap;
+                    let (caption_width, _) =
+                        imageproc::drawing::text_size(PxScale::from(caption_scale), &font, caption);
+                    let caption_x = cell_x + cell_width.saturating_sub(caption_width) / 2;
+                    draw_text_mut(
+                        &mut page,
+                        Rgba([0, 0, 0, 255]),
+                        caption_x as i32,
+                        caption_y as i32,
+                        PxScale::from(caption_scale),
+                        &font,
+                        caption,
+                    );
+                }
+
+                Self::draw_cut_guides(
+                    &mut page,
+                    start_x,
+                    start_y,
+                    cell_width,
+                    cell_height,
+                    cell_gap,
+                    cols,
+                    rows_on_page,
+                    dpi,
+                );
+
+                page
+            })
+            .collect();
+
+        Ok(pages)
+    }
+
+    /// Draw dashed vertical/horizontal guides through the gaps between a
+    /// `compose_sheet` grid's `cols` x `rows` cells, so a printed sheet can
+    /// be cut along them without a straightedge hunting for the gap.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_cut_guides(
+        page: &mut RgbaImage,
+        start_x: u32,
+        start_y: u32,
+        cell_width: u32,
+        cell_height: u32,
+        cell_gap: u32,
+        cols: u32,
+        rows: u32,
+        dpi: u32,
+    ) {
+        let scale = Self::sheet_scale(dpi);
+        let dash = ((Self::SHEET_CUT_DASH_PX_AT_300_DPI * scale).round() as u32).max(1);
+        let gap = ((Self::SHEET_CUT_GAP_PX_AT_300_DPI * scale).round() as u32).max(1);
+        let grid_width = cols * cell_width + cols.saturating_sub(1) * cell_gap;
+        let grid_height = rows * cell_height + rows.saturating_sub(1) * cell_gap;
+
+        for col in 1..cols {
+            let x = start_x + col * cell_width + (col - 1) * cell_gap + cell_gap / 2;
+            Self::draw_dashed_line(page, x, start_y, x, start_y + grid_height, dash, gap, true);
+        }
+        for row in 1..rows {
+            let y = start_y + row * cell_height + (row - 1) * cell_gap + cell_gap / 2;
+            Self::draw_dashed_line(page, start_x, y, start_x + grid_width, y, dash, gap, false);
+        }
+    }
+
+    /// Draw one dashed line from `(x0, y0)` to either `(x0, y1)` (if
+    /// `vertical`) or `(x1, y0)` (otherwise), alternating `dash` pixels of
+    /// `SHEET_CUT_GUIDE_COLOR` with `gap` pixels left untouched.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_dashed_line(
+        page: &mut RgbaImage,
+        x0: u32,
+        y0: u32,
+        x1: u32,
+        y1: u32,
+        dash: u32,
+        gap: u32,
+        vertical: bool,
+    ) {
+        let len = if vertical { y1.saturating_sub(y0) } else { x1.saturating_sub(x0) };
+        let (width, height) = (page.width(), page.height());
+        let mut pos = 0u32;
+        while pos < len {
+            let end = (pos + dash).min(len);
+            for p in pos..end {
+                let (x, y) = if vertical { (x0, y0 + p) } else { (x0 + p, y0) };
+                if x < width && y < height {
+                    page.put_pixel(x, y, Self::SHEET_CUT_GUIDE_COLOR);
+                }
+            }
+            pos = end + gap;
+        }
+    }
+
+    /// Render `images` (card images paired with their captions) onto one or
+    /// more `paper` pages via `compose_sheet`, and save them to `path`
+    /// (the first/only page) plus `<stem>-page-2.png`, `<stem>-page-3.png`,
+    /// ... for any further pages the split didn't fit on one sheet.
+    pub fn save_sheet(
+        images: &[(DynamicImage, String)],
+        path: &Path,
+        paper: PaperSize,
+        dpi: u32,
+    ) -> Result<Vec<PathBuf>> {
+        let pages = Self::compose_sheet(images, paper, dpi)?;
+        let stem = path.file_stem().and_then(|s| s.to_str()).ok_or_else(|| {
+            QRCryptError::QRGeneration(format!("{} has no usable file stem", path.display()))
+        })?;
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("png");
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+
+        let mut paths = Vec::with_capacity(pages.len());
+        for (i, page) in pages.iter().enumerate() {
+            let page_path = if i == 0 {
+                path.to_path_buf()
+            } else {
+                let name = format!("{stem}-page-{}.{extension}", i + 1);
+                match dir {
+                    Some(dir) => dir.join(name),
+                    None => PathBuf::from(name),
+                }
+            };
+            page.save(&page_path)
+                .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+            paths.push(page_path);
+        }
+        Ok(paths)
+    }
+
+    /// Like `save_sheet`, but re-reads each of `card_paths` (already written
+    /// by `save_shamir_card_qrs`/`save_shamir_parity_qrs`/
+    /// `save_shamir_qrs_captioned`) from disk instead of taking decoded
+    /// images directly, pairing each with the caption at the same index in
+    /// `captions`. Mirrors `pdf::write_shares_pdf`'s own re-read of the
+    /// already-rendered card PNGs, which keeps the sheet's QR content
+    /// byte-for-byte identical to what's on disk.
+    pub fn save_sheet_from_paths(
+        card_paths: &[PathBuf],
+        captions: &[String],
+        path: &Path,
+        paper: PaperSize,
+        dpi: u32,
+    ) -> Result<Vec<PathBuf>> {
+        let images = card_paths
+            .iter()
+            .zip(captions)
+            .map(|(card_path, caption)| {
+                let image = image::open(card_path).map_err(|e| {
+                    QRCryptError::QRGeneration(format!(
+                        "could not re-read {} for the sheet: {e}",
+                        card_path.display()
+                    ))
+                })?;
+                Ok((image, caption.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::save_sheet(&images, path, paper, dpi)
+    }
+
+    /// The filename `save_shamir_parity_qrs` gives the `i`th (0-indexed)
+    /// parity share's card.
+    pub fn parity_card_filename(i: usize, prefix: &str) -> String {
+        format!("{prefix}-parity-{}.png", i + 1)
+    }
+
+    /// The caption drawn beneath a parity share's QR code, on
+    /// `save_shamir_parity_qrs`'s card: "Parity 2 of 3 (for 5 shares)".
+    pub fn parity_caption(i: usize, parity: &ParityShare) -> String {
+        format!(
+            "Parity {} of {} (for {} shares)",
+            i + 1,
+            parity.parity_total,
+            parity.total
+        )
+    }
+
+    /// Render and save one card per parity share, named
+    /// `<prefix>-parity-N.png`, generated by `shamir::generate_parity_shares`.
+    /// `title_override`/`subtitle_override` are `--card-title`/
+    /// `--card-subtitle`, if given; `None` keeps the default title and the
+    /// per-parity-share generated caption.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_shamir_parity_qrs(
+        parity: &[ParityShare],
+        output_dir: &Path,
+        prefix: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+        title_override: Option<&str>,
+        subtitle_override: Option<&str>,
+    ) -> Result<Vec<PathBuf>> {
+        let title = title_override.unwrap_or(Self::DEFAULT_CARD_TITLE);
+        let mut paths = Vec::with_capacity(parity.len());
+        for (i, p) in parity.iter().enumerate() {
+            let payload = Self::encode_payload(&QRData::ParityShare(p.clone()))?;
+            let generated_caption = Self::parity_caption(i, p);
+            let caption = subtitle_override.unwrap_or(&generated_caption);
+            let path = output_dir.join(Self::parity_card_filename(i, prefix));
+            Self::save_card_qr(&payload, title, caption, &path, colors, ec, dpi, font_override)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// SHA-256 of the exact JSON payload a share's QR code embeds, hex-encoded.
+    /// Printed in `generate_info_text` next to each share so a holder can
+    /// reverify a card later, and recomputed the same way by `verify-share`
+    /// to check a card hasn't rotted (reprinted, re-scanned, edited by hand).
+    pub fn share_fingerprint(share: &ShamirShare) -> Result<String> {
+        let payload = Self::encode_payload(&QRData::ShamirShare(share.clone()))?;
+        Ok(hex::encode(Sha256::digest(payload.as_bytes())))
+    }
+
+    /// SHA-256 of the secret a split was run on, hex-encoded. Printed in
+    /// info.txt so `reconstruct --verify-only` can confirm a share set still
+    /// reconstructs the right secret without ever displaying it.
+    pub fn secret_fingerprint(secret: &[u8]) -> String {
+        hex::encode(Sha256::digest(secret))
+    }
+
+    /// Build the human-readable summary written alongside a share set.
+    /// `parity_count` is the number of extra parity shares generated by
+    /// `split --parity`, if any, used to describe the combined fault
+    /// tolerance: reconstruction still only needs `threshold` of the
+    /// original shares, but up to `parity_count` destroyed original shares
+    /// can additionally be rebuilt (from the rest plus parity) before that.
+    pub fn generate_info_text(
+        shares: &[ShamirShare],
+        filenames: &[PathBuf],
+        parity_count: usize,
+        secret: &[u8],
+    ) -> String {
+        let mut text = String::new();
+        text.push_str("QRCrypt Shamir Secret Sharing Info\n");
+        text.push_str("===================================\n\n");
+        text.push_str(&format!("Created: {}\n\n", unix_timestamp_now()));
+        if let Some(first) = shares.first() {
+            text.push_str(&format!(
+                "Threshold: {} of {} shares required to reconstruct\n",
+                first.threshold, first.total
+            ));
+            text.push_str(&format!(
+                "You will need exactly {} of the {} share cards below to recover the secret.\n",
+                first.threshold, first.total
+            ));
+            text.push_str(&format!(
+                "Secret fingerprint: {} (run `reconstruct --verify-only` to check the shares \
+                 still reconstruct this, without printing the secret)\n",
+                Self::secret_fingerprint(secret)
+            ));
+            if parity_count > 0 {
+                text.push_str(&format!(
+                    "Parity: {parity_count} extra share(s); up to {parity_count} destroyed \
+                     share(s) can be rebuilt from the rest plus parity before reconstructing\n"
+                ));
+            }
+            if let Some(set_id) = first.set_id {
+                text.push_str(&format!(
+                    "Group fingerprint: {set_id:08x} (every card below should show this \
+                     same fingerprint; don't mix cards from a different split)\n"
+                ));
+            }
+            text.push('\n');
+        }
+        for (share, path) in shares.iter().zip(filenames.iter()) {
+            match (&share.label, &share.note) {
+                (Some(label), Some(note)) => {
+                    text.push_str(&format!(
+                        "Share {} ({label}, {note}): {}\n",
+                        share.index,
+                        path.display()
+                    ));
+                }
+                (Some(label), None) => {
+                    text.push_str(&format!(
+                        "Share {} ({label}): {}\n",
+                        share.index,
+                        path.display()
+                    ));
+                }
+                (None, _) => {
+                    text.push_str(&format!("Share {}: {}\n", share.index, path.display()));
+                }
+            }
+            if let Ok(fingerprint) = Self::share_fingerprint(share) {
+                text.push_str(&format!("  SHA-256: {fingerprint}\n"));
+            }
+            if let Ok(words) = crate::shamir::encode_share_words(share) {
+                text.push_str(&format!("  Words: {}\n", words.join(" ")));
+            }
+        }
+        for (i, path) in filenames.iter().skip(shares.len()).enumerate() {
+            text.push_str(&format!("Parity {}: {}\n", i + 1, path.display()));
+        }
+        if let Some(first) = shares.first() {
+            let threshold = first.threshold as usize;
+            text.push_str("\nTo reconstruct, run:\n  qrcrypt reconstruct");
+            for path in filenames.iter().take(threshold) {
+                text.push_str(&format!(" --shares {}", path.display()));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Like `generate_info_text`, but for `split --words-only`: every share
+    /// is listed by its words (see `shamir::encode_share_words`) instead of a
+    /// QR card path, since no card PNGs are rendered in this mode.
+    pub fn generate_words_only_info_text(shares: &[ShamirShare], secret: &[u8]) -> Result<String> {
+        let mut text = String::new();
+        text.push_str("QRCrypt Shamir Secret Sharing Info (Words)\n");
+        text.push_str("===========================================\n\n");
+        text.push_str(&format!("Created: {}\n\n", unix_timestamp_now()));
+        if let Some(first) = shares.first() {
+            text.push_str(&format!(
+                "Threshold: {} of {} shares required to reconstruct\n",
+                first.threshold, first.total
+            ));
+            text.push_str(&format!(
+                "You will need exactly {} of the {} share word lists below to recover the secret.\n",
+                first.threshold, first.total
+            ));
+            text.push_str(&format!(
+                "Secret fingerprint: {} (run `reconstruct --verify-only` to check the shares \
+                 still reconstruct this, without printing the secret)\n",
+                Self::secret_fingerprint(secret)
+            ));
+            if let Some(set_id) = first.set_id {
+                text.push_str(&format!(
+                    "Group fingerprint: {set_id:08x} (every share below should show this \
+                     same fingerprint; don't mix shares from a different split)\n"
+                ));
+            }
+            text.push('\n');
+        }
+        for share in shares {
+            match (&share.label, &share.note) {
+                (Some(label), Some(note)) => {
+                    text.push_str(&format!("Share {} ({label}, {note}):\n", share.index));
+                }
+                (Some(label), None) => {
+                    text.push_str(&format!("Share {} ({label}):\n", share.index));
+                }
+                (None, _) => {
+                    text.push_str(&format!("Share {}:\n", share.index));
+                }
+            }
+            let words = crate::shamir::encode_share_words(share)?;
+            text.push_str(&format!("  {}\n", words.join(" ")));
+        }
+        if !shares.is_empty() {
+            text.push_str("\nTo reconstruct, run:\n  qrcrypt reconstruct --words\nand paste each share's words when prompted, one share per line.\n");
+        }
+        Ok(text)
+    }
+
+    /// Like `generate_info_text`, but for shares from
+    /// `shamir::split_secret_with_groups`: lists each group's own threshold
+    /// and cards separately, plus how many of the groups are required
+    /// overall.
+    pub fn generate_grouped_info_text(
+        shares: &[ShamirShare],
+        filenames: &[PathBuf],
+        secret: &[u8],
+    ) -> String {
+        let mut text = String::new();
+        text.push_str("QRCrypt Shamir Secret Sharing Info (Groups)\n");
+        text.push_str("============================================\n\n");
+
+        let (groups_required, group_count) = match shares.first() {
+            Some(first) => (
+                first.group_threshold.unwrap_or(1),
+                first.group_count.unwrap_or(1),
+            ),
+            None => return text,
+        };
+        text.push_str(&format!(
+            "You will need enough shares from {groups_required} of these {group_count} groups \
+             to recover the secret.\n"
+        ));
+        text.push_str(&format!(
+            "Secret fingerprint: {} (run `reconstruct --verify-only` to check the shares \
+             still reconstruct this, without printing the secret)\n\n",
+            Self::secret_fingerprint(secret)
+        ));
+
+        let mut by_group: std::collections::BTreeMap<u8, Vec<(&ShamirShare, &PathBuf)>> =
+            std::collections::BTreeMap::new();
+        for (share, path) in shares.iter().zip(filenames.iter()) {
+            by_group
+                .entry(share.group_id.unwrap_or(0))
+                .or_default()
+                .push((share, path));
+        }
+        for (group_id, members) in &by_group {
+            let (first, _) = members[0];
+            text.push_str(&format!(
+                "Group {group_id}: {} of {} shares required\n",
+                first.threshold, first.total
+            ));
+            for (share, path) in members {
+                text.push_str(&format!("  Share {}: {}\n", share.index, path.display()));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The filename `save_slip39_card_qrs` gives the `i`th (0-indexed)
+    /// mnemonic share's card.
+    pub fn slip39_card_filename(i: usize, prefix: &str) -> String {
+        format!("{prefix}-share-{}.png", i + 1)
+    }
+
+    /// Render and save one card PNG per SLIP-39 share mnemonic, named
+    /// `<prefix>-share-N.png`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_slip39_card_qrs(
+        mnemonics: &[String],
+        output_dir: &Path,
+        prefix: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        dpi: u32,
+        font_override: Option<&Path>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::with_capacity(mnemonics.len());
+        for (i, mnemonic) in mnemonics.iter().enumerate() {
+            let payload = Self::encode_payload(&QRData::Slip39Share(mnemonic.clone()))?;
+            let caption = format!("SLIP-39 share {} of {}", i + 1, mnemonics.len());
+            let path = output_dir.join(Self::slip39_card_filename(i, prefix));
+            Self::save_card_qr(
+                &payload,
+                Self::DEFAULT_CARD_TITLE,
+                &caption,
+                &path,
+                colors,
+                ec,
+                dpi,
+                font_override,
+            )?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Slice `ciphertext` into QR-sized `FilePart`s sharing `salt`/`nonce`/
+    /// `kdf`, and render + save one plain QR per part, named
+    /// `<prefix>-part-N.png`. Unlike shares, file parts are meant to be
+    /// re-scanned by software rather than kept as printable cards, so they
+    /// use the unconstrained `generate_qr` renderer instead of the
+    /// fixed-size card, which keeps the per-part chunk size practical.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_file_parts(
+        ciphertext: &[u8],
+        salt: &[u8],
+        nonce: &[u8],
+        kdf: &KdfParams,
+        output_dir: &Path,
+        prefix: &str,
+        colors: QrColors,
+        ec: EcLevel,
+    ) -> Result<Vec<PathBuf>> {
+        let chunks: Vec<&[u8]> = if ciphertext.is_empty() {
+            vec![&[]]
+        } else {
+            ciphertext.chunks(FILE_PART_CHUNK_BYTES).collect()
+        };
+        let total = chunks.len() as u32;
+
+        let mut paths = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let part = FilePart {
+                index: i as u32,
+                total,
+                salt: salt.to_vec(),
+                nonce: nonce.to_vec(),
+                kdf: kdf.clone(),
+                data: chunk.to_vec(),
+            };
+            let payload = Self::encode_payload(&QRData::FilePart(part))?;
+            let path = output_dir.join(format!("{prefix}-part-{}.png", i + 1));
+            Self::generate_qr(&payload, &path, colors, ec, Symbology::Qr)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// The most `PayloadPart` data bytes that still produce an encoded
+    /// payload fitting within `max_version` at `ec`, found the same way
+    /// `max_capacity_bytes` sizes a whole payload: binary-search a probe
+    /// through the real encoder rather than hand-deriving the envelope's
+    /// overhead. Never exceeds `PAYLOAD_PART_CHUNK_BYTES`, so an uncapped
+    /// call (`max_version == MAX_QR_VERSION`) chunks exactly like it always
+    /// has.
+    fn max_part_chunk_bytes(ec: EcLevel, max_version: i16) -> usize {
+        let mut lo = 1usize;
+        let mut hi = PAYLOAD_PART_CHUNK_BYTES;
+        while lo < hi {
+            let mid = lo + (hi - lo).div_ceil(2);
+            let probe = PayloadPart {
+                payload_id: 0,
+                index: 0,
+                total: 1,
+                checksum: 0,
+                data: vec![b'x'; mid],
+            };
+            let fits = Self::encode_payload(&QRData::PayloadPart(probe))
+                .map(|payload| Self::estimate_capacity_capped(payload.len(), ec, max_version).fits)
+                .unwrap_or(false);
+            if fits {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        lo
+    }
+
+    /// Save an already-encoded `QRData` payload as a single QR code if it
+    /// fits within `max_version` (`<prefix>.png`), or else slice it into
+    /// `PayloadPart`s sized to fit `max_version` and save one plain QR per
+    /// part (`<prefix>-part-N.png`), mirroring `save_file_parts`'s naming.
+    /// Unlike `FilePart`, which only ever wraps file ciphertext for
+    /// `encrypt-file`, this applies to any oversized payload --
+    /// `handle_encrypt` uses it so a secret too big for one QR code is split
+    /// automatically instead of erroring out. `max_version` caps how dense a
+    /// single code is allowed to get (`DEFAULT_MAX_QR_VERSION` unless the
+    /// caller overrides it via `--max-qr-version`), for cheap handheld
+    /// scanners that choke above version 20 or so; logs how many parts were
+    /// produced and why whenever splitting is triggered.
+    pub fn save_payload_auto(
+        payload: &str,
+        output_dir: &Path,
+        prefix: &str,
+        colors: QrColors,
+        ec: EcLevel,
+        max_version: i16,
+        symbology: Symbology,
+    ) -> Result<Vec<PathBuf>> {
+        if symbology == Symbology::DataMatrix {
+            // DataMatrix has its own size ladder, not `max_version`'s QR
+            // versions, and no multi-part equivalent yet -- same limitation
+            // `generate_with_logo` has, just for a different reason.
+            let path = output_dir.join(format!("{prefix}.png"));
+            Self::generate_qr(payload, &path, colors, ec, symbology)?;
+            return Ok(vec![path]);
+        }
+        if Self::estimate_capacity_capped(payload.len(), ec, max_version).fits {
+            let path = output_dir.join(format!("{prefix}.png"));
+            Self::generate_qr(payload, &path, colors, ec, symbology)?;
+            return Ok(vec![path]);
+        }
+
+        let chunk_bytes = Self::max_part_chunk_bytes(ec, max_version);
+        let payload_id = OsRng.next_u32();
+        let checksum = checksum_of(payload.as_bytes());
+        let chunks: Vec<&[u8]> = payload.as_bytes().chunks(chunk_bytes).collect();
+        let total = chunks.len() as u32;
+        crate::utils::print_warning(&format!(
+            "payload doesn't fit in a single QR code at or below version {max_version}; \
+             splitting into {total} parts"
+        ));
+
+        let mut paths = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let part = PayloadPart {
+                payload_id,
+                index: i as u32,
+                total,
+                checksum,
+                data: chunk.to_vec(),
+            };
+            let part_payload = Self::encode_payload(&QRData::PayloadPart(part))?;
+            let path = output_dir.join(format!("{prefix}-part-{}.png", i + 1));
+            Self::generate_qr(&part_payload, &path, colors, ec, symbology)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Encode `payload` with `crate::ur`'s fountain code and render each
+    /// frame as a QR code, assembled into one looping GIF at `path`. `frames`
+    /// must be at least as many as `payload` needs fragments for (see
+    /// `ur::encode`); `fps` controls playback speed, and `max_fragment_bytes`
+    /// bounds how much of the payload each frame's QR code has to carry
+    /// (more fragments means a smaller, easier-to-scan code per frame, at
+    /// the cost of needing more frames to see before reconstruction can
+    /// complete).
+    pub fn save_animated_qr(
+        payload: &str,
+        frames: u32,
+        fps: u32,
+        max_fragment_bytes: usize,
+        path: &Path,
+        colors: QrColors,
+    ) -> Result<()> {
+        let seq_length = payload.len().div_ceil(max_fragment_bytes.max(1)).max(1) as u32;
+        if frames < seq_length {
+            return Err(QRCryptError::QRGeneration(format!(
+                "--frames must be at least {seq_length} for this payload at the given \
+                 --max-fragment size, or reconstruction could never complete"
+            )));
+        }
+
+        let fountain_frames = crate::ur::encode(payload.as_bytes(), seq_length, frames);
+        let delay = image::Delay::from_numer_denom_ms(1000, fps.max(1));
+
+        let file = std::fs::File::create(path)?;
+        let mut encoder = image::codecs::gif::GifEncoder::new_with_speed(file, 10);
+        encoder
+            .set_repeat(image::codecs::gif::Repeat::Infinite)
+            .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+
+        for frame in fountain_frames {
+            let frame_payload = Self::encode_payload(&QRData::FountainFrame(frame))?;
+            let image = Self::render_qr_image(&frame_payload, colors, EcLevel::M)?;
+            encoder
+                .encode_frame(image::Frame::from_parts(image, 0, 0, delay))
+                .map_err(|e| QRCryptError::QRGeneration(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Build the human-readable summary written alongside a set of file parts.
+    pub fn generate_file_part_info_text(filenames: &[PathBuf]) -> String {
+        let mut text = String::new();
+        text.push_str("QRCrypt Encrypted File Info\n");
+        text.push_str("============================\n\n");
+        for (i, path) in filenames.iter().enumerate() {
+            text.push_str(&format!("Part {}: {}\n", i + 1, path.display()));
+        }
+        text
+    }
+
+    /// Build the human-readable summary written alongside an ssss-format
+    /// share set. See `shamir::parse_ssss_share` for what this format is and
+    /// isn't compatible with.
+    pub fn generate_ssss_info_text(
+        threshold: u8,
+        total: u8,
+        filenames: &[PathBuf],
+        secret: &[u8],
+    ) -> String {
+        let mut text = String::new();
+        text.push_str("QRCrypt ssss-format Secret Sharing Info\n");
+        text.push_str("=========================================\n\n");
+        text.push_str(&format!(
+            "Threshold: {threshold} of {total} shares required to reconstruct\n"
+        ));
+        text.push_str(&format!(
+            "Secret fingerprint: {}\n\n",
+            Self::secret_fingerprint(secret)
+        ));
+        for (i, path) in filenames.iter().enumerate() {
+            text.push_str(&format!("Share {}: {}\n", i + 1, path.display()));
+        }
+        text
+    }
+
+    /// Build the human-readable summary written alongside a SLIP-39 share set.
+    pub fn generate_slip39_info_text(
+        threshold: u8,
+        total: u8,
+        filenames: &[PathBuf],
+        secret: &[u8],
+    ) -> String {
+        let mut text = String::new();
+        text.push_str("QRCrypt SLIP-39 Secret Sharing Info\n");
+        text.push_str("====================================\n\n");
+        text.push_str(&format!(
+            "Threshold: {threshold} of {total} shares required to reconstruct\n"
+        ));
+        text.push_str(&format!(
+            "Secret fingerprint: {}\n\n",
+            Self::secret_fingerprint(secret)
+        ));
+        for (i, path) in filenames.iter().enumerate() {
+            text.push_str(&format!("Share {}: {}\n", i + 1, path.display()));
+        }
+        text
+    }
+}
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "gif", "tiff", "webp"];
+const TEXT_EXTENSIONS: &[&str] = &["json", "txt"];
+
+/// The shares collected by `QRScanner::scan_directory`, split by format
+/// since a custom Shamir share and a SLIP-39 mnemonic are reconstructed
+/// differently and a directory may not mix the two. `Custom`'s `parity`
+/// shares (if `split --parity` produced any) carry no information about the
+/// secret on their own; they only help rebuild a missing `ShamirShare`.
+#[derive(Debug, Clone)]
+pub enum ScannedShares {
+    Custom {
+        shares: Vec<ShamirShare>,
+        parity: Vec<ParityShare>,
+    },
+    Slip39(Vec<String>),
+}
+
+/// Accumulates shares, parity shares, and SLIP-39 mnemonics scanned from QR
+/// codes or read from plain share files, deduplicating by index (or full
+/// mnemonic text) and rejecting a set that mixes custom shares with SLIP-39
+/// or whose threshold/total disagree. Shared by `scan_directory`'s batch
+/// scan and `load_shares` (in main.rs) for an explicit `--shares` file
+/// list, so e.g. `reconstruct --shares a.json a.json` is rejected the same
+/// way a directory with the same card photographed twice would be.
+#[derive(Default)]
+pub struct ShareCollector {
+    shares: Vec<ShamirShare>,
+    parity: Vec<ParityShare>,
+    mnemonics: Vec<String>,
+    seen_indices: std::collections::HashSet<u8>,
+    seen_parity_indices: std::collections::HashSet<u8>,
+    seen_mnemonics: std::collections::HashSet<String>,
+}
+
+/// What happened when a scanned item was handed to `ShareCollector::add`,
+/// so a caller can decide how loudly to report a duplicate or a non-share.
+pub enum AddOutcome {
+    /// A new, unique share, parity share, or mnemonic.
+    Added,
+    /// The same share index was already present; silently dropped.
+    DuplicateShare(u8),
+    /// The same parity share index was already present; silently dropped.
+    DuplicateParity(u8),
+    /// The same mnemonic was already present; silently dropped.
+    DuplicateMnemonic,
+    /// Decoded fine, but isn't a share at all (e.g. an `Encrypted` payload).
+    NotAShare,
+}
+
+impl ShareCollector {
+    /// True if nothing has been added yet (everything scanned so far was
+    /// either unreadable or decoded to something other than a share).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many shares (custom, parity, or SLIP-39 mnemonic) have been
+    /// added so far.
+    pub fn len(&self) -> usize {
+        self.shares.len() + self.parity.len() + self.mnemonics.len()
+    }
+
+    pub fn add(&mut self, item: QRData) -> AddOutcome {
+        match item {
+            QRData::ShamirShare(share) => {
+                if self.seen_indices.insert(share.index) {
+                    self.shares.push(share);
+                    AddOutcome::Added
+                } else {
+                    AddOutcome::DuplicateShare(share.index)
+                }
+            }
+            QRData::ParityShare(p) => {
+                if self.seen_parity_indices.insert(p.index) {
+                    self.parity.push(p);
+                    AddOutcome::Added
+                } else {
+                    AddOutcome::DuplicateParity(p.index)
+                }
+            }
+            QRData::Slip39Share(mnemonic) => {
+                if self.seen_mnemonics.insert(mnemonic.clone()) {
+                    self.mnemonics.push(mnemonic);
+                    AddOutcome::Added
+                } else {
+                    AddOutcome::DuplicateMnemonic
+                }
+            }
+            QRData::Encrypted(_)
+            | QRData::FilePart(_)
+            | QRData::PayloadPart(_)
+            | QRData::FountainFrame(_)
+            | QRData::PayloadSignature(_)
+            | QRData::ShareVerification(_) => AddOutcome::NotAShare,
+        }
+    }
+
+    /// Finish collecting: reject a set mixing custom shares with SLIP-39,
+    /// or a flat (non-grouped) custom set whose threshold/total don't all
+    /// agree -- the same check `shamir::validate_shares` makes, just
+    /// enforced up front instead of waiting for reconstruction to fail deep
+    /// inside Lagrange interpolation. Grouped shares (`split --group`)
+    /// legitimately carry different thresholds/totals per group, so
+    /// they're exempt.
+    pub fn finish(self) -> Result<ScannedShares> {
+        if (!self.shares.is_empty() || !self.parity.is_empty()) && !self.mnemonics.is_empty() {
+            return Err(QRCryptError::InvalidFormat(
+                "cannot combine custom Shamir shares and SLIP-39 shares".to_string(),
+            ));
+        }
+        if !self.mnemonics.is_empty() {
+            return Ok(ScannedShares::Slip39(self.mnemonics));
+        }
+
+        if let Some(first) = self.shares.iter().find(|s| s.group_id.is_none()) {
+            for share in self.shares.iter().filter(|s| s.group_id.is_none()) {
+                if share.threshold != first.threshold || share.total != first.total {
+                    return Err(QRCryptError::InvalidFormat(format!(
+                        "share {} has a different threshold/total than the others",
+                        share.index
+                    )));
+                }
+            }
+        }
+
+        if self.shares.iter().any(|s| s.group_id.is_some()) {
+            crate::shamir::verify_grouped_set_consistency(&self.shares)?;
+        } else {
+            crate::shamir::verify_set_consistency(&self.shares)?;
+        }
+
+        Ok(ScannedShares::Custom {
+            shares: self.shares,
+            parity: self.parity,
+        })
+    }
+}
+
+/// Decodes QR codes from image files back into `QRData` envelopes.
+pub struct QRScanner;
+
+impl QRScanner {
+    /// Load a `QRData` envelope from `path`, whether it's a QR code image, a
+    /// plain JSON/text file holding the payload directly, or a PDF. The file
+    /// type is detected by extension, falling back to sniffing the file's
+    /// magic bytes when the extension is missing or unrecognized. A PDF with
+    /// more than one share on it only yields the first; use `scan_pdf` to
+    /// collect every share across all its pages.
+    pub fn scan_path(path: &Path) -> Result<QRData> {
+        match Self::scan_path_raw(path)? {
+            QRData::PayloadPart(part) => Self::resolve_payload_parts(path, part),
+            data => Ok(data),
+        }
+    }
+
+    fn scan_path_raw(path: &Path) -> Result<QRData> {
+        if Self::looks_like_pdf(path) {
+            Self::scan_pdf(path)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| QRCryptError::QRScan("no QR code found in PDF".to_string()))
+        } else if Self::looks_like_gif(path) {
+            Self::scan_animated(path)
+        } else if Self::looks_like_image(path)? {
+            Self::scan_file(path)
+        } else {
+            let content = std::fs::read_to_string(path)?;
+            Self::parse_qr_data(&content)
+        }
+    }
+
+    fn looks_like_gif(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
+    }
+
+    /// Given the first `PayloadPart` found at `path`, collect the rest of its
+    /// split from sibling files in the same directory (matched by
+    /// `payload_id`, so an unrelated split in the same folder isn't mixed
+    /// in), reassemble them in index order, verify the whole payload's
+    /// checksum, and re-parse the result as the `QRData` it was split from.
+    /// Mirrors `scan_file_parts`'s reassembly and missing-index reporting.
+    fn resolve_payload_parts(path: &Path, first: PayloadPart) -> Result<QRData> {
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+        let mut parts: std::collections::HashMap<u32, PayloadPart> =
+            std::collections::HashMap::new();
+        parts.insert(first.index, first);
+
+        if let Some(dir) = dir {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|p| p.is_file() && p != path)
+                .collect();
+            entries.sort();
+
+            for entry in entries {
+                if !Self::looks_like_image(&entry).unwrap_or(false) {
+                    continue;
+                }
+                if let Ok(QRData::PayloadPart(part)) = Self::scan_path_raw(&entry) {
+                    if part.payload_id == parts.values().next().expect("just inserted").payload_id {
+                        parts.insert(part.index, part);
+                    }
+                }
+            }
+        }
+
+        let total = parts.values().next().expect("just inserted").total;
+        let checksum = parts.values().next().expect("just inserted").checksum;
+        for part in parts.values() {
+            if part.total != total {
+                return Err(QRCryptError::QRScan(
+                    "payload parts disagree on the total part count".to_string(),
+                ));
+            }
+        }
+
+        let mut data = Vec::new();
+        for i in 0..total {
+            match parts.remove(&i) {
+                Some(part) => data.extend(part.data),
+                None => {
+                    return Err(QRCryptError::QRScan(format!(
+                        "missing payload part {} of {total}",
+                        i + 1
+                    )))
+                }
+            }
+        }
+
+        if checksum_of(&data) != checksum {
+            return Err(QRCryptError::QRScan(
+                "reassembled payload failed its checksum; a part may be corrupt".to_string(),
+            ));
+        }
+
+        let payload = String::from_utf8(data).map_err(|_| {
+            QRCryptError::QRScan("reassembled payload is not valid UTF-8".to_string())
+        })?;
+        Self::parse_qr_data(&payload)
+    }
+
+    fn looks_like_pdf(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+    }
+
+    fn looks_like_image(path: &Path) -> Result<bool> {
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            if IMAGE_EXTENSIONS.iter().any(|c| ext.eq_ignore_ascii_case(c)) {
+                return Ok(true);
+            }
+            if TEXT_EXTENSIONS.iter().any(|c| ext.eq_ignore_ascii_case(c)) {
+                return Ok(false);
+            }
+        }
+
+        // No recognized extension: sniff the magic bytes instead of guessing.
+        let mut header = [0u8; 8];
+        let n = std::fs::File::open(path)?.read(&mut header)?;
+        Ok(image::guess_format(&header[..n]).is_ok())
+    }
+
+    /// Scan a single QR code for interactive use (`decrypt` reading one card
+    /// at a time), as opposed to `scan_directory`'s batch mode. Also accepts
+    /// a PDF, in which case only its first page's code is read. A real scan
+    /// failure (no QR code found, corrupt image, unparsable payload)
+    /// propagates as an error; callers dispatch on the returned `QRData`
+    /// variant rather than guessing which kind of secret they got.
+    pub fn interactive_scan(path: &Path) -> Result<QRData> {
+        Self::scan_path(path)
+    }
+
+    /// Scan an image file for a single QR code and parse its payload.
+    pub fn scan_file(path: &Path) -> Result<QRData> {
+        let image = image::open(path)
+            .map_err(|e| QRCryptError::QRScan(e.to_string()))?
+            .to_luma8();
+        Self::decode_qr_from_luma(image)
+    }
+
+    /// Scan an already-rendered, in-memory image for a single QR code and
+    /// parse its payload, without writing it to disk first. Lets a
+    /// generator (e.g. `generate_with_logo`) verify its own output is
+    /// actually scannable before saving.
+    pub fn scan_from_image(image: &DynamicImage) -> Result<QRData> {
+        Self::decode_qr_from_luma(image.to_luma8())
+    }
+
+    /// Decode a single QR code from already-in-memory image bytes (e.g. a
+    /// camera SDK frame or a network upload), without writing them to disk
+    /// first. Thin wrapper around `image::load_from_memory` and
+    /// `scan_from_image` so library integrators don't need filesystem
+    /// access just to scan a code.
+    pub fn scan_from_bytes(data: &[u8]) -> Result<QRData> {
+        let image = image::load_from_memory(data).map_err(|e| QRCryptError::QRScan(e.to_string()))?;
+        Self::scan_from_image(&image)
+    }
+
+    /// Decode every QR code found in already-in-memory image bytes, the
+    /// in-memory counterpart to `scan_pdf`'s per-page collection. Silently
+    /// skips any grid that fails to decode or doesn't parse as a `QRData`
+    /// envelope, same as `scan_all_from_image`.
+    pub fn scan_all_from_bytes(data: &[u8]) -> Result<Vec<QRData>> {
+        let image = image::load_from_memory(data).map_err(|e| QRCryptError::QRScan(e.to_string()))?;
+        Ok(Self::scan_all_from_image(&image))
+    }
+
+    /// Scan an already-rendered, in-memory image for every QR code it
+    /// contains (like `scan_all_from_image`) and return the first one whose
+    /// variant matches `wanted` -- for a two-QR card (see
+    /// `QRGenerator::generate_card_qr_with_verify`) where a caller wants
+    /// specifically the share or specifically the `ShareVerification` code,
+    /// not whichever grid `rqrr` happens to detect first.
+    pub fn scan_from_image_as(image: &DynamicImage, wanted: QRDataType) -> Result<QRData> {
+        Self::scan_all_from_image(image)
+            .into_iter()
+            .find(|data| wanted.matches(data))
+            .ok_or_else(|| {
+                QRCryptError::QRScan(format!("no {wanted:?} QR code found in this image"))
+            })
+    }
+
+    /// Detect and decode a single QR code in an already-loaded grayscale
+    /// image. Shared by `scan_file` (which loads the image from a path) and
+    /// `scan_animated` (which reads each frame of a GIF straight from
+    /// memory). `rqrr` assumes dark modules on a light background; a code
+    /// rendered with `--invert` (or custom colors that happen to read dark
+    /// in grayscale) fails its first pass, so this retries once on an
+    /// inverted copy before giving up.
+    fn decode_qr_from_luma(image: image::GrayImage) -> Result<QRData> {
+        if let Ok(data) = Self::try_decode_grid(image.clone()) {
+            return Ok(data);
+        }
+        let mut inverted = image.clone();
+        image::imageops::invert(&mut inverted);
+        match Self::try_decode_grid(inverted) {
+            Ok(data) => Ok(data),
+            Err(e) => Self::decode_datamatrix_from_luma(&image).ok_or(e),
+        }
+    }
+
+    /// Fallback for `decode_qr_from_luma` once both QR attempts have failed:
+    /// try `crate::datamatrix::detect_and_decode` (a no-op unless qrcrypt was
+    /// built with `--features datamatrix`) and, if it finds something, parse
+    /// it the same way a QR code's decoded text would be parsed.
+    fn decode_datamatrix_from_luma(image: &image::GrayImage) -> Option<QRData> {
+        let bytes = crate::datamatrix::detect_and_decode(&DynamicImage::ImageLuma8(image.clone()))?;
+        let text = String::from_utf8(bytes).ok()?;
+        Self::parse_qr_data(&text).ok()
+    }
+
+    fn try_decode_grid(image: image::GrayImage) -> Result<QRData> {
+        let mut img = rqrr::PreparedImage::prepare(image);
+        let grids = img.detect_grids();
+        let grid = grids
+            .first()
+            .ok_or_else(|| QRCryptError::QRScan("no QR code found in image".to_string()))?;
+        let (_, content) = grid
+            .decode()
+            .map_err(|e| QRCryptError::QRScan(e.to_string()))?;
+        Self::parse_qr_data(&content)
+    }
+
+    /// Decode every frame of an `encrypt --animated` GIF at `path` for a
+    /// `FountainFrame`, accumulating distinct ones until `ur::decode` has
+    /// enough to reconstruct the original payload, then re-parse that
+    /// payload as the `QRData` it was split from. A frame a scanner can't
+    /// read is skipped with a warning rather than failing the whole scan,
+    /// same as `scan_file_parts` does for a damaged file part.
+    pub fn scan_animated(path: &Path) -> Result<QRData> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let decoder = image::codecs::gif::GifDecoder::new(file)
+            .map_err(|e| QRCryptError::QRScan(e.to_string()))?;
+
+        let mut frames = Vec::new();
+        for (i, frame) in image::AnimationDecoder::into_frames(decoder)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| QRCryptError::QRScan(e.to_string()))?
+            .into_iter()
+            .enumerate()
+        {
+            let luma = DynamicImage::ImageRgba8(frame.into_buffer()).to_luma8();
+            match Self::decode_qr_from_luma(luma) {
+                Ok(QRData::FountainFrame(frame)) => frames.push(frame),
+                Ok(_) => crate::utils::print_warning(&format!(
+                    "frame {} does not contain a fountain frame; skipping",
+                    i + 1
+                )),
+                Err(e) => crate::utils::print_warning(&format!(
+                    "could not read frame {}: {e}; skipping",
+                    i + 1
+                )),
+            }
+        }
+
+        let payload = crate::ur::decode(&frames)?;
+        let payload = String::from_utf8(payload).map_err(|_| {
+            QRCryptError::QRScan("reassembled animated payload is not valid UTF-8".to_string())
+        })?;
+        Self::parse_qr_data(&payload)
+    }
+
+    /// Parse the raw text decoded from a QR code into a `QRData` envelope.
+    /// Rejects input over `MAX_PARSE_INPUT_BYTES` and anything that's
+    /// neither the compact wire format nor JSON before touching
+    /// `serde_json`/`ciborium`, so garbage scanned or pasted content fails
+    /// fast with a friendly message instead of a raw parser error.
+    pub fn parse_qr_data(content: &str) -> Result<QRData> {
+        if content.len() > MAX_PARSE_INPUT_BYTES {
+            return Err(QRCryptError::InvalidFormat(format!(
+                "input is {} bytes, larger than qrcrypt expects from a QR code or raw payload \
+                 (max {MAX_PARSE_INPUT_BYTES} bytes)",
+                content.len()
+            )));
+        }
+        if let Ok(framed) = base45::decode(content) {
+            if let Some(cbor) = framed.strip_prefix(&COMPACT_WIRE_MAGIC) {
+                return ciborium::from_reader(cbor)
+                    .map_err(|e| QRCryptError::InvalidFormat(e.to_string()));
+            }
+        }
+        if !Self::looks_like_json(content) {
+            return Err(QRCryptError::InvalidFormat(
+                "this doesn't look like a QRCrypt code (not JSON and not the compact wire format)"
+                    .to_string(),
+            ));
+        }
+        match serde_json::from_str(content) {
+            Ok(data) => Ok(data),
+            Err(e) => Self::parse_raw_payload(content)
+                .ok_or_else(|| QRCryptError::InvalidFormat(e.to_string())),
+        }
+    }
+
+    /// Cheap sniff for "is this worth handing to `serde_json`": every real
+    /// qrcrypt JSON payload, enveloped or raw, is a `{...}` object, so
+    /// anything whose first non-whitespace byte isn't `{` is rejected up
+    /// front with a clearer message than whatever `serde_json` would have
+    /// produced for it.
+    fn looks_like_json(content: &str) -> bool {
+        content.trim_start().starts_with('{')
+    }
+
+    /// Fall back to a bare `EncryptedData`/`ShamirShare` object with no
+    /// `QRData` "type" tag at all, for `encrypt --raw-payload` output (see
+    /// `encode_raw_payload`) and other tools that write qrcrypt's inner
+    /// types directly. Tried in field-shape order -- `EncryptedData`'s
+    /// `hidden` field first, then `ShamirShare`'s `index` -- rather than a
+    /// tag, since raw payloads have none.
+    fn parse_raw_payload(content: &str) -> Option<QRData> {
+        serde_json::from_str::<EncryptedData>(content)
+            .ok()
+            .map(QRData::Encrypted)
+            .or_else(|| {
+                serde_json::from_str::<ShamirShare>(content)
+                    .ok()
+                    .map(QRData::ShamirShare)
+            })
+    }
+
+    /// Decode every QR code found in `image` into a `QRData`, silently
+    /// skipping any grid that fails to decode or doesn't parse as a
+    /// `QRData` envelope, since a scanned page may contain other graphics
+    /// rqrr mistakes for a code.
+    fn scan_all_from_image(image: &DynamicImage) -> Vec<QRData> {
+        let mut prepared = rqrr::PreparedImage::prepare(image.to_luma8());
+        prepared
+            .detect_grids()
+            .iter()
+            .filter_map(|grid| grid.decode().ok())
+            .filter_map(|(_, content)| Self::parse_qr_data(&content).ok())
+            .collect()
+    }
+
+    /// Rasterize every page of the PDF at `path` and collect the shares
+    /// found across all of them, for a card set archived as one PDF instead
+    /// of individual images. Pages without a readable QR code are skipped
+    /// quietly, since a cover page or blank page is normal. Requires
+    /// qrcrypt to be built with the `pdf` feature.
+    pub fn scan_pdf(path: &Path) -> Result<Vec<QRData>> {
+        let pages = crate::pdf::render_pages(path)?;
+        let found: Vec<QRData> = pages.iter().flat_map(Self::scan_all_from_image).collect();
+        if found.is_empty() {
+            return Err(QRCryptError::QRScan(format!(
+                "no QR code found in any page of {}",
+                path.display()
+            )));
+        }
+        Ok(found)
+    }
+
+    /// Scan every image file in `dir` for a share, skipping (with a warning)
+    /// anything that isn't an image, doesn't contain a QR code, or decodes
+    /// to something other than a share. Duplicate shares (same `index`, or
+    /// the same mnemonic for SLIP-39) are collapsed to one, since the same
+    /// share may get exported more than once (e.g. from a phone's camera
+    /// roll). A directory mixing custom and SLIP-39 shares is rejected
+    /// rather than silently picking one format.
+    pub fn scan_directory(dir: &Path) -> Result<ScannedShares> {
+        let collector = Self::scan_directory_until(dir, |_| false)?;
+
+        if collector.is_empty() {
+            return Err(QRCryptError::QRScan(
+                "no shares found in directory".to_string(),
+            ));
+        }
+
+        collector.finish()
+    }
+
+    /// Like `scan_directory`, but stops as soon as enough shares have been
+    /// collected to reconstruct the secret, instead of always scanning every
+    /// file in `dir`. The threshold isn't known up front, so this reads it
+    /// off the first (flat, non-grouped) share it finds and stops once that
+    /// many unique shares are in hand; grouped sets don't have one threshold
+    /// to check against, so those always scan the whole directory. See
+    /// `scan_directory_for_validation` for the opposite stopping condition.
+    pub fn scan_directory_for_reconstruction(dir: &Path) -> Result<ScannedShares> {
+        let collector = Self::scan_directory_until(dir, |collector| {
+            collector.shares.first().is_some_and(|first| {
+                first.group_id.is_none() && collector.shares.len() >= first.threshold as usize
+            })
+        })?;
+
+        if collector.is_empty() {
+            return Err(QRCryptError::QRScan(
+                "no shares found in directory".to_string(),
+            ));
+        }
+
+        collector.finish()
+    }
+
+    /// Like `scan_directory`, but for `validate --scan-dir --count`: scans
+    /// in the same sorted directory order and, if `count` is given, stops
+    /// once exactly that many shares have been collected rather than
+    /// reading every file. Unlike `scan_directory_for_reconstruction`, this
+    /// never stops early just because enough shares are present to
+    /// reconstruct -- validation is about confirming the shares the caller
+    /// asked for, not the smallest reconstructable subset, so it either
+    /// reaches `count` or reports exactly how many it actually found.
+    pub fn scan_directory_for_validation(
+        dir: &Path,
+        count: Option<usize>,
+    ) -> Result<ScannedShares> {
+        let collector = Self::scan_directory_until(dir, |collector| {
+            count.is_some_and(|n| collector.len() >= n)
+        })?;
+
+        if collector.is_empty() {
+            return Err(QRCryptError::QRScan(
+                "no shares found in directory".to_string(),
+            ));
+        }
+        if let Some(n) = count {
+            if collector.len() < n {
+                return Err(QRCryptError::QRScan(format!(
+                    "found only {} share(s) in {}, expected {n}",
+                    collector.len(),
+                    dir.display()
+                )));
+            }
+        }
+
+        collector.finish()
+    }
+
+    /// Shared scanning loop behind `scan_directory` and its `--count`/
+    /// threshold-early-exit variants: walk every file in `dir` in sorted
+    /// order, adding whatever's a share to `collector` and warning (not
+    /// failing) on anything that isn't, until either the directory is
+    /// exhausted or `should_stop` says enough has been collected.
+    fn scan_directory_until(
+        dir: &Path,
+        mut should_stop: impl FnMut(&ShareCollector) -> bool,
+    ) -> Result<ShareCollector> {
+        let mut collector = ShareCollector::default();
+
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            let found = if Self::looks_like_pdf(&path) {
+                Self::scan_pdf(&path)
+            } else if Self::looks_like_image(&path).unwrap_or(false) {
+                Self::scan_file(&path).map(|data| vec![data])
+            } else {
+                continue;
+            };
+
+            match found {
+                Ok(items) => {
+                    for item in items {
+                        if let AddOutcome::NotAShare = collector.add(item) {
+                            crate::utils::print_warning(&format!(
+                                "{} does not contain a share; skipping",
+                                path.display()
+                            ));
+                        }
+                    }
+                }
+                Err(e) => {
+                    crate::utils::print_warning(&format!(
+                        "could not read a share from {}: {e}; skipping",
+                        path.display()
+                    ));
+                }
+            }
+
+            if should_stop(&collector) {
+                break;
+            }
+        }
+
+        Ok(collector)
+    }
+
+    /// Scan every image file in `dir` for a `FilePart` and reassemble them
+    /// in index order, erroring out with the specific missing index rather
+    /// than a generic "not enough parts" message.
+    pub fn scan_file_parts(dir: &Path) -> Result<Vec<FilePart>> {
+        let mut parts: std::collections::HashMap<u32, FilePart> = std::collections::HashMap::new();
+
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            if !Self::looks_like_image(&path).unwrap_or(false) {
+                continue;
+            }
+            match Self::scan_file(&path) {
+                Ok(QRData::FilePart(part)) => {
+                    parts.insert(part.index, part);
+                }
+                Ok(_) => {
+                    crate::utils::print_warning(&format!(
+                        "{} does not contain a file part; skipping",
+                        path.display()
+                    ));
+                }
+                Err(e) => {
+                    crate::utils::print_warning(&format!(
+                        "could not read a part from {}: {e}; skipping",
+                        path.display()
+                    ));
+                }
+            }
+        }
+
+        if parts.is_empty() {
+            return Err(QRCryptError::QRScan(
+                "no file parts found in directory".to_string(),
+            ));
+        }
+
+        let total = parts.values().next().expect("just checked non-empty").total;
+        for part in parts.values() {
+            if part.total != total {
+                return Err(QRCryptError::QRScan(
+                    "file parts disagree on the total part count".to_string(),
+                ));
+            }
+        }
+
+        let mut ordered = Vec::with_capacity(total as usize);
+        for i in 0..total {
+            match parts.remove(&i) {
+                Some(part) => ordered.push(part),
+                None => {
+                    return Err(QRCryptError::QRScan(format!(
+                        "missing file part {} of {total}",
+                        i + 1
+                    )))
+                }
+            }
+        }
+        Ok(ordered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::shamir::ShamirShare;
+
+    #[test]
+    fn estimate_capacity_matches_known_qr_version_byte_capacities() {
+        // Version 1-M holds at most 14 bytes of byte-mode data, rendered as
+        // a 21x21 module code.
+        let v1_limit = QRGenerator::estimate_capacity(14, EcLevel::M);
+        assert_eq!(
+            v1_limit,
+            CapacityInfo {
+                fits: true,
+                version: Some(1),
+                modules: Some(21),
+            }
+        );
+
+        // One byte more no longer fits version 1 and needs version 2 (up to
+        // 26 bytes, 25x25 modules).
+        let v2_needed = QRGenerator::estimate_capacity(15, EcLevel::M);
+        assert_eq!(
+            v2_needed,
+            CapacityInfo {
+                fits: true,
+                version: Some(2),
+                modules: Some(25),
+            }
+        );
+
+        // Nothing fits beyond version 40's capacity at this EC level.
+        let too_big = QRGenerator::estimate_capacity(5000, EcLevel::M);
+        assert_eq!(
+            too_big,
+            CapacityInfo {
+                fits: false,
+                version: None,
+                modules: None,
+            }
+        );
+    }
+
+    #[test]
+    fn card_module_size_mm_matches_a_rendered_cards_actual_module_scale() {
+        let payload = "module size estimate payload";
+        let card = QRGenerator::generate_card_qr(
+            payload,
+            "QRCrypt",
+            "caption",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap();
+        let capacity = QRGenerator::estimate_capacity(payload.len(), EcLevel::M);
+        let modules = capacity.modules.unwrap();
+        let module_size_mm =
+            QRGenerator::card_module_size_mm(modules, QrColors::default().border, DEFAULT_CARD_DPI)
+                .unwrap();
+
+        // A whole QR module, rendered at this size, must be at least one
+        // pixel -- otherwise the card wouldn't have scanned at all.
+        assert!(module_size_mm > 0.0);
+        assert!(card.width() > 0);
+    }
+
+    #[test]
+    fn card_module_size_mm_is_none_when_the_payload_cant_fit_on_a_card() {
+        // Version 40 at EcLevel::H is too large to fit an 8.5cm x 5.5cm card
+        // at a low DPI no matter how small the scale -- this mirrors the
+        // "too large to fit on a card QR code" error `generate_card_qr`
+        // returns in the same situation.
+        assert_eq!(QRGenerator::card_module_size_mm(177, 4, 10), None);
+    }
+
+    #[test]
+    fn card_module_scale_fills_the_card_exactly_instead_of_flooring_to_a_whole_pixel() {
+        // 61 modules into 489 available pixels floors to 8px/module (488px
+        // used, 1px wasted) under integer division, but exactly fills at
+        // ~8.016px/module -- the fractional scale should reflect that,
+        // not the floored whole-pixel value.
+        let scale = QRGenerator::card_module_scale(1003, 649, 61);
+        assert!(
+            scale > 8.0,
+            "scale should be the exact fractional fit, not floored to 8.0: got {scale}"
+        );
+    }
+
+    #[test]
+    fn generate_card_qr_rejects_a_payload_that_would_render_illegibly_small() {
+        // EcLevel::H inflates a large payload into a QR with far more
+        // modules than an 8.5cm x 5.5cm card has room for at a legible
+        // size; this should fail with a descriptive error rather than
+        // silently rendering a code no scanner could read.
+        let payload = "x".repeat(1200);
+        let err = QRGenerator::generate_card_qr(
+            &payload,
+            "QRCrypt",
+            "caption",
+            QrColors::default(),
+            EcLevel::H,
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("pixels per module") && message.contains("larger card"),
+            "unexpected error message: {message}"
+        );
+    }
+
+    #[test]
+    fn generate_qr_honors_a_chosen_error_correction_level() {
+        // A payload with a known version-1 capacity at EcLevel::L (17 bytes)
+        // but not at EcLevel::H (9 bytes): at H it has to grow to version 2,
+        // one module bigger per side than L's version-1 code.
+        let payload = "x".repeat(17);
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-error-correction-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let low_ec_path = dir.join("low.png");
+        QRGenerator::generate_qr(&payload, &low_ec_path, QrColors::default(), EcLevel::L, Symbology::Qr)
+            .unwrap();
+        let low_ec_width = image::open(&low_ec_path).unwrap().width();
+
+        let high_ec_path = dir.join("high.png");
+        QRGenerator::generate_qr(&payload, &high_ec_path, QrColors::default(), EcLevel::H, Symbology::Qr)
+            .unwrap();
+        let high_ec_width = image::open(&high_ec_path).unwrap().width();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            high_ec_width > low_ec_width,
+            "EcLevel::H should need more modules than EcLevel::L for the same payload: \
+             L={low_ec_width}, H={high_ec_width}"
+        );
+    }
+
+    #[test]
+    fn generate_qr_at_a_high_ec_level_names_a_level_that_would_fit_instead() {
+        // 1300 bytes fits at EcLevel::L (max ~2953 bytes) but not at
+        // EcLevel::H (max ~1273 bytes), so forcing H should fail with a
+        // message pointing back at the levels that do fit.
+        let payload = "x".repeat(1300);
+        let err = QRGenerator::generate_qr(
+            &payload,
+            Path::new("/dev/null/unreachable.png"),
+            QrColors::default(),
+            EcLevel::H,
+            Symbology::Qr,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("1300 bytes"), "unexpected error: {err}");
+        assert!(err.contains("EcLevel::H"), "unexpected error: {err}");
+        assert!(err.contains('L'), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn generate_qr_physical_size_embeds_the_requested_dpi_and_true_size() {
+        let payload = "physical size test payload";
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-physical-size-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("physical.png");
+
+        QRGenerator::generate_qr_physical_size(
+            payload,
+            &path,
+            QrColors::default(),
+            EcLevel::M,
+            40.0,
+            300,
+            DEFAULT_MIN_MODULE_MM,
+        )
+        .unwrap();
+
+        let file = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let decoder = png::Decoder::new(file);
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        let dims = info.pixel_dims.expect("pHYs chunk should be present");
+        let expected_px_per_meter = (300.0_f64 / 0.0254).round() as u32;
+        assert_eq!(dims.xppu, expected_px_per_meter);
+        assert_eq!(dims.yppu, expected_px_per_meter);
+        assert_eq!(dims.unit, png::Unit::Meter);
+
+        // 40mm at 300 DPI should land close to the requested pixel width.
+        let expected_px = (40.0_f64 / 25.4 * 300.0).round() as u32;
+        let actual_px = info.width;
+        assert!(
+            actual_px.abs_diff(expected_px) <= expected_px / 10,
+            "expected roughly {expected_px}px wide, got {actual_px}"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn generate_qr_physical_size_rejects_a_size_that_would_render_below_the_module_floor() {
+        // A large, highly error-corrected payload crammed into a tiny
+        // physical size forces a module size below the legibility floor;
+        // this should fail with a descriptive error instead of silently
+        // writing an unscannable code.
+        let payload = "x".repeat(500);
+        let err = QRGenerator::generate_qr_physical_size(
+            &payload,
+            Path::new("/dev/null/unreachable.png"),
+            QrColors::default(),
+            EcLevel::H,
+            2.0,
+            300,
+            DEFAULT_MIN_MODULE_MM,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("--size-mm"), "unexpected error: {err}");
+        assert!(err.contains("minimum"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn qr_colors_rejects_low_contrast_pairs_but_accepts_the_default() {
+        QrColors::default().validate().unwrap();
+
+        // Two shades of gray close enough in luminance that a scanner
+        // could mistake one for the other.
+        let low_contrast = QrColors {
+            fg: Rgba([100, 100, 100, 255]),
+            bg: Rgba([120, 120, 120, 255]),
+            ..QrColors::default()
+        };
+        assert!(low_contrast.validate().is_err());
+
+        // Dark navy on a light cream background: distinct colors, but
+        // still enough luminance difference to scan.
+        let navy_on_cream = QrColors {
+            fg: Rgba([10, 20, 60, 255]),
+            bg: Rgba([245, 240, 230, 255]),
+            ..QrColors::default()
+        };
+        navy_on_cream.validate().unwrap();
+    }
+
+    #[test]
+    fn generate_card_qr_honors_a_custom_border_in_modules() {
+        let payload = "card border test payload";
+        let colors = QrColors {
+            border: 8,
+            ..QrColors::default()
+        };
+        let card = QRGenerator::generate_card_qr(
+            payload,
+            "QRCrypt",
+            "caption",
+            colors,
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap();
+
+        // Recompute the same module count/scale/position generate_card_qr
+        // derives internally, to work out where the QR code's own top-left
+        // finder pattern should land.
+        let code = QrCode::new(payload.as_bytes()).unwrap();
+        let qr_width = code
+            .render::<Luma<u8>>()
+            .quiet_zone(false)
+            .module_dimensions(1, 1)
+            .build()
+            .width();
+        let (card_width, card_height) = QRGenerator::card_pixel_dimensions(DEFAULT_CARD_DPI);
+        let modules_with_border = qr_width + 2 * colors.border;
+        let scale = (card_width.saturating_sub(80) as f32 / modules_with_border as f32)
+            .min(card_height.saturating_sub(160) as f32 / modules_with_border as f32);
+        let final_qr_size = (modules_with_border as f32 * scale).round() as u32;
+        let offset_x = (card_width - final_qr_size) / 2;
+        let offset_y = 90;
+        let border_px = (colors.border as f32 * scale).round() as u32;
+        let qr_x = offset_x + border_px;
+        let qr_y = offset_y + border_px;
+
+        // The finder pattern's solid outer border starts right at (qr_x,
+        // qr_y); scanning the same row from the card's left edge should
+        // find it there, with nothing but background before it.
+        let first_dark_x = (0..card.width())
+            .find(|&x| card.get_pixel(x, qr_y).0 != colors.bg.0)
+            .expect("card should contain QR modules");
+
+        assert_eq!(first_dark_x, qr_x);
+        assert_eq!(qr_x - offset_x, border_px);
+    }
+
+    #[test]
+    fn card_pixel_dimensions_and_card_rendering_scale_with_dpi() {
+        let (width_300, height_300) = QRGenerator::card_pixel_dimensions(300);
+        let (width_600, height_600) = QRGenerator::card_pixel_dimensions(600);
+        // Roughly double, modulo `cm_to_px`'s truncation to whole pixels.
+        assert!((width_600 as i64 - 2 * width_300 as i64).abs() <= 1);
+        assert!((height_600 as i64 - 2 * height_300 as i64).abs() <= 1);
+
+        let payload = "dpi test payload";
+        let card_300 = QRGenerator::generate_card_qr(
+            payload,
+            "QRCrypt",
+            "caption",
+            QrColors::default(),
+            EcLevel::M,
+            300,
+            None,
+        )
+        .unwrap();
+        let card_600 = QRGenerator::generate_card_qr(
+            payload,
+            "QRCrypt",
+            "caption",
+            QrColors::default(),
+            EcLevel::M,
+            600,
+            None,
+        )
+        .unwrap();
+        assert_eq!(card_300.width(), width_300);
+        assert_eq!(card_600.width(), width_600);
+        assert!(
+            card_600.width() > card_300.width(),
+            "a higher DPI card should render larger, not just the same size scaled by software"
+        );
+    }
+
+    #[test]
+    fn generate_card_back_is_the_same_size_as_the_front_and_draws_text() {
+        let front = QRGenerator::generate_card_qr(
+            "payload",
+            "QRCrypt",
+            "caption",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap();
+        let back = QRGenerator::generate_card_back(
+            2,
+            3,
+            Some("Stored in the kitchen safe."),
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(front.width(), back.width());
+        assert_eq!(front.height(), back.height());
+
+        let white = Rgba([255u8, 255, 255, 255]);
+        let has_dark_pixel = (0..back.width())
+            .any(|x| (0..back.height()).any(|y| back.get_pixel(x, y).0 != white.0));
+        assert!(has_dark_pixel, "back card should have drawn some text");
+    }
+
+    #[test]
+    fn generate_qr_embeds_readable_metadata_with_the_right_data_type_and_fingerprint() {
+        let share = crate::shamir::split_secret(b"metadata test secret", 2, 2)
+            .unwrap()
+            .remove(0);
+        let payload = QRGenerator::encode_payload(&QRData::ShamirShare(share)).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-png-metadata-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("share.png");
+        QRGenerator::generate_qr(&payload, &path, QrColors::default(), EcLevel::M, Symbology::Qr)
+            .unwrap();
+
+        let metadata = QRGenerator::read_png_metadata(&path).unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(metadata.data_type, "shamir_share");
+        assert_eq!(metadata.format_version, PNG_METADATA_FORMAT_VERSION);
+        assert_eq!(
+            metadata.payload_fingerprint,
+            hex::encode(Sha256::digest(payload.as_bytes()))
+        );
+    }
+
+    #[test]
+    fn every_module_style_and_fill_ratio_stays_scannable() {
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(
+            crate::crypto::Crypto::encrypt(b"module style test secret", "password123").unwrap(),
+        ))
+        .unwrap();
+
+        for style in [ModuleStyle::Square, ModuleStyle::Dot, ModuleStyle::Rounded] {
+            for fill_ratio in [1.0, 0.8, 0.5, MIN_FILL_RATIO] {
+                let colors = QrColors {
+                    module_style: style,
+                    fill_ratio,
+                    ..QrColors::default()
+                };
+                let image = QRGenerator::render_qr_image(&payload, colors, EcLevel::M).unwrap();
+                let scanned =
+                    QRScanner::scan_from_image(&DynamicImage::ImageRgba8(image)).unwrap();
+                let rescanned_payload = QRGenerator::encode_payload(&scanned).unwrap();
+                assert_eq!(
+                    rescanned_payload, payload,
+                    "{style:?} at fill_ratio {fill_ratio} didn't round-trip"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn card_module_style_and_fill_ratio_stays_scannable() {
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(
+            crate::crypto::Crypto::encrypt(b"card module style test secret", "password123")
+                .unwrap(),
+        ))
+        .unwrap();
+
+        for style in [ModuleStyle::Dot, ModuleStyle::Rounded] {
+            let colors = QrColors {
+                module_style: style,
+                fill_ratio: 0.8,
+                ..QrColors::default()
+            };
+            let card = QRGenerator::generate_card_qr(
+                &payload,
+                "QRCrypt",
+                "card module style test",
+                colors,
+                EcLevel::M,
+                DEFAULT_CARD_DPI,
+                None,
+            )
+            .unwrap();
+            let scanned = QRScanner::scan_from_image(&DynamicImage::ImageRgba8(card)).unwrap();
+            let rescanned_payload = QRGenerator::encode_payload(&scanned).unwrap();
+            assert_eq!(rescanned_payload, payload, "{style:?} card didn't round-trip");
+        }
+    }
+
+    #[test]
+    fn card_with_verify_renders_both_qr_codes_scannable_by_type() {
+        let shares = crate::shamir::split_secret(b"two qr card secret", 2, 3).unwrap();
+        let verify_info = crate::shamir::build_verification_info(b"two qr card secret", &shares);
+        let payload = QRGenerator::encode_payload(&QRData::ShamirShare(shares[0].clone())).unwrap();
+        let verify_payload =
+            QRGenerator::encode_payload(&QRData::ShareVerification(verify_info.clone())).unwrap();
+
+        let card = QRGenerator::generate_card_qr_with_verify(
+            &payload,
+            &verify_payload,
+            "QRCrypt",
+            "caption",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap();
+        let image = DynamicImage::ImageRgba8(card);
+
+        let scanned_share =
+            QRScanner::scan_from_image_as(&image, QRDataType::ShamirShare).unwrap();
+        match scanned_share {
+            QRData::ShamirShare(share) => assert_eq!(share.index, shares[0].index),
+            other => panic!("expected a ShamirShare, got {other:?}"),
+        }
+
+        let scanned_verify =
+            QRScanner::scan_from_image_as(&image, QRDataType::ShareVerification).unwrap();
+        match scanned_verify {
+            QRData::ShareVerification(info) => {
+                assert_eq!(info.secret_commitment, verify_info.secret_commitment)
+            }
+            other => panic!("expected a ShareVerification, got {other:?}"),
+        }
+
+        assert!(QRScanner::scan_from_image_as(&image, QRDataType::Encrypted).is_err());
+    }
+
+    #[test]
+    fn fill_ratio_outside_range_is_rejected() {
+        let too_small = QrColors {
+            fill_ratio: 0.01,
+            ..QrColors::default()
+        };
+        assert!(too_small.validate().is_err());
+
+        let too_large = QrColors {
+            fill_ratio: 1.5,
+            ..QrColors::default()
+        };
+        assert!(too_large.validate().is_err());
+    }
+
+    #[test]
+    fn read_png_metadata_tolerates_a_png_with_no_metadata_chunk() {
+        let dir = std::env::temp_dir()
+            .join(format!("qrcrypt-png-no-metadata-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plain.png");
+        let image: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        image.save(&path).unwrap();
+
+        let metadata = QRGenerator::read_png_metadata(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(metadata.is_none());
+    }
+
+    #[test]
+    fn generate_card_qr_draws_title_and_caption_text_with_no_system_font_installed() {
+        // `font_override: None` with no --font given; on a fontless system
+        // this used to render a card with no text at all, silently, since
+        // the old system-font search had nothing to fall back to.
+        let card = QRGenerator::generate_card_qr(
+            "fontless payload",
+            "QRCrypt",
+            "caption",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap();
+
+        let has_dark_pixel_in = |x0: u32, y0: u32, x1: u32, y1: u32| {
+            (x0..x1).any(|x| (y0..y1).any(|y| card.get_pixel(x, y).0 != QrColors::default().bg.0))
+        };
+
+        // The title "QRCrypt" is drawn at (40, 20) in a 72px scale; the
+        // caption is drawn 50px up from the bottom in a 24px scale. Both
+        // regions should contain non-background pixels if the embedded font
+        // actually rendered glyphs.
+        assert!(
+            has_dark_pixel_in(40, 20, 400, 92),
+            "title text should be drawn from the embedded font"
+        );
+        assert!(
+            has_dark_pixel_in(40, card.height() - 50, 400, card.height() - 26),
+            "caption text should be drawn from the embedded font"
+        );
+    }
+
+    #[test]
+    fn generate_card_qr_keeps_a_long_subtitle_clear_of_the_qr_quiet_zone() {
+        // `add_text_to_card` used to draw the caption at a fixed offset from
+        // the card's bottom edge regardless of its length; a subtitle this
+        // long at the old fixed 24pt scale would run up into the QR code's
+        // quiet zone instead of shrinking to fit above it.
+        let payload = "quiet zone test payload";
+        let long_subtitle = "x".repeat(120);
+        let colors = QrColors::default();
+        let card = QRGenerator::generate_card_qr(
+            payload,
+            "QRCrypt",
+            &long_subtitle,
+            colors,
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+        )
+        .unwrap();
+
+        // Recompute the same vertical band `generate_card_qr` reserves for
+        // the QR code plus its quiet zone, the way
+        // `generate_card_qr_honors_a_custom_border_in_modules` above does.
+        let qr_width = QrCode::with_error_correction_level(payload.as_bytes(), EcLevel::M)
+            .unwrap()
+            .render::<Luma<u8>>()
+            .quiet_zone(false)
+            .module_dimensions(1, 1)
+            .build()
+            .width();
+        let (card_width, card_height) = QRGenerator::card_pixel_dimensions(DEFAULT_CARD_DPI);
+        let modules_with_border = qr_width + 2 * colors.border;
+        let scale = (card_width.saturating_sub(80) as f32 / modules_with_border as f32)
+            .min(card_height.saturating_sub(160) as f32 / modules_with_border as f32);
+        let final_qr_size = (modules_with_border as f32 * scale).round() as u32;
+        let offset_x = (card_width - final_qr_size) / 2;
+        let qr_top = 90;
+        let qr_bottom = qr_top + final_qr_size;
+
+        // The subtitle is drawn starting at the card's left margin; if it
+        // overflowed into the quiet zone above, dark pixels would show up
+        // there, strictly left of where the QR code (and its quiet zone)
+        // begins.
+        let intrudes = (0..offset_x)
+            .any(|x| (qr_top..qr_bottom).any(|y| card.get_pixel(x, y).0 != colors.bg.0));
+        assert!(
+            !intrudes,
+            "a long --card-subtitle should not draw into the QR code's quiet zone"
+        );
+    }
+
+    #[test]
+    fn load_font_falls_back_to_the_embedded_font_and_warns_on_a_bad_override() {
+        let embedded = QRGenerator::load_font(None);
+        let fallback = QRGenerator::load_font(Some(Path::new("/no/such/font.ttf")));
+        assert_eq!(
+            embedded.glyph_id('Q'),
+            fallback.glyph_id('Q'),
+            "a bad --font path should fall back to the same embedded font"
+        );
+        assert_eq!(embedded.units_per_em(), fallback.units_per_em());
+    }
+
+    #[test]
+    fn scan_path_reads_a_share_straight_from_json() {
+        let share = ShamirShare {
+            version: 2,
+            index: 1,
+            threshold: 2,
+            total: 3,
+            data: vec![1, 2, 3],
+            checksum: None,
+            set_id: None,
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding: Default::default(),
+            signature: None,
+        };
+        let payload = QRGenerator::encode_payload(&QRData::ShamirShare(share.clone())).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-scan-path-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("share.json");
+        std::fs::write(&path, payload).unwrap();
+
+        match QRScanner::scan_path(&path).unwrap() {
+            QRData::ShamirShare(s) => assert_eq!(s.index, share.index),
+            other => panic!("expected a Shamir share, got {other:?}"),
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_qr_data_still_reads_legacy_pretty_json_payloads() {
+        let encrypted = crate::crypto::Crypto::encrypt(b"hello world", "password123").unwrap();
+        let legacy_json =
+            serde_json::to_string_pretty(&QRData::Encrypted(encrypted.clone())).unwrap();
+
+        match QRGenerator::encode_payload(&QRData::Encrypted(encrypted)) {
+            Ok(compact) => assert_ne!(compact, legacy_json, "should use the compact wire format"),
+            Err(e) => panic!("encode_payload failed: {e}"),
+        }
+
+        match QRScanner::parse_qr_data(&legacy_json).unwrap() {
+            QRData::Encrypted(_) => {}
+            other => panic!("expected encrypted data, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn raw_payload_round_trips_with_no_qrdata_envelope() {
+        let encrypted = crate::crypto::Crypto::encrypt(b"hello world", "password123").unwrap();
+        let raw = QRGenerator::encode_raw_payload(&encrypted).unwrap();
+
+        assert!(
+            serde_json::from_str::<QRData>(&raw).is_err(),
+            "a bare EncryptedData object should not parse as a tagged QRData"
+        );
+
+        match QRScanner::parse_qr_data(&raw).unwrap() {
+            QRData::Encrypted(decoded) => {
+                assert_eq!(decoded.hidden.ciphertext, encrypted.hidden.ciphertext)
+            }
+            other => panic!("expected encrypted data, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn raw_payload_also_auto_detects_a_bare_shamir_share() {
+        let shares = crate::shamir::split_secret(b"a secret", 2, 3).unwrap();
+        let raw = serde_json::to_string(&shares[0]).unwrap();
+
+        match QRScanner::parse_qr_data(&raw).unwrap() {
+            QRData::ShamirShare(share) => assert_eq!(share.index, shares[0].index),
+            other => panic!("expected a shamir share, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_qr_data_never_panics_on_malformed_input() {
+        let huge = "x".repeat(MAX_PARSE_INPUT_BYTES + 1);
+        let malformed = [
+            "",
+            " ",
+            "{",
+            "}",
+            "not json at all",
+            "[]",
+            "null",
+            "\"just a string\"",
+            "\u{0}\u{0}\u{0}",
+            "{\"type\":\"encrypted\"",
+            "QRCRYPT",
+            huge.as_str(),
+        ];
+
+        for input in malformed {
+            assert!(
+                QRScanner::parse_qr_data(input).is_err(),
+                "expected {input:?} (truncated) to fail to parse, not succeed"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_qr_data_rejects_oversized_input_before_parsing() {
+        let huge = "{".to_string() + &"x".repeat(MAX_PARSE_INPUT_BYTES + 1);
+
+        let err = QRScanner::parse_qr_data(&huge).unwrap_err().to_string();
+        assert!(err.contains("larger than qrcrypt expects"), "{err}");
+    }
+
+    #[test]
+    fn parse_qr_data_gives_a_friendly_error_for_obviously_non_json_input() {
+        let err = QRScanner::parse_qr_data("clearly not a qrcrypt payload")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("doesn't look like a QRCrypt code"), "{err}");
+    }
+
+    #[test]
+    fn compact_encoding_uses_meaningfully_fewer_modules_than_legacy_json() {
+        // A typical encrypted 12-word BIP39 phrase.
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                       abandon abandon art";
+        let encrypted =
+            crate::crypto::Crypto::encrypt(phrase.as_bytes(), "correct horse battery").unwrap();
+
+        let legacy_json =
+            serde_json::to_string_pretty(&QRData::Encrypted(encrypted.clone())).unwrap();
+        let compact = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+
+        fn modules_needed(data: &[u8]) -> usize {
+            (1..=40)
+                .find_map(|v| QrCode::with_version(data, Version::Normal(v), EcLevel::M).ok())
+                .expect("fits some QR version")
+                .width()
+        }
+        let legacy_modules = modules_needed(legacy_json.as_bytes());
+        let compact_modules = modules_needed(compact.as_bytes());
+
+        assert!(
+            compact_modules as f64 <= legacy_modules as f64 * 0.7,
+            "expected at least a 30% reduction in module count: legacy {legacy_modules}, \
+             compact {compact_modules}"
+        );
+    }
+
+    #[test]
+    fn scan_path_reads_an_encrypted_secret_straight_from_a_qr_image() {
+        let encrypted = crate::crypto::Crypto::encrypt(b"hello world", "password123").unwrap();
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-scan-path-image-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("secret.png");
+        QRGenerator::generate_qr(&payload, &path, QrColors::default(), EcLevel::M, Symbology::Qr)
+            .unwrap();
+
+        match QRScanner::scan_path(&path).unwrap() {
+            QRData::Encrypted(_) => {}
+            other => panic!("expected encrypted data, got {other:?}"),
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scan_path_still_decodes_an_inverted_white_on_black_qr() {
+        let encrypted = crate::crypto::Crypto::encrypt(b"hello world", "password123").unwrap();
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-scan-path-inverted-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("secret.png");
+        let inverted = QrColors {
+            fg: Rgba([255, 255, 255, 255]),
+            bg: Rgba([0, 0, 0, 255]),
+            ..QrColors::default()
+        };
+        QRGenerator::generate_qr(&payload, &path, inverted, EcLevel::M, Symbology::Qr).unwrap();
+
+        match QRScanner::scan_path(&path).unwrap() {
+            QRData::Encrypted(_) => {}
+            other => panic!("expected encrypted data, got {other:?}"),
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scan_from_bytes_reads_an_encrypted_secret_without_touching_disk() {
+        let encrypted = crate::crypto::Crypto::encrypt(b"hello world", "password123").unwrap();
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-scan-from-bytes-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("secret.png");
+        QRGenerator::generate_qr(&payload, &path, QrColors::default(), EcLevel::M, Symbology::Qr)
+            .unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        match QRScanner::scan_from_bytes(&bytes).unwrap() {
+            QRData::Encrypted(_) => {}
+            other => panic!("expected encrypted data, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn scan_all_from_bytes_finds_every_code_in_a_multi_share_image() {
+        let shares = crate::shamir::split_secret(b"hello world", 3, 5).unwrap();
+        let mut width = 0;
+        let mut height = 0;
+        let mut tiles = Vec::new();
+        for share in &shares {
+            let payload = QRGenerator::encode_payload(&QRData::ShamirShare(share.clone())).unwrap();
+            let code = QrCode::with_error_correction_level(payload.as_bytes(), EcLevel::M).unwrap();
+            let modules: ImageBuffer<Luma<u8>, Vec<u8>> = code.render::<Luma<u8>>().build();
+            width = width.max(modules.width());
+            height += modules.height();
+            tiles.push(modules);
+        }
+        let mut combined: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width, height);
+        let mut y_offset = 0;
+        for tile in &tiles {
+            for (x, y, pixel) in tile.enumerate_pixels() {
+                combined.put_pixel(x, y + y_offset, Rgba([pixel.0[0], pixel.0[0], pixel.0[0], 255]));
+            }
+            y_offset += tile.height();
+        }
+        let mut bytes: Vec<u8> = Vec::new();
+        DynamicImage::ImageRgba8(combined)
+            .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+
+        let found = QRScanner::scan_all_from_bytes(&bytes).unwrap();
+        assert_eq!(found.len(), shares.len());
+        for data in &found {
+            assert!(matches!(data, QRData::ShamirShare(_)));
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "pdf"))]
+    fn scan_path_on_a_pdf_reports_the_missing_feature_instead_of_misreading_it_as_json() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-scan-pdf-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shares.pdf");
+        std::fs::write(&path, b"%PDF-1.7\n...").unwrap();
+
+        let err = QRScanner::scan_path(&path).unwrap_err().to_string();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.contains("--features pdf"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn scan_directory_collects_shares_dedupes_and_skips_bad_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-scan-directory-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let share_one = ShamirShare {
+            version: 2,
+            index: 1,
+            threshold: 2,
+            total: 3,
+            data: vec![1, 2, 3],
+            checksum: None,
+            set_id: None,
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding: Default::default(),
+            signature: None,
+        };
+        let share_two = ShamirShare {
+            version: 2,
+            index: 2,
+            threshold: 2,
+            total: 3,
+            data: vec![4, 5, 6],
+            checksum: None,
+            set_id: None,
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding: Default::default(),
+            signature: None,
+        };
+
+        // Two images of the same share (simulating a re-scanned duplicate), one
+        // image of a different share, and one image that doesn't contain a QR
+        // code at all, all dropped into the same directory.
+        let payload_one =
+            QRGenerator::encode_payload(&QRData::ShamirShare(share_one.clone())).unwrap();
+        let payload_two =
+            QRGenerator::encode_payload(&QRData::ShamirShare(share_two.clone())).unwrap();
+        QRGenerator::generate_qr(
+            &payload_one,
+            &dir.join("share-1.png"),
+            QrColors::default(),
+            EcLevel::M,
+            Symbology::Qr,
+        )
+        .unwrap();
+        QRGenerator::generate_qr(
+            &payload_one,
+            &dir.join("share-1-copy.png"),
+            QrColors::default(),
+            EcLevel::M,
+            Symbology::Qr,
+        )
+        .unwrap();
+        QRGenerator::generate_qr(
+            &payload_two,
+            &dir.join("share-2.png"),
+            QrColors::default(),
+            EcLevel::M,
+            Symbology::Qr,
+        )
+        .unwrap();
+        image::RgbaImage::new(8, 8)
+            .save(dir.join("blank.png"))
+            .unwrap();
+
+        let scanned = QRScanner::scan_directory(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut shares = match scanned {
+            ScannedShares::Custom { shares, .. } => shares,
+            ScannedShares::Slip39(_) => panic!("expected custom Shamir shares"),
+        };
+        shares.sort_by_key(|s| s.index);
+
+        assert_eq!(shares.len(), 2);
+        assert_eq!(shares[0].index, share_one.index);
+        assert_eq!(shares[1].index, share_two.index);
+    }
+
+    #[test]
+    fn scan_directory_for_reconstruction_stops_early_but_validation_scans_the_full_count() {
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-scan-directory-stop-conditions-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let make_share = |index: u8| ShamirShare {
+            version: 2,
+            index,
+            threshold: 2,
+            total: 3,
+            data: vec![index; 3],
+            checksum: None,
+            set_id: None,
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding: Default::default(),
+            signature: None,
+        };
+        let shares = [make_share(1), make_share(2), make_share(3)];
+
+        // Filenames sort in index order, so both scans see share 1, then 2,
+        // then 3, and any difference in what they collect comes from their
+        // stopping condition, not scan order.
+        for (i, share) in shares.iter().enumerate() {
+            let payload = QRGenerator::encode_payload(&QRData::ShamirShare(share.clone())).unwrap();
+            QRGenerator::generate_qr(
+                &payload,
+                &dir.join(format!("share-{}.png", i + 1)),
+                QrColors::default(),
+                EcLevel::M,
+                Symbology::Qr,
+            )
+            .unwrap();
+        }
+
+        // Reconstruction only needs `threshold` (2) shares, so it should
+        // stop after share 1 and share 2, never reading share 3.
+        let for_reconstruction = QRScanner::scan_directory_for_reconstruction(&dir).unwrap();
+        let mut reconstruction_shares = match for_reconstruction {
+            ScannedShares::Custom { shares, .. } => shares,
+            ScannedShares::Slip39(_) => panic!("expected custom Shamir shares"),
+        };
+        reconstruction_shares.sort_by_key(|s| s.index);
+        assert_eq!(
+            reconstruction_shares.iter().map(|s| s.index).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        // Validation with --count 3 must keep going past the reconstruction
+        // threshold and collect all three.
+        let for_validation = QRScanner::scan_directory_for_validation(&dir, Some(3)).unwrap();
+        let mut validation_shares = match for_validation {
+            ScannedShares::Custom { shares, .. } => shares,
+            ScannedShares::Slip39(_) => panic!("expected custom Shamir shares"),
+        };
+        validation_shares.sort_by_key(|s| s.index);
+        assert_eq!(
+            validation_shares.iter().map(|s| s.index).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        // Asking for more shares than exist reports exactly how many were
+        // actually found instead of a generic "not enough" error.
+        let err = QRScanner::scan_directory_for_validation(&dir, Some(4))
+            .unwrap_err()
+            .to_string();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.contains("found only 3"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn save_and_scan_file_parts_round_trip_in_order() {
+        let ciphertext: Vec<u8> = (0..1200).map(|i| (i % 256) as u8).collect();
+        let salt = vec![1, 2, 3, 4];
+        let nonce = vec![5, 6, 7];
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-file-parts-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let filenames = QRGenerator::save_file_parts(
+            &ciphertext,
+            &salt,
+            &nonce,
+            &KdfParams::Argon2id,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+        )
+        .unwrap();
+        assert!(filenames.len() > 1);
+
+        let parts = QRScanner::scan_file_parts(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let reassembled: Vec<u8> = parts.iter().flat_map(|p| p.data.clone()).collect();
+        assert_eq!(reassembled, ciphertext);
+        assert_eq!(parts[0].salt, salt);
+        assert_eq!(parts[0].nonce, nonce);
+    }
+
+    #[test]
+    fn scan_file_parts_reports_which_index_is_missing() {
+        let ciphertext: Vec<u8> = (0..1200).map(|i| (i % 256) as u8).collect();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-missing-part-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let filenames = QRGenerator::save_file_parts(
+            &ciphertext,
+            &[1, 2, 3],
+            &[4, 5, 6],
+            &KdfParams::Argon2id,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+        )
+        .unwrap();
+        assert!(filenames.len() > 2);
+        std::fs::remove_file(&filenames[1]).unwrap();
+
+        let err = QRScanner::scan_file_parts(&dir).unwrap_err().to_string();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            err.contains("missing file part 2"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn save_payload_auto_writes_a_single_file_when_the_payload_fits() {
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-payload-fits-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let share = crate::shamir::split_secret(b"tiny secret", 2, 2)
+            .unwrap()
+            .remove(0);
+        let payload = QRGenerator::encode_payload(&QRData::ShamirShare(share.clone())).unwrap();
+        let paths = QRGenerator::save_payload_auto(
+            &payload,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_MAX_QR_VERSION,
+            Symbology::Qr,
+        )
+        .unwrap();
+        assert_eq!(paths, vec![dir.join("test.png")]);
+
+        let scanned = QRScanner::scan_path(&paths[0]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        match scanned {
+            QRData::ShamirShare(s) => assert_eq!(s.index, share.index),
+            other => panic!("expected a ShamirShare, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn save_and_scan_payload_parts_round_trip_an_oversized_payload() {
+        let secret = "a".repeat(4000);
+        let encrypted = crate::crypto::Crypto::encrypt(secret.as_bytes(), "password").unwrap();
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+        assert!(!QRGenerator::estimate_capacity(payload.len(), EcLevel::M).fits);
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-payload-parts-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = QRGenerator::save_payload_auto(
+            &payload,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_MAX_QR_VERSION,
+            Symbology::Qr,
+        )
+        .unwrap();
+        assert!(paths.len() > 1);
+
+        let scanned = QRScanner::scan_path(&paths[0]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        match scanned {
+            QRData::Encrypted(data) => {
+                let decrypted = crate::crypto::Crypto::decrypt(&data, "password").unwrap();
+                assert_eq!(decrypted.as_slice(), secret.as_bytes());
+            }
+            other => panic!("expected an Encrypted payload, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn resolve_payload_parts_reports_which_index_is_missing() {
+        let secret = "b".repeat(4000);
+        let encrypted = crate::crypto::Crypto::encrypt(secret.as_bytes(), "password").unwrap();
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-missing-payload-part-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = QRGenerator::save_payload_auto(
+            &payload,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_MAX_QR_VERSION,
+            Symbology::Qr,
+        )
+        .unwrap();
+        assert!(paths.len() > 2);
+        std::fs::remove_file(&paths[1]).unwrap();
+
+        let err = QRScanner::scan_path(&paths[0]).unwrap_err().to_string();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            err.contains("missing payload part 2"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn save_payload_auto_splits_when_capped_below_the_version_the_payload_needs() {
+        let share = crate::shamir::split_secret(b"tiny secret", 2, 2)
+            .unwrap()
+            .remove(0);
+        let payload = QRGenerator::encode_payload(&QRData::ShamirShare(share.clone())).unwrap();
+        assert!(QRGenerator::estimate_capacity(payload.len(), EcLevel::M).fits);
+
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-payload-capped-version-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let paths = QRGenerator::save_payload_auto(
+            &payload,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            1,
+            Symbology::Qr,
+        )
+        .unwrap();
+        assert!(
+            paths.len() > 1,
+            "expected a cap of version 1 to force a split"
+        );
+
+        let scanned = QRScanner::scan_path(&paths[0]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        match scanned {
+            QRData::ShamirShare(s) => assert_eq!(s.index, share.index),
+            other => panic!("expected a ShamirShare, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn compose_sheet_paginates_and_draws_cut_guides_between_cells() {
+        let shares = crate::shamir::split_secret(b"sheet test secret", 3, 5).unwrap();
+        let dir = std::env::temp_dir().join(format!("qrcrypt-sheet-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let card_paths = QRGenerator::save_shamir_card_qrs(
+            &shares,
+            &dir,
+            "qrcrypt",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let captions: Vec<String> = shares.iter().map(QRGenerator::shamir_caption).collect();
+        let images: Vec<(DynamicImage, String)> = card_paths
+            .iter()
+            .zip(&captions)
+            .map(|(path, caption)| (image::open(path).unwrap(), caption.clone()))
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let pages = QRGenerator::compose_sheet(&images, PaperSize::A4, 72).unwrap();
+        assert!(
+            pages.len() > 1,
+            "expected 5 full-size cards at 72 DPI not to fit a single A4 page"
+        );
+
+        let page = &pages[0];
+        let has_guide_pixel = page
+            .pixels()
+            .any(|p| *p == QRGenerator::SHEET_CUT_GUIDE_COLOR);
+        assert!(has_guide_pixel, "expected at least one dashed cut guide pixel");
+    }
+
+    #[test]
+    fn save_and_scan_animated_qr_round_trips_a_payload() {
+        let secret = "animated qr round trip test secret";
+        let encrypted = crate::crypto::Crypto::encrypt(secret.as_bytes(), "password").unwrap();
+        let payload = QRGenerator::encode_payload(&QRData::Encrypted(encrypted)).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-animated-qr-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let gif_path = dir.join("out.gif");
+
+        QRGenerator::save_animated_qr(&payload, 20, 4, 100, &gif_path, QrColors::default())
+            .unwrap();
+        let scanned = QRScanner::scan_path(&gif_path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        match scanned {
+            QRData::Encrypted(data) => {
+                let decrypted = crate::crypto::Crypto::decrypt(&data, "password").unwrap();
+                assert_eq!(decrypted.as_slice(), secret.as_bytes());
+            }
+            other => panic!("expected an Encrypted payload, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn save_animated_qr_rejects_too_few_frames_for_the_payload() {
+        let dir = std::env::temp_dir().join(format!(
+            "qrcrypt-animated-qr-too-few-frames-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let gif_path = dir.join("out.gif");
+
+        let err = QRGenerator::save_animated_qr(
+            &"x".repeat(1000),
+            2,
+            4,
+            100,
+            &gif_path,
+            QrColors::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            err.contains("--frames must be at least"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn labeled_shares_get_a_slugified_filename_suffix_and_unlabeled_ones_dont() {
+        let mut shares = crate::shamir::split_secret(b"abc", 2, 2).unwrap();
+        shares[0].label = Some("Mom's House".to_string());
+
+        let dir = std::env::temp_dir().join(format!("qrcrypt-label-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let filenames = QRGenerator::save_shamir_card_qrs(
+            &shares,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            filenames[0].file_name().unwrap().to_str().unwrap(),
+            "test-share-1-mom-s-house.png"
+        );
+        assert_eq!(
+            filenames[1].file_name().unwrap().to_str().unwrap(),
+            "test-share-2.png"
+        );
+    }
+
+    #[test]
+    fn save_shamir_card_qrs_errs_cleanly_on_an_oversized_share_instead_of_panicking() {
+        let mut shares = crate::shamir::split_secret(b"abc", 2, 2).unwrap();
+        // Too big to fit any card QR code's capacity, regardless of error
+        // correction level; `save_shamir_card_qrs` should surface this as an
+        // `Err` (from `encode_payload`/`generate_card_qr`) and leave no
+        // partial output, not panic partway through writing the share PNGs.
+        shares[0].data = vec![0u8; 10_000];
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-oversized-share-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let result = QRGenerator::save_shamir_card_qrs(
+            &shares,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            0,
+            "the other share still renders fine in parallel, but its files should be \
+             cleaned up once the oversized share fails the batch"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_shamir_card_qrs_cleans_up_every_share_when_one_in_the_middle_fails() {
+        let mut shares = crate::shamir::split_secret(b"cleanup test secret", 3, 5).unwrap();
+        shares[2].data = vec![0u8; 10_000];
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-mid-batch-failure-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let err = QRGenerator::save_shamir_card_qrs(
+            &shares,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(
+            err.contains(&format!("share {}", shares[2].index)),
+            "the error should name the failing share's id: {err}"
+        );
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            0,
+            "the four shares that rendered fine should be cleaned up alongside the one that failed"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_shamir_card_qrs_keeps_filenames_in_share_order_regardless_of_parallel_completion() {
+        let shares = crate::shamir::split_secret(b"ordering test secret", 5, 20).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("qrcrypt-order-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let filenames = QRGenerator::save_shamir_card_qrs(
+            &shares,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            DEFAULT_CARD_DPI,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(filenames.len(), shares.len());
+        for (share, filename) in shares.iter().zip(&filenames) {
+            assert_eq!(
+                filename.file_name().unwrap().to_str().unwrap(),
+                format!("test-share-{}.png", share.index),
+                "rendering in parallel shouldn't reorder the returned paths"
+            );
+        }
+    }
+
+    #[test]
+    fn shamir_caption_includes_index_total_threshold_set_id_and_label() {
+        let mut shares = crate::shamir::split_secret(b"caption test secret", 2, 3).unwrap();
+        shares[0].label = Some("cold storage".to_string());
+
+        let captioned = QRGenerator::shamir_caption(&shares[0]);
+        assert!(captioned.starts_with("Share 1 of 3 - threshold 2"));
+        assert!(captioned.contains("cold storage"));
+        if let Some(set_id) = shares[0].set_id {
+            assert!(captioned.contains(&format!("{set_id:08x}")));
+        }
+
+        let unlabeled = QRGenerator::shamir_caption(&shares[1]);
+        assert!(!unlabeled.contains("cold storage"));
+    }
+
+    #[test]
+    fn generate_captioned_qr_draws_the_caption_below_the_qr_code_without_shrinking_it() {
+        let qr_alone =
+            QRGenerator::render_qr_image("plain qr payload", QrColors::default(), EcLevel::M)
+                .unwrap();
+        let captioned = QRGenerator::generate_captioned_qr(
+            "plain qr payload",
+            "Share 1 of 3 - threshold 2",
+            QrColors::default(),
+            EcLevel::M,
+            None,
+            Symbology::Qr,
+        )
+        .unwrap();
+
+        assert_eq!(
+            captioned.width(),
+            qr_alone.width(),
+            "the caption shouldn't change the QR code's own size"
+        );
+        assert!(
+            captioned.height() > qr_alone.height(),
+            "the canvas should grow to make room for the caption"
+        );
+
+        for y in 0..qr_alone.height() {
+            for x in 0..qr_alone.width() {
+                assert_eq!(
+                    captioned.get_pixel(x, y),
+                    qr_alone.get_pixel(x, y),
+                    "the QR code itself shouldn't be touched by the caption"
+                );
+            }
+        }
+        let has_dark_pixel_below_qr = (0..captioned.width()).any(|x| {
+            (qr_alone.height()..captioned.height())
+                .any(|y| captioned.get_pixel(x, y).0 != QrColors::default().bg.0)
+        });
+        assert!(
+            has_dark_pixel_below_qr,
+            "the caption should be drawn somewhere below the QR code"
+        );
+    }
+
+    #[test]
+    fn save_shamir_qrs_captioned_writes_one_plain_qr_per_share() {
+        let shares = crate::shamir::split_secret(b"captioned qr test secret", 2, 3).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("qrcrypt-captioned-qrs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let filenames = QRGenerator::save_shamir_qrs_captioned(
+            &shares,
+            &dir,
+            "test",
+            QrColors::default(),
+            EcLevel::M,
+            None,
+            None,
+            Symbology::Qr,
+        )
+        .unwrap();
+
+        assert_eq!(filenames.len(), shares.len());
+        for path in &filenames {
+            assert!(path.exists());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn info_text_lists_each_shares_hash_and_the_reconstruct_command() {
+        let shares = crate::shamir::split_secret(b"info text test secret", 2, 3).unwrap();
+        let filenames: Vec<PathBuf> = (1..=3)
+            .map(|i| PathBuf::from(format!("qrcrypt-share-{i}.png")))
+            .collect();
+
+        let text =
+            QRGenerator::generate_info_text(&shares, &filenames, 0, b"info text test secret");
+
+        for share in &shares {
+            let fingerprint = QRGenerator::share_fingerprint(share).unwrap();
+            assert!(
+                text.contains(&format!("SHA-256: {fingerprint}")),
+                "missing hash for share {}",
+                share.index
+            );
+        }
+        assert!(text.contains("Created: "));
+        assert!(text.contains(
+            "qrcrypt reconstruct --shares qrcrypt-share-1.png --shares qrcrypt-share-2.png"
+        ));
+    }
+
+    #[test]
+    fn generate_with_logo_roundtrips_through_scan_file() {
+        // A big-enough share data payload to land on a QR version whose
+        // error-correction budget comfortably covers a centered logo at
+        // DEFAULT_LOGO_MAX_FRACTION.
+        let share = ShamirShare {
+            version: 2,
+            index: 1,
+            threshold: 2,
+            total: 3,
+            data: vec![7; 150],
+            checksum: None,
+            set_id: None,
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding: Default::default(),
+            signature: None,
+        };
+        let payload = QRGenerator::encode_payload(&QRData::ShamirShare(share)).unwrap();
+        let logo =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(40, 40, Rgba([200, 30, 30, 255])));
+
+        let path =
+            std::env::temp_dir().join(format!("qrcrypt-logo-test-{}.png", std::process::id()));
+        QRGenerator::generate_with_logo(
+            &payload,
+            &logo,
+            &path,
+            QrColors::default(),
+            DEFAULT_LOGO_MAX_FRACTION,
+        )
+        .unwrap();
+
+        let scanned = QRScanner::scan_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        match scanned.unwrap() {
+            QRData::ShamirShare(s) => assert_eq!(s.index, 1),
+            other => panic!("unexpected payload shape: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn generate_with_logo_rejects_a_logo_too_big_for_the_error_budget() {
+        // A version-1 code's EcLevel::H budget is a handful of modules;
+        // LOGO_MAX_MODULE_FRACTION of even that tiny code exceeds it.
+        let logo = DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 10, Rgba([0, 0, 0, 255])));
+        let path = std::env::temp_dir().join(format!(
+            "qrcrypt-logo-reject-test-{}.png",
+            std::process::id()
+        ));
+
+        let err = QRGenerator::generate_with_logo(
+            "x",
+            &logo,
+            &path,
+            QrColors::default(),
+            DEFAULT_LOGO_MAX_FRACTION,
+        )
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("error-correction budget"),
+            "unexpected error: {err}"
+        );
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use zeroize::ZeroizeOnDrop;
+
+/// A secret value (seed phrase, passphrase, raw key material) that is wiped
+/// from memory when dropped.
+#[derive(Clone, ZeroizeOnDrop)]
+pub struct SecretData {
+    bytes: Vec<u8>,
+}
+
+impl SecretData {
+    pub fn from_string(s: String) -> Self {
+        SecretData {
+            bytes: s.into_bytes(),
+        }
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        SecretData { bytes }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Interpret the secret as UTF-8 text, if it is valid.
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.bytes).ok()
+    }
+}
+
+impl std::fmt::Debug for SecretData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecretData(REDACTED, {} bytes)", self.bytes.len())
+    }
+}
+
+/// Prefix marking a `SecretData` payload as a `Keyring` of several named
+/// secrets rather than a single plain one, so `Keyring::parse` doesn't
+/// mistake an unrelated secret that happens to parse as JSON for one.
+const KEYRING_MAGIC: &[u8] = b"qrcrypt:keyring:v1:";
+
+/// Several named secrets (seed phrases, passwords, notes) encrypted
+/// together under one password instead of one QR code each. Entries keep
+/// insertion order rather than being sorted, since that's the order a user
+/// added them in and expects to see them listed back.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Keyring {
+    entries: Vec<(String, String)>,
+}
+
+impl Keyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry, replacing any existing one with the same name.
+    pub fn insert(&mut self, name: String, secret: String) {
+        match self.entries.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = secret,
+            None => self.entries.push((name, secret)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, secret)| secret.as_str())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Pack this keyring into bytes suitable for `Crypto::encrypt`, prefixed
+    /// so `Keyring::parse` can tell a decrypted keyring apart from a plain
+    /// secret.
+    pub fn bundle(&self) -> Vec<u8> {
+        let mut bundle = KEYRING_MAGIC.to_vec();
+        bundle.extend_from_slice(
+            &serde_json::to_vec(&self.entries).expect("Vec<(String, String)> always serializes"),
+        );
+        bundle
+    }
+
+    /// Recover a `Keyring` from a decrypted payload, if it was produced by
+    /// `bundle`. Returns `None` for a plain secret that was never bundled.
+    pub fn parse(payload: &[u8]) -> Option<Keyring> {
+        let rest = payload.strip_prefix(KEYRING_MAGIC)?;
+        let entries = serde_json::from_slice(rest).ok()?;
+        Some(Keyring { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyring_round_trips_through_bundle_and_parse() {
+        let mut keyring = Keyring::new();
+        keyring.insert("cold storage".to_string(), "zoo zoo zoo ... wrong".to_string());
+        keyring.insert("exchange 2fa".to_string(), "backup code 42".to_string());
+
+        let bundle = keyring.bundle();
+        let parsed = Keyring::parse(&bundle).unwrap();
+
+        assert_eq!(
+            parsed.names().collect::<Vec<_>>(),
+            vec!["cold storage", "exchange 2fa"]
+        );
+        assert_eq!(parsed.get("cold storage"), Some("zoo zoo zoo ... wrong"));
+        assert_eq!(parsed.get("nonexistent"), None);
+    }
+
+    #[test]
+    fn keyring_insert_replaces_an_existing_entry_in_place() {
+        let mut keyring = Keyring::new();
+        keyring.insert("a".to_string(), "first".to_string());
+        keyring.insert("b".to_string(), "second".to_string());
+        keyring.insert("a".to_string(), "replaced".to_string());
+
+        assert_eq!(keyring.get("a"), Some("replaced"));
+        assert_eq!(keyring.names().collect::<Vec<_>>(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn a_plain_secret_is_not_mistaken_for_a_keyring() {
+        assert!(Keyring::parse(b"just a plain secret").is_none());
+    }
+}
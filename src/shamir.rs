@@ -0,0 +1,2336 @@
+use rand::RngCore;
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use crate::crypto::{Crypto, EncryptedData};
+use crate::error::{QRCryptError, Result};
+use crate::secret::SecretData;
+
+/// The current `ShamirShare` encoding version. Version 1 shares (produced
+/// before `data` was base64-encoded) serialized `data` as a raw JSON array
+/// of numbers, which ballooned a 24-word phrase to kilobytes of JSON — too
+/// big to scan reliably as a QR code. `reconstruct_secret` still reads both.
+const CURRENT_SHARE_VERSION: u8 = 2;
+
+fn default_share_version() -> u8 {
+    1
+}
+
+/// Groups of `ShamirShare::version` values whose wire encodings differ only
+/// in incidental framing, not in the underlying secret-sharing math --
+/// shares from any two versions in the same group can be combined during
+/// reconstruction. Versions 1 and 2 differ only in how `data` is framed
+/// (raw JSON array vs. base64), so they're grouped together; a future
+/// version that changed the actual GF(256)/GF(65536) encoding would need
+/// its own group instead of being added here.
+const VERSION_COMPAT_GROUPS: &[&[u8]] = &[&[1, 2]];
+
+/// Which compatibility group (an index into `VERSION_COMPAT_GROUPS`)
+/// `version` belongs to, or `None` if it isn't a version this build knows
+/// about.
+fn version_compat_group(version: u8) -> Option<usize> {
+    VERSION_COMPAT_GROUPS
+        .iter()
+        .position(|group| group.contains(&version))
+}
+
+/// Whether shares with encoding versions `a` and `b` are safe to combine
+/// during reconstruction. Versions in the same `VERSION_COMPAT_GROUPS`
+/// entry are compatible by construction; an unrecognized version (from a
+/// future build) is only compatible with an exact match, since there's no
+/// way to know what it changed.
+fn versions_compatible(a: u8, b: u8) -> bool {
+    match (version_compat_group(a), version_compat_group(b)) {
+        (Some(ga), Some(gb)) => ga == gb,
+        _ => a == b,
+    }
+}
+
+/// Which finite field a share's `data` is interpreted over. `Gf256` (the
+/// default) evaluates the sharing polynomial one secret byte at a time;
+/// `Gf65536` batches two bytes per polynomial element instead, for
+/// `split --share-encoding gf65536`. Both produce the same `data` byte
+/// length for a given secret -- this changes how many field elements the
+/// polynomial math works over, not how much space a share takes up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareEncoding {
+    #[default]
+    Gf256,
+    Gf65536,
+}
+
+impl ShareEncoding {
+    fn is_gf256(&self) -> bool {
+        *self == ShareEncoding::Gf256
+    }
+}
+
+/// One share of a Shamir secret-sharing split. `data` holds one GF(256)
+/// y-value per secret byte, at x-coordinate `index`. Shares missing a
+/// `version` field (from before this field existed) are treated as version 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShamirShare {
+    #[serde(default = "default_share_version")]
+    pub version: u8,
+    pub index: u8,
+    pub threshold: u8,
+    pub total: u8,
+    #[serde(with = "share_data_encoding")]
+    pub data: Vec<u8>,
+    /// A checksum of `data`, so a share damaged by a bad scan is reported
+    /// clearly instead of producing a cryptic Lagrange-interpolation result
+    /// or garbage secret. Absent on shares from before this field existed,
+    /// in which case there's nothing to check it against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<u32>,
+    /// A random fingerprint shared by every share from one `split_secret`
+    /// call, so shares from two different splits with the same threshold
+    /// and total can't be silently combined into one (reconstructing to
+    /// garbage). Absent on shares from before this field existed, in which
+    /// case there's nothing to compare it against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_id: Option<u32>,
+    /// Present when `split --share-passwords` encrypted this share's `data`
+    /// with a per-holder password (in which case `data` is empty): the
+    /// share is useless to whoever finds its QR code without also knowing
+    /// that password. `checksum` was computed before encryption and only
+    /// verifies again once `decrypt_share` restores the plaintext.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptedData>,
+    /// A human-friendly name for whoever holds this share (e.g. "mom"), set
+    /// via `split --labels`. Purely informational: it has no bearing on
+    /// reconstruction, and shares with and without a label interoperate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// A free-form note about this share, e.g. where it's kept. Like
+    /// `label`, purely informational.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Which group (0-indexed) this share belongs to, for shares produced by
+    /// `split_secret_with_groups` (e.g. "family" vs. "lawyers"). `threshold`
+    /// and `total` above describe only this group; `group_threshold` and
+    /// `group_count` describe the outer structure across all groups. Absent
+    /// for shares from a flat `split_secret` call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<u8>,
+    /// How many groups must each have enough shares present to reconstruct
+    /// the secret. Shared by every share from one `split_secret_with_groups`
+    /// call, regardless of which group they're in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_threshold: Option<u8>,
+    /// How many groups exist in total. Shared by every share from one
+    /// `split_secret_with_groups` call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_count: Option<u8>,
+    /// Which finite field `data` is interpreted over; see `ShareEncoding`.
+    /// Absent (and assumed `Gf256`) on shares from before this field existed.
+    #[serde(default, skip_serializing_if = "ShareEncoding::is_gf256")]
+    pub encoding: ShareEncoding,
+    /// A detached Ed25519 signature proving this share came from whoever
+    /// holds the matching secret key, set by `split --sign-key`. Checked by
+    /// `validate --verify-key`; see `crate::signing`. Absent on shares split
+    /// without --sign-key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<crate::signing::ShareSignature>,
+}
+
+impl ShamirShare {
+    /// Confirm `data` matches `checksum`, if one is present. Covers only the
+    /// share payload, not metadata like `index` or the filename it was
+    /// scanned from, which can change (e.g. a renamed file) without the
+    /// share itself being damaged.
+    pub fn verify_checksum(&self) -> Result<()> {
+        match self.checksum {
+            Some(expected) if expected != checksum_of(&self.data) => {
+                Err(QRCryptError::Shamir(format!(
+                    "share {} failed its integrity check; it may have been scanned incorrectly or damaged",
+                    self.index
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Public, non-secret metadata about one `split_secret` call, meant to ride
+/// on its own small QR code alongside a share's -- see
+/// `QRGenerator::generate_card_qr_with_verify` and `split --with-verify`.
+/// Lets a holder confirm which split a card's share belongs to, and, once
+/// enough shares are reconstructed, that the result is the secret this
+/// split started from -- without needing to trust any other single share's
+/// holder. Unlike a Feldman-style commitment, this can't verify one share in
+/// isolation before reconstruction: the byte-wise GF(256)/GF(65536) sharing
+/// `split_secret` uses has no algebraic structure to commit to per share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareVerificationInfo {
+    /// Matches every share's `ShamirShare::set_id` from the same split.
+    pub set_id: u32,
+    pub threshold: u8,
+    pub total: u8,
+    /// Unix timestamp of when the split was made; see
+    /// `crate::qr::unix_timestamp_now` for why this is a plain integer
+    /// rather than a formatted date.
+    pub created_at: u64,
+    /// Hex SHA-256 of the value that was actually Shamir-split, checked
+    /// against the reconstructed result after the fact. A bare unsalted
+    /// SHA-256 is only safe to publish on every share card when what it
+    /// commits to is already high-entropy -- pre-image resistance doesn't
+    /// stop guessing over a small or known keyspace, and this same hash is
+    /// what `build_verification_info`'s caller, `split --with-verify`,
+    /// prints on a card next to every share. That's why `--with-verify`
+    /// requires `--password`: the commitment covers the `--password`
+    /// ciphertext (see `encrypt_split_secret`), not the original secret, so
+    /// a holder of just the verification QR gets no brute-forceable oracle.
+    pub secret_commitment: String,
+}
+
+/// Hex SHA-256 of `secret`, for `ShareVerificationInfo::secret_commitment`.
+/// Callers must only pass already high-entropy bytes (e.g. the output of
+/// `encrypt_split_secret`) -- see `ShareVerificationInfo::secret_commitment`
+/// for why a bare SHA-256 of anything else is unsafe to publish.
+pub fn commit_secret(secret: &[u8]) -> String {
+    hex::encode(Sha256::digest(secret))
+}
+
+/// Build the `ShareVerificationInfo` for a just-split secret, for `split
+/// --with-verify`. `shares` must be non-empty and share one `set_id` --
+/// true of every `split_secret*` function's output. `secret` must be
+/// high-entropy (`--with-verify` requires `--password`, so in practice this
+/// is always `encrypt_split_secret`'s ciphertext) -- see
+/// `ShareVerificationInfo::secret_commitment`.
+pub fn build_verification_info(secret: &[u8], shares: &[ShamirShare]) -> ShareVerificationInfo {
+    let first = &shares[0];
+    ShareVerificationInfo {
+        set_id: first.set_id.unwrap_or(0),
+        threshold: first.threshold,
+        total: first.total,
+        created_at: crate::qr::unix_timestamp_now(),
+        secret_commitment: commit_secret(secret),
+    }
+}
+
+/// Encrypt `share.data` with `password`, clearing the plaintext and storing
+/// the result in `share.encryption`. `share.checksum` was already computed
+/// over the plaintext by `split_secret`, so it keeps verifying once
+/// `decrypt_share` restores it; it can't be checked while the share stays
+/// encrypted.
+pub fn encrypt_share(share: &mut ShamirShare, password: &str) -> Result<()> {
+    let encryption = Crypto::encrypt(&share.data, password)?;
+    share.data.clear();
+    share.encryption = Some(encryption);
+    Ok(())
+}
+
+/// Reverse `encrypt_share`, restoring `share.data` to its plaintext bytes.
+/// A no-op if the share wasn't encrypted.
+pub fn decrypt_share(share: &mut ShamirShare, password: &str) -> Result<()> {
+    if let Some(encryption) = &share.encryption {
+        let plaintext = Crypto::decrypt(encryption, password)?;
+        share.data = plaintext.to_vec();
+        share.encryption = None;
+    }
+    Ok(())
+}
+
+/// Disguise `share` as an ordinary encrypted secret for `split --stealth`:
+/// JSON-encode its full metadata (threshold, total, index, checksum, etc.)
+/// and encrypt that with `password`, producing an `EncryptedData` identical
+/// in shape to what `Crypto::encrypt` returns for a real secret. A thief who
+/// finds the resulting QR code can't tell from its contents that it's a
+/// share at all, let alone which one. See `stealth_decrypt_share` for the
+/// reverse.
+pub fn stealth_encrypt_share(share: &ShamirShare, password: &str) -> Result<EncryptedData> {
+    let json = serde_json::to_vec(share).map_err(|e| QRCryptError::Serialization(e.to_string()))?;
+    Crypto::encrypt(&json, password)
+}
+
+/// Reverse `stealth_encrypt_share`: decrypt `encrypted` with `password` and
+/// parse the result back into the `ShamirShare` it came from.
+pub fn stealth_decrypt_share(encrypted: &EncryptedData, password: &str) -> Result<ShamirShare> {
+    let plaintext = Crypto::decrypt(encrypted, password)?;
+    serde_json::from_slice(&plaintext).map_err(|e| QRCryptError::Serialization(e.to_string()))
+}
+
+/// Encrypt `secret` with `password` for `split --password`'s two-factor
+/// recovery mode: the returned bytes (an `EncryptedData`, JSON-encoded) are
+/// what actually gets Shamir-split, not `secret` itself, so reconstructing
+/// the threshold's worth of shares alone only yields ciphertext -- `password`
+/// is still needed to get the secret back. See `decrypt_split_secret` for
+/// the reverse.
+pub fn encrypt_split_secret(secret: &[u8], password: &str) -> Result<Vec<u8>> {
+    let encrypted = Crypto::encrypt(secret, password)?;
+    serde_json::to_vec(&encrypted).map_err(|e| QRCryptError::Serialization(e.to_string()))
+}
+
+/// Reverse `encrypt_split_secret`: parse `reconstruct_secret`'s output back
+/// into an `EncryptedData` and decrypt it with `password`.
+pub fn decrypt_split_secret(
+    reconstructed: &[u8],
+    password: &str,
+) -> Result<zeroize::Zeroizing<Vec<u8>>> {
+    let encrypted: EncryptedData = serde_json::from_slice(reconstructed)
+        .map_err(|e| QRCryptError::Serialization(e.to_string()))?;
+    Crypto::decrypt(&encrypted, password)
+}
+
+/// Render `share` as an `ssss-split`-style "index-hexshare" text line (e.g.
+/// "1-a1b2c3"), the format the classic Debian `ssss` utility reads and
+/// writes. See `parse_ssss_share` for the important caveat on how far this
+/// compatibility actually goes.
+pub fn format_ssss_share(share: &ShamirShare) -> String {
+    format!("{}-{}", share.index, hex::encode(&share.data))
+}
+
+/// Parse one `ssss-split`-style "index-hexshare" line into a `ShamirShare`,
+/// for importing shares produced by the classic `ssss` tool (or by
+/// `split --format ssss`). Unlike QRCrypt's own format, ssss's plain-text
+/// shares carry neither a threshold nor a set fingerprint, so both must be
+/// supplied by the caller; `set_id` is left `None`, same as a pre-fingerprint
+/// QRCrypt share, so mixing shares from different splits only gets a warning
+/// rather than being rejected outright.
+///
+/// Caveat: `ssss-split` shares a secret shorter than `threshold` bytes* as a
+/// single element of a large `GF(2^n)` field (`n` = the secret's bit length)
+/// under its own per-length reduction polynomial, not as independent
+/// per-byte `GF(256)` shares the way `split_secret` does here. The two
+/// schemes agree bit-for-bit only for single-byte secrets, where both use
+/// the same `GF(256)` field as AES. For anything longer, this reads and
+/// writes the same textual shape ssss uses but does not reconstruct shares
+/// actually produced by the real `ssss-split` binary -- only shares this
+/// tool produced with `--format ssss` round-trip correctly.
+pub fn parse_ssss_share(line: &str, threshold: u8, total: u8) -> Result<ShamirShare> {
+    let (index, hex_data) = line.trim().split_once('-').ok_or_else(|| {
+        QRCryptError::InvalidFormat(format!(
+            "not an ssss share (expected \"index-hexshare\"): {line}"
+        ))
+    })?;
+    let index: u8 = index.parse().map_err(|_| {
+        QRCryptError::InvalidFormat(format!("ssss share index is not a number: {line}"))
+    })?;
+    let data = hex::decode(hex_data)
+        .map_err(|e| QRCryptError::InvalidFormat(format!("ssss share is not valid hex: {e}")))?;
+    Ok(ShamirShare {
+        version: CURRENT_SHARE_VERSION,
+        index,
+        threshold,
+        total,
+        checksum: Some(checksum_of(&data)),
+        data,
+        set_id: None,
+        encryption: None,
+        label: None,
+        note: None,
+        group_id: None,
+        group_threshold: None,
+        group_count: None,
+        encoding: ShareEncoding::Gf256,
+        signature: None,
+    })
+}
+
+/// Confirm every share in `shares` carries the same `set_id` and that no two
+/// share the same `.index`, rejecting the first violation found by name.
+/// Shares with no `set_id` (from before this field existed) can't be
+/// checked for mixing and are accepted with a warning instead, but a
+/// repeated index is always rejected: Lagrange interpolation divides by
+/// `share_i.index ^ share_j.index`, which is zero for a repeated index (the
+/// same card scanned twice, say), and GF division by zero silently returns
+/// zero rather than erroring, so reconstruction would otherwise return a
+/// wrong secret instead of failing loudly.
+pub(crate) fn verify_set_consistency(shares: &[ShamirShare]) -> Result<()> {
+    let mut seen_indices = std::collections::HashSet::new();
+    let mut expected: Option<u32> = None;
+    for share in shares {
+        if !seen_indices.insert(share.index) {
+            return Err(QRCryptError::Shamir(format!(
+                "share {} appears more than once in the given set",
+                share.index
+            )));
+        }
+        match share.set_id {
+            Some(id) => match expected {
+                None => expected = Some(id),
+                Some(e) if e != id => {
+                    return Err(QRCryptError::Shamir(format!(
+                        "share {} belongs to a different split than the others; shares from different splits cannot be combined",
+                        share.index
+                    )));
+                }
+                _ => {}
+            },
+            None => {
+                crate::utils::print_warning(&format!(
+                    "share {} predates the set fingerprint and can't be checked for mixing",
+                    share.index
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A truncated-SHA-256 checksum of `data`: the first 4 bytes of the digest,
+/// as a big-endian `u32`.
+fn checksum_of(data: &[u8]) -> u32 {
+    let digest = Sha256::digest(data);
+    u32::from_be_bytes(digest[..4].try_into().expect("digest is at least 4 bytes"))
+}
+
+/// (De)serializes `ShamirShare::data` as base64 instead of a JSON array of
+/// numbers, which is far more compact once JSON's per-element overhead is
+/// accounted for. Still accepts the old array-of-numbers form on read, so
+/// version 1 shares keep working.
+mod share_data_encoding {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Base64(String),
+        Legacy(Vec<u8>),
+    }
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        match Repr::deserialize(deserializer)? {
+            Repr::Base64(s) => STANDARD.decode(&s).map_err(serde::de::Error::custom),
+            Repr::Legacy(bytes) => Ok(bytes),
+        }
+    }
+}
+
+// GF(256) multiplication using the AES reduction polynomial (0x11b). Shared
+// with `slip39`, which uses the same field for its own Lagrange interpolation.
+pub(crate) fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0u8;
+    for _ in 0..8 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        let high_bit_set = a & 0x80 != 0;
+        a <<= 1;
+        if high_bit_set {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    result
+}
+
+fn gf_pow(a: u8, mut n: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    while n > 0 {
+        if n & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        n >>= 1;
+    }
+    result
+}
+
+fn gf_inv(a: u8) -> u8 {
+    // a^254 == a^-1 in GF(256)
+    gf_pow(a, 254)
+}
+
+pub(crate) fn gf_div(a: u8, b: u8) -> u8 {
+    gf_mul(a, gf_inv(b))
+}
+
+/// GF(65536) multiplication, for `ShareEncoding::Gf65536`. Same
+/// shift-and-reduce approach as `gf_mul`, scaled up to 16 bits and reduced
+/// modulo the irreducible polynomial x^16 + x^5 + x^3 + x + 1.
+fn gf65536_mul(mut a: u16, mut b: u16) -> u16 {
+    let mut result = 0u16;
+    for _ in 0..16 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        let high_bit_set = a & 0x8000 != 0;
+        a <<= 1;
+        if high_bit_set {
+            a ^= 0x002b;
+        }
+        b >>= 1;
+    }
+    result
+}
+
+fn gf65536_pow(a: u16, mut n: u32) -> u16 {
+    let mut result = 1u16;
+    let mut base = a;
+    while n > 0 {
+        if n & 1 != 0 {
+            result = gf65536_mul(result, base);
+        }
+        base = gf65536_mul(base, base);
+        n >>= 1;
+    }
+    result
+}
+
+fn gf65536_inv(a: u16) -> u16 {
+    // a^65534 == a^-1 in GF(65536)
+    gf65536_pow(a, 65534)
+}
+
+fn gf65536_div(a: u16, b: u16) -> u16 {
+    gf65536_mul(a, gf65536_inv(b))
+}
+
+/// Evaluate the polynomial with the given coefficients (coefficients[0] is
+/// the secret word) at point `x` over GF(65536).
+fn eval_poly_65536(coefficients: &[u16], x: u16) -> u16 {
+    let mut result = 0u16;
+    for &coeff in coefficients.iter().rev() {
+        result = gf65536_mul(result, x) ^ coeff;
+    }
+    result
+}
+
+/// Evaluate the polynomial with the given coefficients (coefficients[0] is
+/// the secret byte) at point `x` over GF(256).
+fn eval_poly(coefficients: &[u8], x: u8) -> u8 {
+    let mut result = 0u8;
+    for &coeff in coefficients.iter().rev() {
+        result = gf_mul(result, x) ^ coeff;
+    }
+    result
+}
+
+/// Split `secret` into `total` shares such that any `threshold` of them can
+/// reconstruct it, using Shamir's secret sharing over GF(256). `total`'s `u8`
+/// type is itself the field's safe share count: shares live at nonzero
+/// points `1..=255` (x = 0 is reserved for the secret), so `total` can never
+/// exceed the 255 points GF(256) actually has room for.
+pub fn split_secret(secret: &[u8], threshold: u8, total: u8) -> Result<Vec<ShamirShare>> {
+    if threshold < 2 {
+        return Err(QRCryptError::Shamir(
+            "threshold must be at least 2".to_string(),
+        ));
+    }
+    split_secret_raw(secret, threshold, total)
+}
+
+/// The actual split, shared by `split_secret` and `split_secret_with_groups`.
+/// Unlike `split_secret`, this allows `threshold == 1`: a degenerate but
+/// legitimate split where every share carries the secret outright, needed
+/// for a group with `--group 1ofN` ("any single lawyer share is enough").
+fn split_secret_raw(secret: &[u8], threshold: u8, total: u8) -> Result<Vec<ShamirShare>> {
+    if threshold == 0 {
+        return Err(QRCryptError::Shamir(
+            "threshold must be at least 1".to_string(),
+        ));
+    }
+    if total < threshold {
+        return Err(QRCryptError::Shamir(
+            "total shares must be >= threshold".to_string(),
+        ));
+    }
+    if total == 0 {
+        return Err(QRCryptError::Shamir(
+            "total shares must be at least 1".to_string(),
+        ));
+    }
+
+    let ids: Vec<u8> = (1..=total).collect();
+    split_secret_at_ids(secret, threshold, total, &ids)
+}
+
+/// Warn (without failing) about share parameters that are valid but weaken
+/// the split: `threshold == 1` means every single share reveals the secret
+/// outright (only legitimate as one member of a 1-of-N `--group`), and
+/// `threshold == total` leaves no redundancy at all -- losing even one share
+/// makes the secret unrecoverable.
+fn warn_about_weak_share_parameters(threshold: u8, total: u8) {
+    if threshold == 1 {
+        crate::utils::print_warning(
+            "threshold is 1: every share on its own reveals the secret, with no splitting at all",
+        );
+    } else if threshold == total {
+        crate::utils::print_warning(
+            "threshold equals total shares: losing even one share makes the secret unrecoverable; consider a lower threshold for redundancy",
+        );
+    }
+}
+
+/// Split `secret` at the given x-coordinates `ids` rather than the default
+/// `1..=total`, so a share's id alone doesn't reveal how many shares exist in
+/// the split, and a specific lost id can be regenerated later. Shared by
+/// `split_secret_raw` (with `ids = 1..=total`) and `split_secret_with_ids`.
+fn split_secret_at_ids(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    ids: &[u8],
+) -> Result<Vec<ShamirShare>> {
+    warn_about_weak_share_parameters(threshold, total);
+    let set_id = OsRng.next_u32();
+    let mut shares: Vec<ShamirShare> = ids
+        .iter()
+        .map(|&i| ShamirShare {
+            version: CURRENT_SHARE_VERSION,
+            index: i,
+            threshold,
+            total,
+            data: Vec::with_capacity(secret.len()),
+            checksum: None,
+            set_id: Some(set_id),
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding: ShareEncoding::Gf256,
+            signature: None,
+        })
+        .collect();
+
+    for &byte in secret {
+        let mut coefficients = vec![byte];
+        for _ in 1..threshold {
+            let mut rnd = [0u8; 1];
+            OsRng.fill_bytes(&mut rnd);
+            coefficients.push(rnd[0]);
+        }
+        for share in shares.iter_mut() {
+            share.data.push(eval_poly(&coefficients, share.index));
+        }
+    }
+
+    for share in shares.iter_mut() {
+        share.checksum = Some(checksum_of(&share.data));
+    }
+
+    Ok(shares)
+}
+
+/// Like `split_secret_at_ids`, but batches two secret bytes into one
+/// GF(65536) polynomial element instead of one GF(256) element per byte, for
+/// `ShareEncoding::Gf65536`. `secret` must have an even length: an odd
+/// trailing byte is rejected up front rather than silently padded, since a
+/// pad byte would need its own bookkeeping to strip back off on
+/// reconstruction.
+fn split_secret_packed_at_ids(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    ids: &[u8],
+) -> Result<Vec<ShamirShare>> {
+    if !secret.len().is_multiple_of(2) {
+        return Err(QRCryptError::Shamir(
+            "--share-encoding gf65536 requires an even number of secret bytes".to_string(),
+        ));
+    }
+    warn_about_weak_share_parameters(threshold, total);
+
+    let set_id = OsRng.next_u32();
+    let mut shares: Vec<ShamirShare> = ids
+        .iter()
+        .map(|&i| ShamirShare {
+            version: CURRENT_SHARE_VERSION,
+            index: i,
+            threshold,
+            total,
+            data: Vec::with_capacity(secret.len()),
+            checksum: None,
+            set_id: Some(set_id),
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding: ShareEncoding::Gf65536,
+            signature: None,
+        })
+        .collect();
+
+    for word in secret.chunks_exact(2) {
+        let mut coefficients = vec![u16::from_be_bytes([word[0], word[1]])];
+        for _ in 1..threshold {
+            let mut rnd = [0u8; 2];
+            OsRng.fill_bytes(&mut rnd);
+            coefficients.push(u16::from_be_bytes(rnd));
+        }
+        for share in shares.iter_mut() {
+            let y = eval_poly_65536(&coefficients, share.index as u16);
+            share.data.extend_from_slice(&y.to_be_bytes());
+        }
+    }
+
+    for share in shares.iter_mut() {
+        share.checksum = Some(checksum_of(&share.data));
+    }
+
+    Ok(shares)
+}
+
+/// Split `secret` into `total` shares at `1..=total`, same as `split_secret`,
+/// but under the given `ShareEncoding` instead of always GF(256). Exposed
+/// for `split --share-encoding`.
+pub fn split_secret_with_encoding(
+    secret: &[u8],
+    threshold: u8,
+    total: u8,
+    encoding: ShareEncoding,
+) -> Result<Vec<ShamirShare>> {
+    if threshold < 2 {
+        return Err(QRCryptError::Shamir(
+            "threshold must be at least 2".to_string(),
+        ));
+    }
+    if total < threshold {
+        return Err(QRCryptError::Shamir(
+            "total shares must be >= threshold".to_string(),
+        ));
+    }
+    if total == 0 {
+        return Err(QRCryptError::Shamir(
+            "total shares must be at least 1".to_string(),
+        ));
+    }
+
+    let ids: Vec<u8> = (1..=total).collect();
+    match encoding {
+        ShareEncoding::Gf256 => split_secret_at_ids(secret, threshold, total, &ids),
+        ShareEncoding::Gf65536 => split_secret_packed_at_ids(secret, threshold, total, &ids),
+    }
+}
+
+/// Like `split_secret`, but at explicit share ids instead of the default
+/// `1..=total`, so an id alone doesn't reveal the total share count, and a
+/// specific lost id can be regenerated later with `reshare --ids`.
+/// `ids.len()` becomes each share's recorded `total`. Every id must be
+/// nonzero (x = 0 would evaluate the polynomial at the secret itself,
+/// exposing it outright) and unique.
+pub fn split_secret_with_ids(secret: &[u8], threshold: u8, ids: &[u8]) -> Result<Vec<ShamirShare>> {
+    if threshold < 2 {
+        return Err(QRCryptError::Shamir(
+            "threshold must be at least 2".to_string(),
+        ));
+    }
+    if ids.contains(&0) {
+        return Err(QRCryptError::Shamir(
+            "share id 0 is not allowed; it would expose the secret directly".to_string(),
+        ));
+    }
+    let mut unique = ids.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+    if unique.len() != ids.len() {
+        return Err(QRCryptError::Shamir("share ids must be unique".to_string()));
+    }
+    if ids.len() < threshold as usize {
+        return Err(QRCryptError::Shamir(
+            "total shares must be >= threshold".to_string(),
+        ));
+    }
+    let total = u8::try_from(ids.len())
+        .map_err(|_| QRCryptError::Shamir("too many share ids (max 255)".to_string()))?;
+    split_secret_at_ids(secret, threshold, total, ids)
+}
+
+/// Lagrange-interpolate at x = 0 using exactly `shares`' data, with none of
+/// `reconstruct_secret`'s integrity or consistency checks -- callers run
+/// those first (or, in `diagnose_shares`'s case, are deliberately comparing
+/// results across subsets that haven't been checked against each other).
+fn lagrange_interpolate_secret(shares: &[&ShamirShare]) -> Vec<u8> {
+    let secret_len = shares[0].data.len();
+    let mut secret = Vec::with_capacity(secret_len);
+    for byte_idx in 0..secret_len {
+        let mut value = 0u8;
+        for (i, share_i) in shares.iter().enumerate() {
+            let mut numerator = 1u8;
+            let mut denominator = 1u8;
+            for (j, share_j) in shares.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                numerator = gf_mul(numerator, share_j.index);
+                denominator = gf_mul(denominator, share_i.index ^ share_j.index);
+            }
+            let lagrange_coeff = gf_div(numerator, denominator);
+            value ^= gf_mul(share_i.data[byte_idx], lagrange_coeff);
+        }
+        secret.push(value);
+    }
+    secret
+}
+
+/// Same as `lagrange_interpolate_secret`, but over GF(65536) instead of
+/// GF(256), for shares produced with `ShareEncoding::Gf65536`: `data` is
+/// read as big-endian u16 pairs instead of individual bytes.
+fn lagrange_interpolate_secret_packed(shares: &[&ShamirShare]) -> Vec<u8> {
+    let word_count = shares[0].data.len() / 2;
+    let mut secret = Vec::with_capacity(word_count * 2);
+    for word_idx in 0..word_count {
+        let mut value = 0u16;
+        for (i, share_i) in shares.iter().enumerate() {
+            let xi = share_i.index as u16;
+            let mut numerator = 1u16;
+            let mut denominator = 1u16;
+            for (j, share_j) in shares.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let xj = share_j.index as u16;
+                numerator = gf65536_mul(numerator, xj);
+                denominator = gf65536_mul(denominator, xi ^ xj);
+            }
+            let lagrange_coeff = gf65536_div(numerator, denominator);
+            let yi =
+                u16::from_be_bytes([share_i.data[word_idx * 2], share_i.data[word_idx * 2 + 1]]);
+            value ^= gf65536_mul(yi, lagrange_coeff);
+        }
+        secret.extend_from_slice(&value.to_be_bytes());
+    }
+    secret
+}
+
+/// Reconstruct the secret from at least `threshold` shares using Lagrange
+/// interpolation at x = 0, over whichever `ShareEncoding` the shares were
+/// split with. The recovered bytes are returned as a `SecretData` so they're
+/// wiped from memory once the caller drops it, rather than lingering in a
+/// plain `String`.
+pub fn reconstruct_secret(shares: &[ShamirShare]) -> Result<SecretData> {
+    if shares.is_empty() {
+        return Err(QRCryptError::Shamir("no shares provided".to_string()));
+    }
+
+    for share in shares {
+        share.verify_checksum()?;
+    }
+    verify_set_consistency(shares)?;
+
+    let encoding = shares[0].encoding;
+    if shares.iter().any(|s| s.encoding != encoding) {
+        return Err(QRCryptError::Shamir(
+            "shares were split with different --share-encoding settings and cannot be combined"
+                .to_string(),
+        ));
+    }
+
+    let version = shares[0].version;
+    if let Some(incompatible) = shares
+        .iter()
+        .find(|s| !versions_compatible(s.version, version))
+    {
+        return Err(QRCryptError::Shamir(format!(
+            "share {} is encoding version {}, incompatible with version {} -- shares that changed \
+             the underlying secret-sharing math cannot be combined",
+            incompatible.index, incompatible.version, version
+        )));
+    }
+
+    let threshold = shares[0].threshold;
+    if shares.len() < threshold as usize {
+        return Err(QRCryptError::Shamir(format!(
+            "need at least {} shares, got {}",
+            threshold,
+            shares.len()
+        )));
+    }
+
+    let secret_len = shares[0].data.len();
+    for share in shares {
+        if share.data.len() != secret_len {
+            return Err(QRCryptError::Shamir(
+                "shares have mismatched lengths".to_string(),
+            ));
+        }
+    }
+    if encoding == ShareEncoding::Gf65536 && !secret_len.is_multiple_of(2) {
+        return Err(QRCryptError::Shamir(
+            "gf65536-encoded share data has an odd length".to_string(),
+        ));
+    }
+
+    let used: Vec<&ShamirShare> = shares.iter().take(threshold as usize).collect();
+    let bytes = match encoding {
+        ShareEncoding::Gf256 => lagrange_interpolate_secret(&used),
+        ShareEncoding::Gf65536 => lagrange_interpolate_secret_packed(&used),
+    };
+    Ok(SecretData::from_bytes(bytes))
+}
+
+/// Pack an outer group point's x-coordinate together with its y-values into
+/// one byte string, so it can be handed to `split_secret` as the "secret"
+/// for that group's own inner split. `decode_group_secret` reverses this
+/// once the group's inner shares reconstruct it.
+fn encode_group_secret(outer_index: u8, outer_data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(1 + outer_data.len());
+    encoded.push(outer_index);
+    encoded.extend_from_slice(outer_data);
+    encoded
+}
+
+fn decode_group_secret(bytes: &[u8]) -> Result<(u8, Vec<u8>)> {
+    let (&index, data) = bytes.split_first().ok_or_else(|| {
+        QRCryptError::Shamir("a group's reconstructed secret was empty".to_string())
+    })?;
+    Ok((index, data.to_vec()))
+}
+
+/// Split `secret` into named groups, SLIP-39 style: first split it into one
+/// "group secret" per entry of `groups`, such that any `groups_required` of
+/// them reconstruct it, then split each group secret again with that
+/// group's own `(threshold, total)`. Lets a holder require e.g. "any 2 of 3
+/// family shares AND any 1 of 2 lawyer shares" rather than one flat
+/// threshold. Every returned share's `threshold`/`total` describe only its
+/// own group; `group_id`, `group_threshold` (== `groups_required`), and
+/// `group_count` (== `groups.len()`) describe the outer structure.
+pub fn split_secret_with_groups(
+    secret: &[u8],
+    groups: &[(u8, u8)],
+    groups_required: u8,
+) -> Result<Vec<ShamirShare>> {
+    if groups.is_empty() {
+        return Err(QRCryptError::Shamir(
+            "at least one group is required".to_string(),
+        ));
+    }
+    let group_count = groups.len() as u8;
+    if groups_required == 0 || groups_required > group_count {
+        return Err(QRCryptError::Shamir(format!(
+            "--groups-required must be between 1 and {group_count} (the number of groups defined)"
+        )));
+    }
+
+    // One point per group, from an outer split of the secret itself. A
+    // single group needs no outer split at all -- "any 1 of 1 groups" has
+    // nothing to divide, so that one group just gets the whole secret.
+    let outer_points: Vec<(u8, Vec<u8>)> = if group_count == 1 {
+        vec![(1, secret.to_vec())]
+    } else {
+        split_secret_raw(secret, groups_required, group_count)?
+            .into_iter()
+            .map(|s| (s.index, s.data))
+            .collect()
+    };
+
+    let mut shares = Vec::new();
+    for (group_id, ((inner_threshold, inner_total), (outer_index, outer_data))) in
+        groups.iter().copied().zip(outer_points).enumerate()
+    {
+        let group_secret = encode_group_secret(outer_index, &outer_data);
+        let mut group_shares = split_secret_raw(&group_secret, inner_threshold, inner_total)?;
+        for share in group_shares.iter_mut() {
+            share.group_id = Some(group_id as u8);
+            share.group_threshold = Some(groups_required);
+            share.group_count = Some(group_count);
+        }
+        shares.extend(group_shares);
+    }
+    Ok(shares)
+}
+
+/// One group that doesn't yet have enough of its own shares present to
+/// reconstruct, as reported by `group_progress`.
+#[derive(Debug, Clone)]
+pub struct IncompleteGroup {
+    pub group_id: u8,
+    pub have: usize,
+    pub need: u8,
+}
+
+/// How close a set of grouped shares is to satisfying their outer
+/// `groups_required`-of-`group_count` threshold, without attempting
+/// reconstruction.
+#[derive(Debug, Clone)]
+pub struct GroupProgress {
+    pub groups_required: u8,
+    pub group_count: u8,
+    pub complete_groups: Vec<u8>,
+    pub incomplete_groups: Vec<IncompleteGroup>,
+}
+
+impl GroupProgress {
+    pub fn satisfied(&self) -> bool {
+        self.complete_groups.len() >= self.groups_required as usize
+    }
+}
+
+/// Confirm every group has an internally consistent `set_id` (each group's
+/// own inner `split_secret` call picks its own fingerprint, so unlike
+/// `verify_set_consistency`, different groups are *expected* to disagree).
+pub(crate) fn verify_grouped_set_consistency(shares: &[ShamirShare]) -> Result<()> {
+    let mut by_group: std::collections::BTreeMap<u8, Vec<ShamirShare>> =
+        std::collections::BTreeMap::new();
+    for share in shares {
+        by_group
+            .entry(share.group_id.unwrap_or(0))
+            .or_default()
+            .push(share.clone());
+    }
+    for members in by_group.values() {
+        verify_set_consistency(members)?;
+    }
+    Ok(())
+}
+
+/// Summarize how close `shares` (all belonging to one grouped split) are to
+/// satisfying their outer threshold, grouping them by `group_id` first.
+pub fn group_progress(shares: &[ShamirShare]) -> Result<GroupProgress> {
+    let first = shares
+        .first()
+        .ok_or_else(|| QRCryptError::Shamir("no shares provided".to_string()))?;
+    let groups_required = first.group_threshold.ok_or_else(|| {
+        QRCryptError::Shamir("shares do not belong to a grouped split".to_string())
+    })?;
+    let group_count = first.group_count.unwrap_or(1);
+
+    let mut by_group: std::collections::BTreeMap<u8, Vec<&ShamirShare>> =
+        std::collections::BTreeMap::new();
+    for share in shares {
+        let group_id = share.group_id.ok_or_else(|| {
+            QRCryptError::Shamir("shares do not belong to a grouped split".to_string())
+        })?;
+        by_group.entry(group_id).or_default().push(share);
+    }
+
+    let mut complete_groups = Vec::new();
+    let mut incomplete_groups = Vec::new();
+    for (&group_id, members) in &by_group {
+        let need = members[0].threshold;
+        if members.len() >= need as usize {
+            complete_groups.push(group_id);
+        } else {
+            incomplete_groups.push(IncompleteGroup {
+                group_id,
+                have: members.len(),
+                need,
+            });
+        }
+    }
+
+    Ok(GroupProgress {
+        groups_required,
+        group_count,
+        complete_groups,
+        incomplete_groups,
+    })
+}
+
+/// Reconstruct a secret split by `split_secret_with_groups`: reconstruct
+/// every group that already has enough of its own shares, then (unless
+/// there's only the one implicit group) Lagrange-interpolate across enough
+/// reconstructed groups to recover the original secret.
+pub fn reconstruct_grouped_secret(shares: &[ShamirShare]) -> Result<SecretData> {
+    for share in shares {
+        share.verify_checksum()?;
+    }
+    verify_grouped_set_consistency(shares)?;
+
+    let progress = group_progress(shares)?;
+    if !progress.satisfied() {
+        let missing: Vec<String> = progress
+            .incomplete_groups
+            .iter()
+            .map(|g| format!("group {} ({}/{})", g.group_id, g.have, g.need))
+            .collect();
+        return Err(QRCryptError::Shamir(format!(
+            "need {} of {} groups complete, only {} are; still incomplete: {}",
+            progress.groups_required,
+            progress.group_count,
+            progress.complete_groups.len(),
+            missing.join(", ")
+        )));
+    }
+
+    let mut by_group: std::collections::BTreeMap<u8, Vec<ShamirShare>> =
+        std::collections::BTreeMap::new();
+    for share in shares {
+        by_group
+            .entry(share.group_id.expect("checked by group_progress"))
+            .or_default()
+            .push(share.clone());
+    }
+
+    let mut outer_points: Vec<(u8, Vec<u8>)> = Vec::new();
+    for group_id in &progress.complete_groups {
+        let group_secret = reconstruct_secret(&by_group[group_id])?;
+        outer_points.push(decode_group_secret(group_secret.as_bytes())?);
+    }
+
+    if progress.group_count == 1 {
+        let (_, secret) = outer_points
+            .into_iter()
+            .next()
+            .expect("one group is complete");
+        return Ok(SecretData::from_bytes(secret));
+    }
+
+    // Drain (and wipe) any surplus complete-group secrets before truncating,
+    // rather than after -- `Vec::truncate` drops the discarded elements
+    // itself, so zeroizing only what survives it would leave those groups'
+    // secret bytes unwiped in the freed allocation.
+    outer_points
+        .drain(progress.groups_required as usize..)
+        .for_each(|(_, mut data)| data.zeroize());
+    let secret_len = outer_points[0].1.len();
+    let mut secret = Vec::with_capacity(secret_len);
+    for byte_idx in 0..secret_len {
+        let points: Vec<(u8, u8)> = outer_points
+            .iter()
+            .map(|(i, d)| (*i, d[byte_idx]))
+            .collect();
+        secret.push(lagrange_interpolate(&points, 0));
+    }
+    // `outer_points` holds each reconstructed group's secret bytes, which are
+    // themselves sensitive intermediate material; wipe them now that the
+    // final secret has been interpolated, rather than leaving them to
+    // linger in freed memory.
+    for (_, data) in outer_points.iter_mut() {
+        data.zeroize();
+    }
+    Ok(SecretData::from_bytes(secret))
+}
+
+/// How many shares `diagnose_shares` will cross-check at once. The number of
+/// threshold-sized subsets to try grows combinatorially with the share
+/// count, so this keeps the search from blowing up.
+const MAX_DIAGNOSE_SHARES: usize = 10;
+
+/// The outcome of `diagnose_shares`: either every threshold-sized subset of
+/// the supplied shares reconstructs to the same secret (nothing obviously
+/// wrong), or `suspect_indices` names the share(s) that are never part of a
+/// subset agreeing with the majority answer.
+#[derive(Debug, Clone)]
+pub struct ShareDiagnosis {
+    pub consistent: bool,
+    pub suspect_indices: Vec<u8>,
+}
+
+/// All k-element subsets of `0..n`, as index lists. `n` and `k` are small
+/// enough in practice (`MAX_DIAGNOSE_SHARES` shares or fewer) that a simple
+/// recursive search is plenty fast.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    fn go(start: usize, n: usize, k: usize, combo: &mut Vec<usize>, result: &mut Vec<Vec<usize>>) {
+        if combo.len() == k {
+            result.push(combo.clone());
+            return;
+        }
+        for i in start..n {
+            combo.push(i);
+            go(i + 1, n, k, combo, result);
+            combo.pop();
+        }
+    }
+    let mut result = Vec::new();
+    go(0, n, k, &mut Vec::with_capacity(k), &mut result);
+    result
+}
+
+/// When `shares` outnumbers their threshold, reconstruct from every
+/// threshold-sized subset and compare the results, to locate which share(s)
+/// are likely damaged when a straight `reconstruct_secret` call would just
+/// fail outright (or, for shares predating the checksum field, silently
+/// return garbage instead of failing at all). Returns `Ok(None)` rather than
+/// diagnosing anything when there aren't enough spare shares to cross-check.
+pub fn diagnose_shares(shares: &[ShamirShare]) -> Result<Option<ShareDiagnosis>> {
+    if shares.is_empty() {
+        return Err(QRCryptError::Shamir("no shares provided".to_string()));
+    }
+    if shares.len() > MAX_DIAGNOSE_SHARES {
+        return Err(QRCryptError::Shamir(format!(
+            "--diagnose only supports up to {MAX_DIAGNOSE_SHARES} shares at once, got {}",
+            shares.len()
+        )));
+    }
+
+    let threshold = shares[0].threshold;
+    if shares.len() <= threshold as usize {
+        return Ok(None);
+    }
+
+    let secret_len = shares[0].data.len();
+    for share in shares {
+        if share.data.len() != secret_len {
+            return Err(QRCryptError::Shamir(
+                "shares have mismatched lengths".to_string(),
+            ));
+        }
+    }
+
+    let mut results: Vec<(Vec<u8>, Vec<Vec<usize>>)> = Vec::new();
+    for subset in combinations(shares.len(), threshold as usize) {
+        let used: Vec<&ShamirShare> = subset.iter().map(|&i| &shares[i]).collect();
+        let secret = lagrange_interpolate_secret(&used);
+        match results.iter_mut().find(|(s, _)| *s == secret) {
+            Some((_, subsets)) => subsets.push(subset),
+            None => results.push((secret, vec![subset])),
+        }
+    }
+
+    results.sort_by_key(|(_, subsets)| std::cmp::Reverse(subsets.len()));
+    let majority_subsets = &results[0].1;
+
+    let suspect_indices: Vec<u8> = shares
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !majority_subsets.iter().any(|subset| subset.contains(i)))
+        .map(|(_, share)| share.index)
+        .collect();
+
+    Ok(Some(ShareDiagnosis {
+        consistent: suspect_indices.is_empty(),
+        suspect_indices,
+    }))
+}
+
+/// Evaluate, at `x`, the unique polynomial over GF(256) passing through
+/// `points` (each a distinct x-coordinate paired with the polynomial's value
+/// there). `reconstruct_secret` inlines this same formula specialized to
+/// x = 0; this general form also backs `generate_parity_shares` (evaluating
+/// beyond the original shares' x-coordinates) and `repair_missing_shares`
+/// (evaluating at a missing share's x-coordinate).
+fn lagrange_interpolate(points: &[(u8, u8)], x: u8) -> u8 {
+    let mut value = 0u8;
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let mut numerator = 1u8;
+        let mut denominator = 1u8;
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            numerator = gf_mul(numerator, x ^ xj);
+            denominator = gf_mul(denominator, xi ^ xj);
+        }
+        value ^= gf_mul(yi, gf_div(numerator, denominator));
+    }
+    value
+}
+
+/// An outer erasure-coding symbol generated by `generate_parity_shares`, one
+/// per byte of the secret, from all `total` original shares of one split.
+/// On its own it reveals nothing about the secret: it isn't a point on the
+/// secret's own degree-(threshold-1) polynomial, just on the degree-(total-1)
+/// polynomial interpolated through the original shares' data. Alongside at
+/// least `total` other shares (original or parity) it lets
+/// `repair_missing_shares` rebuild a destroyed original share's bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParityShare {
+    pub index: u8,
+    pub total: u8,
+    pub parity_total: u8,
+    #[serde(with = "share_data_encoding")]
+    pub data: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_id: Option<u32>,
+}
+
+/// Generate `parity` extra parity shares from a complete set of `shares`
+/// (every one of the `total` shares from a single `split_secret` call, in
+/// any order). Up to `parity` of the original shares can later be destroyed
+/// and rebuilt by `repair_missing_shares`, as long as at least `total`
+/// shares (original or parity, any mix) still survive.
+pub fn generate_parity_shares(shares: &[ShamirShare], parity: u8) -> Result<Vec<ParityShare>> {
+    if parity == 0 {
+        return Err(QRCryptError::Shamir(
+            "parity count must be at least 1".to_string(),
+        ));
+    }
+    let total = shares
+        .first()
+        .ok_or_else(|| QRCryptError::Shamir("no shares provided".to_string()))?
+        .total;
+    if shares.len() != total as usize {
+        return Err(QRCryptError::Shamir(
+            "parity generation needs every original share, not just a threshold subset".to_string(),
+        ));
+    }
+    if total as u16 + parity as u16 > u8::MAX as u16 {
+        return Err(QRCryptError::Shamir(
+            "total shares plus parity shares cannot exceed 255".to_string(),
+        ));
+    }
+    verify_set_consistency(shares)?;
+    for share in shares {
+        share.verify_checksum()?;
+    }
+
+    let secret_len = shares[0].data.len();
+    let mut parity_data = vec![Vec::with_capacity(secret_len); parity as usize];
+    for byte_idx in 0..secret_len {
+        let points: Vec<(u8, u8)> = shares.iter().map(|s| (s.index, s.data[byte_idx])).collect();
+        for (p, bytes) in parity_data.iter_mut().enumerate() {
+            let x = total + 1 + p as u8;
+            bytes.push(lagrange_interpolate(&points, x));
+        }
+    }
+
+    let set_id = shares[0].set_id;
+    Ok(parity_data
+        .into_iter()
+        .enumerate()
+        .map(|(p, data)| ParityShare {
+            index: total + 1 + p as u8,
+            total,
+            parity_total: parity,
+            checksum: Some(checksum_of(&data)),
+            set_id,
+            data,
+        })
+        .collect())
+}
+
+/// Confirm `parity`'s checksums, if that integrity check was requested at
+/// generation time.
+fn verify_parity_checksums(parity: &[ParityShare]) -> Result<()> {
+    for p in parity {
+        if let Some(expected) = p.checksum {
+            if expected != checksum_of(&p.data) {
+                return Err(QRCryptError::Shamir(format!(
+                    "parity share {} failed its integrity check; it may have been scanned incorrectly or damaged",
+                    p.index
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rebuild whichever of the `total` original shares are missing from
+/// `available`, using whatever mix of `available` and `parity` shares
+/// survived. Shares `encrypt_share` left encrypted can't contribute a data
+/// point and are treated the same as missing. Returns `available` together
+/// with the rebuilt shares; errors out naming how many more shares (of
+/// either kind) are needed if there still aren't enough to repair.
+pub fn repair_missing_shares(
+    available: &[ShamirShare],
+    parity: &[ParityShare],
+    total: u8,
+) -> Result<Vec<ShamirShare>> {
+    let usable: Vec<&ShamirShare> = available
+        .iter()
+        .filter(|s| s.encryption.is_none())
+        .collect();
+    for share in &usable {
+        share.verify_checksum()?;
+    }
+    verify_parity_checksums(parity)?;
+
+    let mut set_ids = usable
+        .iter()
+        .filter_map(|s| s.set_id)
+        .chain(parity.iter().filter_map(|p| p.set_id));
+    if let Some(first) = set_ids.next() {
+        if set_ids.any(|id| id != first) {
+            return Err(QRCryptError::Shamir(
+                "shares and parity shares belong to different splits and cannot be combined"
+                    .to_string(),
+            ));
+        }
+    }
+
+    let have_indices: std::collections::HashSet<u8> = usable.iter().map(|s| s.index).collect();
+    let missing: Vec<u8> = (1..=total).filter(|i| !have_indices.contains(i)).collect();
+    if missing.is_empty() {
+        return Ok(available.to_vec());
+    }
+
+    let point_count = usable.len() + parity.len();
+    if point_count < total as usize {
+        return Err(QRCryptError::Shamir(format!(
+            "need at least {total} shares (original or parity) to repair a missing share, have {point_count}"
+        )));
+    }
+
+    let secret_len = usable
+        .first()
+        .map(|s| s.data.len())
+        .or_else(|| parity.first().map(|p| p.data.len()))
+        .ok_or_else(|| QRCryptError::Shamir("no shares or parity shares provided".to_string()))?;
+    let threshold = usable.first().map(|s| s.threshold).unwrap_or(0);
+    let encoding = usable.first().map(|s| s.encoding).unwrap_or_default();
+    let set_id = usable
+        .first()
+        .and_then(|s| s.set_id)
+        .or_else(|| parity.first().and_then(|p| p.set_id));
+
+    let mut repaired = Vec::with_capacity(missing.len());
+    for index in missing {
+        let mut data = Vec::with_capacity(secret_len);
+        for byte_idx in 0..secret_len {
+            let mut points: Vec<(u8, u8)> =
+                usable.iter().map(|s| (s.index, s.data[byte_idx])).collect();
+            points.extend(parity.iter().map(|p| (p.index, p.data[byte_idx])));
+            data.push(lagrange_interpolate(&points, index));
+        }
+        repaired.push(ShamirShare {
+            version: CURRENT_SHARE_VERSION,
+            index,
+            threshold,
+            total,
+            checksum: Some(checksum_of(&data)),
+            data,
+            set_id,
+            encryption: None,
+            label: None,
+            note: None,
+            group_id: None,
+            group_threshold: None,
+            group_count: None,
+            encoding,
+            signature: None,
+        });
+    }
+
+    let mut result = available.to_vec();
+    result.append(&mut repaired);
+    Ok(result)
+}
+
+/// How close a set of shares is to reconstructing their secret, as
+/// reported by `analyze_shares`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareAnalysis {
+    pub threshold: u8,
+    pub total: u8,
+    pub present_ids: Vec<u8>,
+    pub missing_count: u8,
+    pub reconstructable: bool,
+}
+
+/// Confirm `shares` are structurally consistent and report how close they
+/// are to reconstructing their secret, without needing the password for
+/// any share `encrypt_share` encrypted. Stops short of actually
+/// reconstructing, which needs every share's plaintext `data`.
+pub fn analyze_shares(shares: &[ShamirShare]) -> Result<ShareAnalysis> {
+    if shares.is_empty() {
+        return Err(QRCryptError::Shamir("no shares provided".to_string()));
+    }
+
+    verify_set_consistency(shares)?;
+
+    let threshold = shares[0].threshold;
+    let total = shares[0].total;
+    for share in shares {
+        if share.threshold != threshold || share.total != total {
+            return Err(QRCryptError::Shamir(format!(
+                "share {} has a different threshold/total than the others",
+                share.index
+            )));
+        }
+        if share.encryption.is_none() {
+            share.verify_checksum()?;
+        }
+    }
+
+    let mut present_ids: Vec<u8> = shares.iter().map(|s| s.index).collect();
+    present_ids.sort_unstable();
+    present_ids.dedup();
+
+    let missing_count = threshold.saturating_sub(present_ids.len() as u8);
+    Ok(ShareAnalysis {
+        threshold,
+        total,
+        present_ids,
+        missing_count,
+        reconstructable: missing_count == 0,
+    })
+}
+
+/// Confirm `shares` are structurally consistent and there are enough of them
+/// to reconstruct their secret. A thin wrapper around `analyze_shares` for
+/// callers that only care about pass/fail.
+pub fn validate_shares(shares: &[ShamirShare]) -> Result<()> {
+    let analysis = analyze_shares(shares)?;
+    if !analysis.reconstructable {
+        return Err(QRCryptError::Shamir(format!(
+            "need at least {} shares, got {}",
+            analysis.threshold,
+            analysis.present_ids.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Like `validate_shares`, but for a grouped split: confirm every share's
+/// checksum and that each group is internally consistent, then check that
+/// enough groups already have enough of their own shares -- without
+/// actually reconstructing.
+pub fn validate_grouped_shares(shares: &[ShamirShare]) -> Result<()> {
+    if shares.is_empty() {
+        return Err(QRCryptError::Shamir("no shares provided".to_string()));
+    }
+    for share in shares {
+        if share.encryption.is_none() {
+            share.verify_checksum()?;
+        }
+    }
+    verify_grouped_set_consistency(shares)?;
+
+    let progress = group_progress(shares)?;
+    if !progress.satisfied() {
+        let missing: Vec<String> = progress
+            .incomplete_groups
+            .iter()
+            .map(|g| format!("group {} ({}/{})", g.group_id, g.have, g.need))
+            .collect();
+        return Err(QRCryptError::Shamir(format!(
+            "need {} of {} groups complete, only {} are; still incomplete: {}",
+            progress.groups_required,
+            progress.group_count,
+            progress.complete_groups.len(),
+            missing.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Number of trailing checksum bytes `encode_share_words`/`decode_share_words`
+/// append to catch a mistyped or dropped word, beyond the integrity already
+/// provided by `ShamirShare::checksum`, which only covers `data`.
+const WORD_CHECKSUM_BYTES: usize = 2;
+
+/// Packed byte length up to and including `data_len`, before the variable
+/// `data` bytes that follow.
+const WORD_FIXED_PREFIX_BYTES: usize = 16;
+
+/// Word-encoded shares are padded to a multiple of this many bytes (= 4
+/// ten-bit words exactly, since `lcm(8, 10) / 8 == 5`), so the word count
+/// alone tells a decoder exactly how many bytes it decoded -- no partial
+/// word or leftover-bit bookkeeping needed.
+const WORD_BYTE_ALIGNMENT: usize = 5;
+
+/// Flatten the fields needed to reconstruct from a (flat, non-grouped)
+/// `ShamirShare` into a byte string, for `encode_share_words`. `label` and
+/// `note` are left out as purely cosmetic; grouped shares aren't supported
+/// since there's no SLIP-39-style hardware-wallet precedent to match here.
+fn pack_share_for_words(share: &ShamirShare) -> Result<Vec<u8>> {
+    if share.group_id.is_some() {
+        return Err(QRCryptError::Shamir(
+            "word encoding doesn't support grouped shares".to_string(),
+        ));
+    }
+    if share.encryption.is_some() {
+        return Err(QRCryptError::Shamir(
+            "word encoding doesn't support password-encrypted shares".to_string(),
+        ));
+    }
+    if share.encoding != ShareEncoding::Gf256 {
+        return Err(QRCryptError::Shamir(
+            "word encoding only supports the default gf256 share encoding".to_string(),
+        ));
+    }
+    if share.data.len() > u16::MAX as usize {
+        return Err(QRCryptError::Shamir(
+            "share data is too large to encode as words".to_string(),
+        ));
+    }
+
+    let mut body = Vec::with_capacity(WORD_FIXED_PREFIX_BYTES + share.data.len());
+    body.push(share.version);
+    body.push(share.index);
+    body.push(share.threshold);
+    body.push(share.total);
+    body.push(share.checksum.is_some() as u8);
+    body.extend_from_slice(&share.checksum.unwrap_or(0).to_be_bytes());
+    body.push(share.set_id.is_some() as u8);
+    body.extend_from_slice(&share.set_id.unwrap_or(0).to_be_bytes());
+    body.extend_from_slice(&(share.data.len() as u16).to_be_bytes());
+    body.extend_from_slice(&share.data);
+    Ok(body)
+}
+
+/// Reverse `pack_share_for_words`.
+fn unpack_share_from_words(body: &[u8]) -> Result<ShamirShare> {
+    if body.len() < WORD_FIXED_PREFIX_BYTES {
+        return Err(QRCryptError::Shamir(
+            "word share is too short to be valid".to_string(),
+        ));
+    }
+    let data_len = u16::from_be_bytes([body[14], body[15]]) as usize;
+    if body.len() < WORD_FIXED_PREFIX_BYTES + data_len {
+        return Err(QRCryptError::Shamir(
+            "word share is missing data bytes".to_string(),
+        ));
+    }
+    Ok(ShamirShare {
+        version: body[0],
+        index: body[1],
+        threshold: body[2],
+        total: body[3],
+        data: body[WORD_FIXED_PREFIX_BYTES..WORD_FIXED_PREFIX_BYTES + data_len].to_vec(),
+        checksum: (body[4] == 1).then(|| u32::from_be_bytes(body[5..9].try_into().unwrap())),
+        set_id: (body[9] == 1).then(|| u32::from_be_bytes(body[10..14].try_into().unwrap())),
+        encryption: None,
+        label: None,
+        note: None,
+        group_id: None,
+        group_threshold: None,
+        group_count: None,
+        encoding: ShareEncoding::Gf256,
+        signature: None,
+    })
+}
+
+/// Spell a (flat) `ShamirShare` out as a sequence of words from the same
+/// 1024-word list SLIP-39 mnemonics use, for manual transcription when a QR
+/// code or JSON file isn't readable. Unlike SLIP-39, this directly encodes
+/// QRCrypt's own share fields rather than a separate wire format -- it's
+/// the existing `ShamirShare` spelled out in words instead of base64.
+pub fn encode_share_words(share: &ShamirShare) -> Result<Vec<String>> {
+    let body = pack_share_for_words(share)?;
+    let checksum = checksum_of(&body);
+
+    let mut payload = body;
+    payload.extend_from_slice(&checksum.to_be_bytes()[..WORD_CHECKSUM_BYTES]);
+    let pad = (WORD_BYTE_ALIGNMENT - payload.len() % WORD_BYTE_ALIGNMENT) % WORD_BYTE_ALIGNMENT;
+    payload.extend(std::iter::repeat_n(0u8, pad));
+
+    let wordlist = crate::slip39::wordlist();
+    let mut bits = Vec::with_capacity(payload.len() * 8);
+    for byte in &payload {
+        for i in (0..8).rev() {
+            bits.push((byte >> i) & 1 == 1);
+        }
+    }
+    Ok(bits
+        .chunks(10)
+        .map(|chunk| {
+            let index = chunk.iter().fold(0u16, |acc, &bit| (acc << 1) | bit as u16);
+            wordlist[index as usize].to_string()
+        })
+        .collect())
+}
+
+/// Reverse `encode_share_words`, rejecting a word that isn't in the list or
+/// a checksum mismatch (most likely a mistyped or dropped word) before
+/// handing back a usable `ShamirShare`.
+pub fn decode_share_words(words: &[String]) -> Result<ShamirShare> {
+    if !words.len().is_multiple_of(4) {
+        return Err(QRCryptError::Shamir(format!(
+            "a word share's length must be a multiple of 4 words, got {}",
+            words.len()
+        )));
+    }
+
+    let mut bits = Vec::with_capacity(words.len() * 10);
+    for word in words {
+        let index = crate::slip39::word_index(word.trim().to_lowercase().as_str())
+            .ok_or_else(|| QRCryptError::Shamir(format!("\"{word}\" is not a share word")))?;
+        for i in (0..10).rev() {
+            bits.push((index >> i) & 1 == 1);
+        }
+    }
+
+    let payload: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0u8, |acc, &bit| (acc << 1) | bit as u8))
+        .collect();
+
+    if payload.len() < WORD_FIXED_PREFIX_BYTES + WORD_CHECKSUM_BYTES {
+        return Err(QRCryptError::Shamir("word share is too short".to_string()));
+    }
+    let data_len = u16::from_be_bytes([payload[14], payload[15]]) as usize;
+    let body_len = WORD_FIXED_PREFIX_BYTES + data_len;
+    if payload.len() < body_len + WORD_CHECKSUM_BYTES {
+        return Err(QRCryptError::Shamir(
+            "word share failed its checksum; check for a mistyped or missing word".to_string(),
+        ));
+    }
+
+    let body = &payload[..body_len];
+    let expected = checksum_of(body).to_be_bytes();
+    if payload[body_len..body_len + WORD_CHECKSUM_BYTES] != expected[..WORD_CHECKSUM_BYTES] {
+        return Err(QRCryptError::Shamir(
+            "word share failed its checksum; check for a mistyped or missing word".to_string(),
+        ));
+    }
+
+    unpack_share_from_words(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_reconstruct_round_trip() {
+        let secret = b"my secret seed phrase".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        let recovered = reconstruct_secret(&shares[0..3]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn any_threshold_subset_reconstructs() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 2, 4).unwrap();
+
+        let recovered = reconstruct_secret(&[shares[1].clone(), shares[3].clone()]).unwrap();
+        assert_eq!(recovered.as_str(), Some("abc"));
+    }
+
+    #[test]
+    fn too_few_shares_errors() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        assert!(reconstruct_secret(&shares[0..2]).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_threshold() {
+        assert!(split_secret(b"abc", 1, 5).is_err());
+        assert!(split_secret(b"abc", 6, 5).is_err());
+    }
+
+    #[test]
+    fn total_at_the_gf256_fields_maximum_of_255_shares_still_round_trips() {
+        // 255 is the largest total split_secret (or any of its GF(256)
+        // siblings) can ever be asked for -- u8 itself caps it there, which
+        // happens to be exactly how many nonzero points GF(256) has.
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 2, 255).unwrap();
+        assert_eq!(shares.len(), 255);
+
+        let recovered = reconstruct_secret(&[shares[0].clone(), shares[254].clone()]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn threshold_equal_to_total_still_splits_with_no_redundancy() {
+        // No redundancy (every share is required) is a legitimate, if risky,
+        // choice -- split_secret only warns about it, it doesn't reject it.
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 3, 3).unwrap();
+        let recovered = reconstruct_secret(&shares).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn threshold_of_one_via_a_single_group_still_splits() {
+        // split_secret itself rejects threshold < 2, but a 1-of-N group
+        // legitimately needs threshold == 1 for its inner split; make sure
+        // the weak-parameter warning doesn't turn into a rejection there.
+        let secret = b"abc".to_vec();
+        let shares = split_secret_with_groups(&secret, &[(1, 3)], 1).unwrap();
+        assert_eq!(shares.len(), 3);
+        let recovered = reconstruct_grouped_secret(&shares[0..1]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn split_with_ids_uses_the_given_ids_instead_of_1_through_total() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret_with_ids(&secret, 2, &[5, 12, 200]).unwrap();
+        let indices: Vec<u8> = shares.iter().map(|s| s.index).collect();
+        assert_eq!(indices, vec![5, 12, 200]);
+        assert!(shares.iter().all(|s| s.total == 3));
+
+        let recovered = reconstruct_secret(&[shares[0].clone(), shares[2].clone()]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn split_with_ids_rejects_zero_duplicate_or_too_few_ids() {
+        assert!(split_secret_with_ids(b"abc", 2, &[0, 1, 2]).is_err());
+        assert!(split_secret_with_ids(b"abc", 2, &[1, 1, 2]).is_err());
+        assert!(split_secret_with_ids(b"abc", 3, &[1, 2]).is_err());
+    }
+
+    #[test]
+    fn gf65536_encoding_round_trips_and_rejects_odd_length_secrets() {
+        let secret = b"abcd".to_vec();
+        let shares = split_secret_with_encoding(&secret, 2, 4, ShareEncoding::Gf65536).unwrap();
+        assert!(shares.iter().all(|s| s.encoding == ShareEncoding::Gf65536));
+
+        let recovered = reconstruct_secret(&shares[0..2]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+
+        assert!(split_secret_with_encoding(b"odd", 2, 3, ShareEncoding::Gf65536).is_err());
+    }
+
+    #[test]
+    fn shares_with_different_encodings_cannot_be_combined() {
+        let gf256 = split_secret(b"abcd", 2, 2).unwrap();
+        let mut gf65536 =
+            split_secret_with_encoding(b"abcd", 2, 2, ShareEncoding::Gf65536).unwrap();
+        let mixed = vec![gf256[0].clone(), gf65536.remove(0)];
+        assert!(reconstruct_secret(&mixed).is_err());
+    }
+
+    #[test]
+    fn shares_from_an_incompatible_future_version_are_rejected_by_index() {
+        let mut shares = split_secret(b"abcd", 2, 2).unwrap();
+        shares[1].version = 99;
+        let err = reconstruct_secret(&shares).unwrap_err().to_string();
+        assert!(
+            err.contains(&shares[1].index.to_string()),
+            "error did not name the incompatible share: {err}"
+        );
+    }
+
+    #[test]
+    fn gf65536_shares_cannot_be_word_encoded() {
+        let shares = split_secret_with_encoding(b"abcd", 2, 2, ShareEncoding::Gf65536).unwrap();
+        assert!(encode_share_words(&shares[0]).is_err());
+    }
+
+    #[test]
+    fn v2_share_for_a_24_word_phrase_stays_well_under_a_kilobyte() {
+        let phrase = "abandon ability able about above absent absorb abstract absurd abuse access accident \
+                      account accuse achieve acid acoustic acquire across act action actor actress actual";
+        let shares = split_secret(phrase.as_bytes(), 3, 5).unwrap();
+        let json = serde_json::to_string(&shares[0]).unwrap();
+        assert!(
+            json.len() < 400,
+            "v2 share JSON was {} bytes: {json}",
+            json.len()
+        );
+    }
+
+    #[test]
+    fn reconstructs_from_a_mix_of_v1_and_v2_shares() {
+        let secret = b"abc".to_vec();
+        let mut shares = split_secret(&secret, 2, 2).unwrap();
+
+        // Re-encode one share the old way: a plain JSON array of numbers
+        // instead of base64, with no `version` field at all.
+        let legacy_json = format!(
+            r#"{{"index":{},"threshold":{},"total":{},"data":{:?}}}"#,
+            shares[0].index, shares[0].threshold, shares[0].total, shares[0].data
+        );
+        let legacy_share: ShamirShare = serde_json::from_str(&legacy_json).unwrap();
+        assert_eq!(legacy_share.version, 1);
+        shares[0] = legacy_share;
+
+        let recovered = reconstruct_secret(&shares).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn damaged_share_is_reported_by_index_instead_of_corrupting_the_result() {
+        let secret = b"my secret seed phrase".to_vec();
+        let mut shares = split_secret(&secret, 3, 5).unwrap();
+        shares[1].data[0] ^= 0xff;
+
+        let err = reconstruct_secret(&shares[0..3]).unwrap_err().to_string();
+        assert!(
+            err.contains(&shares[1].index.to_string()),
+            "error did not name the damaged share: {err}"
+        );
+    }
+
+    #[test]
+    fn shares_from_different_splits_are_rejected_by_index() {
+        let mut shares_a = split_secret(b"wallet a seed", 2, 2).unwrap();
+        let shares_b = split_secret(b"wallet b seed", 2, 2).unwrap();
+        shares_a[1] = shares_b[1].clone();
+
+        let err = reconstruct_secret(&shares_a).unwrap_err().to_string();
+        assert!(
+            err.contains(&shares_a[1].index.to_string()),
+            "error did not name the mismatched share: {err}"
+        );
+    }
+
+    #[test]
+    fn duplicate_index_shares_are_rejected_instead_of_reconstructing_garbage() {
+        let mut shares = split_secret(b"wallet seed", 2, 3).unwrap();
+        shares[1] = shares[0].clone();
+
+        let err = reconstruct_secret(&shares[..2]).unwrap_err().to_string();
+        assert!(
+            err.contains(&shares[0].index.to_string()),
+            "error did not name the duplicated share: {err}"
+        );
+    }
+
+    #[test]
+    fn round_trips_arbitrary_binary_data() {
+        let secret: Vec<u8> = (0u8..=255).chain(0u8..=255).collect();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let recovered = reconstruct_secret(&shares[1..4]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+        assert!(
+            recovered.as_str().is_none(),
+            "0x00 and high bytes aren't valid UTF-8"
+        );
+    }
+
+    #[test]
+    fn shares_without_a_set_id_are_accepted() {
+        let secret = b"abc".to_vec();
+        let mut shares = split_secret(&secret, 2, 2).unwrap();
+        shares[0].set_id = None;
+        shares[1].set_id = None;
+
+        let recovered = reconstruct_secret(&shares).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn encrypted_share_round_trips_through_encrypt_and_decrypt() {
+        let secret = b"my secret seed phrase".to_vec();
+        let mut shares = split_secret(&secret, 3, 5).unwrap();
+        let original_data = shares[0].data.clone();
+
+        encrypt_share(&mut shares[0], "correct horse").unwrap();
+        assert!(shares[0].data.is_empty());
+        assert!(shares[0].encryption.is_some());
+
+        decrypt_share(&mut shares[0], "correct horse").unwrap();
+        assert_eq!(shares[0].data, original_data);
+        assert!(shares[0].encryption.is_none());
+
+        let recovered = reconstruct_secret(&shares[0..3]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn password_split_secret_round_trips_and_the_wrong_password_fails() {
+        let secret = b"two-factor recovery secret".to_vec();
+        let ciphertext = encrypt_split_secret(&secret, "split password").unwrap();
+
+        let shares = split_secret(&ciphertext, 2, 3).unwrap();
+        let reconstructed = reconstruct_secret(&shares[0..2]).unwrap();
+
+        let recovered = decrypt_split_secret(reconstructed.as_bytes(), "split password").unwrap();
+        assert_eq!(recovered.as_slice(), secret.as_slice());
+
+        assert!(decrypt_split_secret(reconstructed.as_bytes(), "wrong password").is_err());
+    }
+
+    #[test]
+    fn ssss_format_round_trips_through_format_and_parse() {
+        let secret = b"ssss format round trip secret".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let lines: Vec<String> = shares.iter().map(format_ssss_share).collect();
+        assert_eq!(lines[0], format!("1-{}", hex::encode(&shares[0].data)));
+
+        let recovered_shares: Vec<ShamirShare> = lines[0..3]
+            .iter()
+            .map(|line| parse_ssss_share(line, 3, 5).unwrap())
+            .collect();
+        let recovered = reconstruct_secret(&recovered_shares).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn parse_ssss_share_rejects_malformed_lines() {
+        assert!(parse_ssss_share("not-a-share-line-missing-dash", 2, 3).is_err());
+        assert!(parse_ssss_share("x-a1b2", 2, 3).is_err());
+        assert!(parse_ssss_share("1-not-hex", 2, 3).is_err());
+    }
+
+    #[test]
+    fn stealth_encrypted_share_round_trips_and_still_reconstructs() {
+        let secret = b"stealth share test secret".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let encrypted: Vec<EncryptedData> = shares
+            .iter()
+            .map(|share| stealth_encrypt_share(share, "metadata password").unwrap())
+            .collect();
+
+        let recovered_shares: Vec<ShamirShare> = encrypted
+            .iter()
+            .take(3)
+            .map(|enc| stealth_decrypt_share(enc, "metadata password").unwrap())
+            .collect();
+
+        let recovered = reconstruct_secret(&recovered_shares).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn stealth_decrypt_share_fails_with_the_wrong_password() {
+        let shares = split_secret(b"stealth wrong password test", 2, 3).unwrap();
+        let encrypted = stealth_encrypt_share(&shares[0], "correct password").unwrap();
+        assert!(stealth_decrypt_share(&encrypted, "wrong password").is_err());
+    }
+
+    #[test]
+    fn decrypt_share_with_wrong_password_fails() {
+        let secret = b"my secret seed phrase".to_vec();
+        let mut shares = split_secret(&secret, 3, 5).unwrap();
+        encrypt_share(&mut shares[0], "correct horse").unwrap();
+
+        assert!(decrypt_share(&mut shares[0], "wrong horse").is_err());
+    }
+
+    #[test]
+    fn validate_shares_checks_metadata_without_decrypting() {
+        let secret = b"abc".to_vec();
+        let mut shares = split_secret(&secret, 2, 3).unwrap();
+        encrypt_share(&mut shares[0], "a password").unwrap();
+
+        assert!(validate_shares(&shares[0..2]).is_ok());
+    }
+
+    #[test]
+    fn analyze_shares_reports_how_many_more_are_needed() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let short = analyze_shares(&shares[0..2]).unwrap();
+        assert_eq!(short.threshold, 3);
+        assert_eq!(short.total, 5);
+        assert_eq!(short.present_ids, vec![shares[0].index, shares[1].index]);
+        assert_eq!(short.missing_count, 1);
+        assert!(!short.reconstructable);
+
+        let enough = analyze_shares(&shares[0..3]).unwrap();
+        assert_eq!(enough.missing_count, 0);
+        assert!(enough.reconstructable);
+    }
+
+    #[test]
+    fn labels_and_notes_are_cosmetic_and_dont_affect_reconstruction() {
+        let secret = b"my secret seed phrase".to_vec();
+        let mut shares = split_secret(&secret, 3, 5).unwrap();
+        shares[0].label = Some("mom".to_string());
+        shares[1].note = Some("lives in the safe".to_string());
+        // shares[2] is left unlabeled, so labeled and unlabeled shares must
+        // interoperate in the same reconstruction.
+
+        let recovered = reconstruct_secret(&shares[0..3]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn parity_shares_repair_a_destroyed_share_before_reconstruction() {
+        let secret = b"my secret seed phrase".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        let parity = generate_parity_shares(&shares, 2).unwrap();
+
+        // Destroy two of the five original shares.
+        let mut available: Vec<ShamirShare> = shares.clone();
+        available.remove(4);
+        available.remove(3);
+        assert_eq!(available.len(), 3);
+
+        let repaired = repair_missing_shares(&available, &parity, 5).unwrap();
+        assert_eq!(repaired.len(), 5);
+
+        let recovered = reconstruct_secret(&repaired[0..3]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn repair_fails_without_enough_surviving_shares_and_parity() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 2, 4).unwrap();
+        let parity = generate_parity_shares(&shares, 1).unwrap();
+
+        // 4 originals + 1 parity = 5 total points, but 2 originals are
+        // destroyed, leaving only 3 -- one short of the 4 needed to repair.
+        let available = vec![shares[0].clone(), shares[1].clone()];
+        assert!(repair_missing_shares(&available, &parity, 4).is_err());
+    }
+
+    #[test]
+    fn generate_parity_shares_requires_every_original_share() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 2, 4).unwrap();
+        assert!(generate_parity_shares(&shares[0..3], 1).is_err());
+    }
+
+    #[test]
+    fn diagnose_shares_finds_nothing_wrong_with_undamaged_shares() {
+        let secret = b"my secret seed phrase".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let diagnosis = diagnose_shares(&shares).unwrap().unwrap();
+        assert!(diagnosis.consistent);
+        assert!(diagnosis.suspect_indices.is_empty());
+    }
+
+    #[test]
+    fn diagnose_shares_names_a_damaged_share_even_without_a_checksum() {
+        let secret = b"my secret seed phrase".to_vec();
+        let mut shares = split_secret(&secret, 3, 5).unwrap();
+        // Clear the checksums, as if these were pre-checksum v1 shares, so
+        // a damaged share can't just be caught by `verify_checksum`.
+        for share in shares.iter_mut() {
+            share.checksum = None;
+        }
+        let damaged_index = shares[1].index;
+        shares[1].data[0] ^= 0xff;
+
+        let diagnosis = diagnose_shares(&shares).unwrap().unwrap();
+        assert!(!diagnosis.consistent);
+        assert_eq!(diagnosis.suspect_indices, vec![damaged_index]);
+    }
+
+    #[test]
+    fn diagnose_shares_skips_cross_checking_with_only_threshold_many_shares() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 3, 3).unwrap();
+        assert!(diagnose_shares(&shares).unwrap().is_none());
+    }
+
+    #[test]
+    fn validate_shares_still_catches_mixed_splits() {
+        let mut shares_a = split_secret(b"wallet a seed", 2, 2).unwrap();
+        let shares_b = split_secret(b"wallet b seed", 2, 2).unwrap();
+        shares_a[1] = shares_b[1].clone();
+
+        assert!(validate_shares(&shares_a).is_err());
+    }
+
+    #[test]
+    fn grouped_split_reconstructs_from_any_2_of_3_family_and_1_of_2_lawyer_shares() {
+        let secret = b"my secret seed phrase".to_vec();
+        let shares = split_secret_with_groups(&secret, &[(2, 3), (1, 2)], 2).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        let family: Vec<ShamirShare> = shares
+            .iter()
+            .filter(|s| s.group_id == Some(0))
+            .take(2)
+            .cloned()
+            .collect();
+        let lawyers: Vec<ShamirShare> = shares
+            .iter()
+            .filter(|s| s.group_id == Some(1))
+            .take(1)
+            .cloned()
+            .collect();
+        let mut used = family;
+        used.extend(lawyers);
+
+        let recovered = reconstruct_grouped_secret(&used).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn grouped_reconstruct_with_more_than_groups_required_complete_still_recovers() {
+        // groups_required is 1, but shares for both groups are supplied --
+        // reconstruct_grouped_secret must drain (not just truncate) the
+        // surplus group's reconstructed secret before interpolating.
+        let secret = b"my secret seed phrase".to_vec();
+        let shares = split_secret_with_groups(&secret, &[(2, 3), (1, 2)], 1).unwrap();
+
+        let family: Vec<ShamirShare> = shares
+            .iter()
+            .filter(|s| s.group_id == Some(0))
+            .take(2)
+            .cloned()
+            .collect();
+        let lawyers: Vec<ShamirShare> = shares
+            .iter()
+            .filter(|s| s.group_id == Some(1))
+            .take(1)
+            .cloned()
+            .collect();
+        let mut used = family;
+        used.extend(lawyers);
+
+        let recovered = reconstruct_grouped_secret(&used).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn grouped_split_with_a_single_group_skips_the_outer_split() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret_with_groups(&secret, &[(2, 3)], 1).unwrap();
+        assert_eq!(shares.len(), 3);
+
+        let recovered = reconstruct_grouped_secret(&shares[0..2]).unwrap();
+        assert_eq!(recovered.as_bytes(), secret.as_slice());
+    }
+
+    #[test]
+    fn grouped_reconstruct_reports_which_groups_are_still_incomplete() {
+        let secret = b"my secret seed phrase".to_vec();
+        let shares = split_secret_with_groups(&secret, &[(2, 3), (1, 2)], 2).unwrap();
+
+        // Only one family share and no lawyer shares: the family group is
+        // short, and the lawyer group is missing entirely.
+        let used: Vec<ShamirShare> = shares
+            .into_iter()
+            .filter(|s| s.group_id == Some(0))
+            .take(1)
+            .collect();
+
+        let err = reconstruct_grouped_secret(&used).unwrap_err().to_string();
+        assert!(err.contains("group 0"), "error did not name group 0: {err}");
+    }
+
+    #[test]
+    fn group_progress_reports_complete_and_incomplete_groups() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret_with_groups(&secret, &[(2, 3), (1, 2)], 2).unwrap();
+
+        let mut used: Vec<ShamirShare> = shares
+            .iter()
+            .filter(|s| s.group_id == Some(0))
+            .take(2)
+            .cloned()
+            .collect();
+        used.push(
+            shares
+                .iter()
+                .find(|s| s.group_id == Some(1))
+                .unwrap()
+                .clone(),
+        );
+
+        let progress = group_progress(&used).unwrap();
+        assert!(progress.satisfied());
+        assert_eq!(progress.complete_groups, vec![0, 1]);
+        assert!(progress.incomplete_groups.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_group_counts() {
+        assert!(split_secret_with_groups(b"abc", &[], 1).is_err());
+        assert!(split_secret_with_groups(b"abc", &[(2, 3), (1, 2)], 0).is_err());
+        assert!(split_secret_with_groups(b"abc", &[(2, 3), (1, 2)], 3).is_err());
+    }
+
+    #[test]
+    fn word_encoded_share_round_trips() {
+        let secret = b"my secret seed phrase".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        for share in &shares {
+            let words = encode_share_words(share).unwrap();
+            let decoded = decode_share_words(&words).unwrap();
+            assert_eq!(decoded.version, share.version);
+            assert_eq!(decoded.index, share.index);
+            assert_eq!(decoded.threshold, share.threshold);
+            assert_eq!(decoded.total, share.total);
+            assert_eq!(decoded.data, share.data);
+            assert_eq!(decoded.checksum, share.checksum);
+            assert_eq!(decoded.set_id, share.set_id);
+        }
+    }
+
+    #[test]
+    fn word_encoded_shares_still_reconstruct_the_secret() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 2, 4).unwrap();
+
+        let w0 = encode_share_words(&shares[0]).unwrap();
+        let w2 = encode_share_words(&shares[2]).unwrap();
+        let recovered = reconstruct_secret(&[
+            decode_share_words(&w0).unwrap(),
+            decode_share_words(&w2).unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(recovered.as_str(), Some("abc"));
+    }
+
+    #[test]
+    fn word_count_is_always_a_multiple_of_four() {
+        for len in [0usize, 1, 10, 16, 33, 100] {
+            let share = ShamirShare {
+                version: 2,
+                index: 1,
+                threshold: 2,
+                total: 3,
+                data: vec![9; len],
+                checksum: Some(0x1234),
+                set_id: Some(0xabcd),
+                encryption: None,
+                label: None,
+                note: None,
+                group_id: None,
+                group_threshold: None,
+                group_count: None,
+                encoding: ShareEncoding::Gf256,
+                signature: None,
+            };
+            let words = encode_share_words(&share).unwrap();
+            assert!(words.len().is_multiple_of(4));
+        }
+    }
+
+    #[test]
+    fn a_single_mistyped_word_is_caught_by_the_checksum() {
+        let secret = b"another secret".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+        let mut words = encode_share_words(&shares[0]).unwrap();
+
+        let wordlist = crate::slip39::wordlist();
+        let original = words[0].clone();
+        words[0] = wordlist
+            .iter()
+            .map(|w| w.to_string())
+            .find(|w| *w != original)
+            .unwrap();
+
+        let err = decode_share_words(&words).unwrap_err().to_string();
+        assert!(err.contains("checksum"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn a_word_not_in_the_wordlist_is_rejected() {
+        let secret = b"another secret".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+        let mut words = encode_share_words(&shares[0]).unwrap();
+        words[0] = "notarealword".to_string();
+
+        let err = decode_share_words(&words).unwrap_err().to_string();
+        assert!(err.contains("not a share word"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn a_word_count_not_a_multiple_of_four_is_rejected() {
+        let secret = b"another secret".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+        let mut words = encode_share_words(&shares[0]).unwrap();
+        words.pop();
+
+        let err = decode_share_words(&words).unwrap_err().to_string();
+        assert!(err.contains("multiple of 4"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn grouped_shares_cannot_be_word_encoded() {
+        let groups = [(2u8, 3u8), (1, 2)];
+        let grouped = split_secret_with_groups(b"abc", &groups, 2).unwrap();
+        assert!(encode_share_words(&grouped[0]).is_err());
+    }
+
+    #[test]
+    fn password_encrypted_shares_cannot_be_word_encoded() {
+        let secret = b"abc".to_vec();
+        let shares = split_secret(&secret, 2, 3).unwrap();
+        let mut share = shares[0].clone();
+        encrypt_share(&mut share, "hunter2").unwrap();
+        assert!(encode_share_words(&share).is_err());
+    }
+
+    #[test]
+    fn build_verification_info_commits_to_the_secret_and_carries_the_split_shape() {
+        let secret = b"a secret worth verifying".to_vec();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let info = build_verification_info(&secret, &shares);
+
+        assert_eq!(info.set_id, shares[0].set_id.unwrap());
+        assert_eq!(info.threshold, 3);
+        assert_eq!(info.total, 5);
+        assert_eq!(info.secret_commitment, commit_secret(&secret));
+    }
+
+    #[test]
+    fn commit_secret_is_deterministic_but_sensitive_to_every_byte() {
+        assert_eq!(commit_secret(b"a secret"), commit_secret(b"a secret"));
+        assert_ne!(commit_secret(b"a secret"), commit_secret(b"a secrey"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Detached Ed25519 signatures over Shamir shares, for `split --sign-key`
+//! and `validate --verify-key`: proof a share genuinely came from whoever
+//! holds the matching secret key, not a substitute. Signing/verifying keys
+//! are plain 32-byte files -- the raw seed or public key bytes, with no
+//! PEM/PKCS8 wrapping, since nothing else qrcrypt reads needs one either.
+
+use std::path::Path;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{QRCryptError, Result};
+use crate::qr::QRData;
+use crate::shamir::ShamirShare;
+
+/// Generate a fresh Ed25519 keypair for `encrypt --sign-key`/`qrcrypt
+/// verify` or `split --sign-key`/`validate --verify-key` (both read the
+/// same raw 32-byte key format), for `qrcrypt keygen`.
+pub fn generate_signing_key() -> SigningKey {
+    let mut seed = [0u8; 32];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut seed);
+    SigningKey::from_bytes(&seed)
+}
+
+/// Read a 32-byte Ed25519 signing (secret) key from `path`, for `--sign-key`.
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let bytes = std::fs::read(path)?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| {
+        QRCryptError::InvalidFormat(format!(
+            "{} is not a 32-byte Ed25519 signing key",
+            path.display()
+        ))
+    })?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+/// Read a 32-byte Ed25519 verifying (public) key from `path`, for
+/// `--verify-key`.
+pub fn load_verifying_key(path: &Path) -> Result<VerifyingKey> {
+    let bytes = std::fs::read(path)?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| {
+        QRCryptError::InvalidFormat(format!(
+            "{} is not a 32-byte Ed25519 public key",
+            path.display()
+        ))
+    })?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| {
+        QRCryptError::InvalidFormat(format!(
+            "{} is not a valid Ed25519 public key: {e}",
+            path.display()
+        ))
+    })
+}
+
+/// A short, human-displayable stand-in for a full public key: the first 8
+/// bytes of its SHA-256 digest, hex-encoded. Purely informational --
+/// `verify_share` always checks against the full key from `--verify-key`,
+/// never this.
+pub fn key_fingerprint(verifying_key: &VerifyingKey) -> String {
+    hex::encode(&Sha256::digest(verifying_key.as_bytes())[..8])
+}
+
+/// The signature and signer fingerprint `sign_share` records on a share; see
+/// `ShamirShare::signature`. Deliberately doesn't carry the public key
+/// itself -- `verify_share` is always given one separately via
+/// `--verify-key`, so embedding it here would let a forged share simply
+/// swap in its own key and "verify" against itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareSignature {
+    pub signature: Vec<u8>,
+    pub key_fingerprint: String,
+}
+
+/// The bytes `sign_share`/`verify_share` actually sign: `share` as JSON with
+/// `signature` itself cleared first, so the signature doesn't cover its own
+/// field and the same bytes are reproduced on both sides.
+fn canonical_bytes(share: &ShamirShare) -> Vec<u8> {
+    let mut unsigned = share.clone();
+    unsigned.signature = None;
+    serde_json::to_vec(&unsigned).expect("ShamirShare always serializes")
+}
+
+/// Sign `share`'s canonical bytes with `signing_key`, recording the result
+/// (plus the signer's fingerprint) in `share.signature`.
+pub fn sign_share(share: &mut ShamirShare, signing_key: &SigningKey) {
+    let signature = signing_key.sign(&canonical_bytes(share));
+    share.signature = Some(ShareSignature {
+        signature: signature.to_bytes().to_vec(),
+        key_fingerprint: key_fingerprint(&signing_key.verifying_key()),
+    });
+}
+
+/// Confirm `share.signature` is a valid Ed25519 signature over `share`'s
+/// canonical bytes under `verifying_key`. Fails loudly -- including when
+/// `share` was never signed at all -- since a silent pass on an unsigned
+/// share would defeat the point of asking to verify one.
+pub fn verify_share(share: &ShamirShare, verifying_key: &VerifyingKey) -> Result<()> {
+    let recorded = share.signature.as_ref().ok_or_else(|| {
+        QRCryptError::Shamir(format!("share {} has no signature to verify", share.index))
+    })?;
+    let bytes: [u8; 64] = recorded.signature.clone().try_into().map_err(|_| {
+        QRCryptError::Shamir(format!("share {} has a malformed signature", share.index))
+    })?;
+    let signature = Signature::from_bytes(&bytes);
+    verifying_key
+        .verify(&canonical_bytes(share), &signature)
+        .map_err(|_| {
+            QRCryptError::Shamir(format!(
+                "share {} failed signature verification; it may not be genuine",
+                share.index
+            ))
+        })
+}
+
+/// The signature and signer fingerprint `sign_payload` records, carried in
+/// its own QR code as `QRData::PayloadSignature` alongside (not inside) the
+/// payload it covers -- see `ShareSignature` for why the signer's public
+/// key itself isn't included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadSignature {
+    pub signature: Vec<u8>,
+    pub key_fingerprint: String,
+}
+
+/// The bytes `sign_payload`/`verify_payload` actually sign: `data` as
+/// canonical JSON, the same convention `canonical_bytes` uses for a
+/// `ShamirShare`. Signing the re-serialized envelope rather than the
+/// scanned QR code's raw text means the signature still checks out
+/// whichever wire format (plain JSON or the compact CBOR+base45 encoding)
+/// the payload QR happened to use.
+fn canonical_payload_bytes(data: &QRData) -> Vec<u8> {
+    serde_json::to_vec(data).expect("QRData always serializes")
+}
+
+/// Sign `data`'s canonical bytes with `signing_key`, for `encrypt
+/// --sign-key`.
+pub fn sign_payload(data: &QRData, signing_key: &SigningKey) -> PayloadSignature {
+    let signature = signing_key.sign(&canonical_payload_bytes(data));
+    PayloadSignature {
+        signature: signature.to_bytes().to_vec(),
+        key_fingerprint: key_fingerprint(&signing_key.verifying_key()),
+    }
+}
+
+/// Confirm `signature` is a valid Ed25519 signature over `data`'s canonical
+/// bytes under `verifying_key`, for `qrcrypt verify`.
+pub fn verify_payload(
+    data: &QRData,
+    signature: &PayloadSignature,
+    verifying_key: &VerifyingKey,
+) -> Result<()> {
+    let bytes: [u8; 64] = signature
+        .signature
+        .clone()
+        .try_into()
+        .map_err(|_| QRCryptError::InvalidFormat("payload signature is malformed".to_string()))?;
+    let sig = Signature::from_bytes(&bytes);
+    verifying_key
+        .verify(&canonical_payload_bytes(data), &sig)
+        .map_err(|_| {
+            QRCryptError::InvalidFormat(
+                "payload signature is invalid; it may not be genuine".to_string(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shamir::split_secret;
+
+    fn test_signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    #[test]
+    fn a_signed_share_verifies_against_the_matching_public_key() {
+        let signing_key = test_signing_key();
+        let mut shares = split_secret(b"a secret", 2, 3).unwrap();
+        sign_share(&mut shares[0], &signing_key);
+
+        assert!(verify_share(&shares[0], &signing_key.verifying_key()).is_ok());
+    }
+
+    #[test]
+    fn a_tampered_share_fails_verification() {
+        let signing_key = test_signing_key();
+        let mut shares = split_secret(b"a secret", 2, 3).unwrap();
+        sign_share(&mut shares[0], &signing_key);
+        shares[0].label = Some("tampered".to_string());
+
+        assert!(verify_share(&shares[0], &signing_key.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn an_unsigned_share_fails_verification_instead_of_passing_silently() {
+        let shares = split_secret(b"a secret", 2, 3).unwrap();
+        let verifying_key = test_signing_key().verifying_key();
+
+        assert!(verify_share(&shares[0], &verifying_key).is_err());
+    }
+
+    #[test]
+    fn a_share_signed_by_a_different_key_fails_verification() {
+        let mut shares = split_secret(b"a secret", 2, 3).unwrap();
+        sign_share(&mut shares[0], &test_signing_key());
+
+        let other_key = SigningKey::from_bytes(&[9u8; 32]);
+        assert!(verify_share(&shares[0], &other_key.verifying_key()).is_err());
+    }
+
+    fn test_payload(ciphertext: &[u8]) -> QRData {
+        use crate::crypto::{EncryptedData, KdfParams, Layer};
+
+        QRData::Encrypted(EncryptedData {
+            hidden: Layer {
+                salt: vec![1u8; 16],
+                nonce: vec![2u8; 12],
+                ciphertext: ciphertext.to_vec(),
+                key_commitment: None,
+            },
+            decoy: None,
+            fido2_challenge: None,
+            kdf: KdfParams::Argon2id,
+            kdf_algorithm: None,
+            kdf_version: None,
+            created_at: None,
+            label: None,
+        })
+    }
+
+    #[test]
+    fn a_signed_payload_verifies_against_the_matching_public_key() {
+        let signing_key = test_signing_key();
+        let data = test_payload(b"not a real ciphertext, just a QRData to sign");
+        let signature = sign_payload(&data, &signing_key);
+
+        assert!(verify_payload(&data, &signature, &signing_key.verifying_key()).is_ok());
+    }
+
+    #[test]
+    fn a_payload_signature_does_not_verify_against_a_different_payload() {
+        let signing_key = test_signing_key();
+        let data = test_payload(b"not a real ciphertext, just a QRData to sign");
+        let signature = sign_payload(&data, &signing_key);
+
+        let other = test_payload(b"a different payload entirely");
+        assert!(verify_payload(&other, &signature, &signing_key.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn a_payload_signature_does_not_verify_against_a_different_key() {
+        let data = test_payload(b"not a real ciphertext, just a QRData to sign");
+        let signature = sign_payload(&data, &test_signing_key());
+
+        let other_key = SigningKey::from_bytes(&[9u8; 32]);
+        assert!(verify_payload(&data, &signature, &other_key.verifying_key()).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rand::RngCore;
+
+static JSON_MODE: AtomicBool = AtomicBool::new(false);
+static WIZARD_MODE: AtomicBool = AtomicBool::new(false);
+static QUIET_MODE: AtomicBool = AtomicBool::new(false);
+static VERBOSE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Switch every `print_success`/`print_info` call into a no-op so stdout
+/// carries only the single structured JSON object `main` prints at the end.
+pub fn set_json_mode(enabled: bool) {
+    JSON_MODE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_json_mode() -> bool {
+    JSON_MODE.load(Ordering::Relaxed)
+}
+
+/// Suppress `print_info`'s decorative chatter, set by `--quiet`, so scripted
+/// callers only see warnings, errors, and the actual result.
+pub fn set_quiet_mode(enabled: bool) {
+    QUIET_MODE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_quiet_mode() -> bool {
+    QUIET_MODE.load(Ordering::Relaxed)
+}
+
+/// Show `print_debug`'s otherwise-silent diagnostics, set by `--verbose`.
+pub fn set_verbose_mode(enabled: bool) {
+    VERBOSE_MODE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_verbose_mode() -> bool {
+    VERBOSE_MODE.load(Ordering::Relaxed)
+}
+
+/// Mark that the `setup` wizard already collected a secret interactively
+/// and is handing it to `read_secret` through the same `Option<String>`
+/// parameter a `--secret` flag would use, so `read_secret`'s "passing
+/// --secret on the command line" warning (accurate for a real flag, false
+/// for a hidden wizard prompt) stays suppressed for the duration.
+pub fn set_wizard_mode(enabled: bool) {
+    WIZARD_MODE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_wizard_mode() -> bool {
+    WIZARD_MODE.load(Ordering::Relaxed)
+}
+
+/// Print a success message with a checkmark.
+pub fn print_success(msg: &str) {
+    if !is_json_mode() {
+        println!("✅ {msg}");
+    }
+}
+
+/// Print an informational message.
+pub fn print_info(msg: &str) {
+    if !is_json_mode() && !is_quiet_mode() {
+        println!("ℹ️  {msg}");
+    }
+}
+
+/// Print a warning message to stderr.
+pub fn print_warning(msg: &str) {
+    eprintln!("⚠️  {msg}");
+}
+
+/// Print an error message to stderr.
+pub fn print_error(msg: &str) {
+    eprintln!("❌ {msg}");
+}
+
+/// Print a debug-level diagnostic to stderr, shown only with `--verbose`.
+/// For things like a font falling back or a scanner skipping a bad frame --
+/// useful while troubleshooting, noise otherwise.
+pub fn print_debug(msg: &str) {
+    if is_verbose_mode() {
+        eprintln!("🔎 {msg}");
+    }
+}
+
+/// Prompt the user for a password without echoing it to the terminal.
+pub fn prompt_password(prompt: &str) -> std::io::Result<String> {
+    print!("{prompt}");
+    std::io::stdout().flush()?;
+    rpassword::read_password()
+}
+
+/// Prompt the user for a single line of visible input, trimmed of its
+/// trailing newline. Unlike `prompt_password`, this echoes what's typed --
+/// for share words, which aren't secret on their own (a single share below
+/// its threshold reveals nothing about the secret).
+pub fn prompt_line(prompt: &str) -> std::io::Result<String> {
+    print!("{prompt}");
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+/// Ask the user a yes/no question, defaulting to `no` on empty input.
+pub fn confirm(prompt: &str) -> std::io::Result<bool> {
+    print!("{prompt} [y/N]: ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Run `f` (expected to block on a slow key derivation) on a worker thread
+/// while printing `message` with a spinner, clearing both once it finishes.
+/// Skipped entirely in JSON mode, where spinner frames would corrupt the
+/// single structured JSON object on stdout.
+pub fn with_kdf_progress<T: Send>(message: &str, f: impl FnOnce() -> T + Send) -> T {
+    if is_json_mode() {
+        return f();
+    }
+    std::thread::scope(|scope| {
+        let (tx, rx) = std::sync::mpsc::channel();
+        scope.spawn(move || {
+            let _ = tx.send(f());
+        });
+
+        print!("{message}");
+        let _ = std::io::stdout().flush();
+        let frames = ['|', '/', '-', '\\'];
+        let mut frame = 0;
+        let result = loop {
+            match rx.recv_timeout(std::time::Duration::from_millis(120)) {
+                Ok(result) => break result,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    print!("\r{message} {}", frames[frame % frames.len()]);
+                    let _ = std::io::stdout().flush();
+                    frame += 1;
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    unreachable!("worker thread dropped its sender without sending a result")
+                }
+            }
+        };
+        print!("\r{}\r", " ".repeat(message.len() + 2));
+        let _ = std::io::stdout().flush();
+        result
+    })
+}
+
+/// An entropy-based estimate of how hard a password is to crack.
+pub struct PasswordStrength {
+    /// zxcvbn's 0-4 strength score; below 3 is considered weak.
+    pub score: u8,
+    /// A human-readable estimate of how long an offline attacker would need.
+    pub crack_time: String,
+}
+
+impl PasswordStrength {
+    pub fn is_weak(&self) -> bool {
+        self.score < 3
+    }
+}
+
+/// Estimate how hard `password` would be to crack, using zxcvbn's pattern-based
+/// entropy estimation rather than a naive length/character-class check.
+pub fn estimate_password_strength(password: &str) -> PasswordStrength {
+    let entropy = zxcvbn::zxcvbn(password, &[]);
+    PasswordStrength {
+        score: entropy.score().into(),
+        crack_time: entropy
+            .crack_times()
+            .offline_slow_hashing_1e4_per_second()
+            .to_string(),
+    }
+}
+
+/// Warn about a weak password and, unless `allow_weak` is set, ask the user
+/// to confirm they want to continue anyway.
+pub fn check_password_strength(password: &str, allow_weak: bool) -> std::io::Result<bool> {
+    let strength = estimate_password_strength(password);
+    if !strength.is_weak() {
+        return Ok(true);
+    }
+    print_warning(&format!(
+        "this password looks weak; an offline attacker could likely crack it in {}",
+        strength.crack_time
+    ));
+    if allow_weak {
+        return Ok(true);
+    }
+    confirm("Continue with this password anyway?")
+}
+
+/// Best-effort secure deletion: overwrite `path`'s contents with random
+/// bytes before unlinking it, so a plaintext secret doesn't just drop out
+/// of the directory listing while the original bytes sit untouched on
+/// disk. This is a mitigation, not a guarantee -- on an SSD, wear-leveling
+/// can land the overwrite on a different physical block than the original
+/// write, leaving the old block intact until the drive reclaims it.
+pub fn secure_delete(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let mut remaining = len;
+    let mut buf = [0u8; 8192];
+    while remaining > 0 {
+        let chunk = remaining.min(buf.len() as u64) as usize;
+        rand::thread_rng().fill_bytes(&mut buf[..chunk]);
+        file.write_all(&buf[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secure_delete_overwrites_and_removes_the_file() {
+        let path = std::env::temp_dir()
+            .join(format!("qrcrypt-secure-delete-test-{}", std::process::id()));
+        std::fs::write(&path, b"a very secret plaintext").unwrap();
+
+        secure_delete(&path).unwrap();
+
+        assert!(!path.exists(), "file should be gone after secure_delete");
+    }
+}